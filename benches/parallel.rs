@@ -0,0 +1,92 @@
+// Compares sequential and parallel reductions for growing input sizes to find the crossover point,
+// which is the smallest input where the parallel version wins. Run with `cargo bench --bench parallel`.
+// The result is a reasonable value for `Parallel::sequential_threshold` on this machine.
+use hello_rust::parallel::Parallel;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 8] = [
+    1_000, 4_000, 16_000, 64_000, 256_000, 1_000_000, 4_000_000, 16_000_000,
+];
+
+// Best of several runs, which filters out most of the scheduling noise
+fn measure(runs: usize, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+// Speedups below this margin are treated as noise
+const MIN_SPEEDUP: f64 = 1.1;
+
+fn main() {
+    let sequential = Parallel::with_threads(1);
+    let parallel = Parallel {
+        sequential_threshold: 0,
+        ..Parallel::default()
+    };
+
+    println!("Parallel reductions with {} threads", parallel.threads);
+    if parallel.threads <= 1 {
+        println!("Only one thread is available, so the parallel version can't win here");
+    }
+    println!(
+        "{:>12} {:>8} {:>14} {:>14} {:>8}",
+        "size", "op", "sequential", "parallel", "speedup"
+    );
+
+    let names = ["max", "sum", "argmax"];
+    let mut speedups: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+
+    for size in SIZES {
+        let data: Vec<f64> = (0..size).map(|i| ((i * 7919) % 100_003) as f64).collect();
+        let runs = (50_000_000 / size).clamp(5, 200);
+
+        let cases: [&dyn Fn(&Parallel); 3] = [
+            &|p: &Parallel| {
+                black_box(p.max(black_box(&data)));
+            },
+            &|p: &Parallel| {
+                black_box(p.sum(black_box(&data)));
+            },
+            &|p: &Parallel| {
+                black_box(p.argmax(black_box(&data)));
+            },
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let sequential_time = measure(runs, || case(&sequential));
+            let parallel_time = measure(runs, || case(&parallel));
+            let speedup = sequential_time.as_secs_f64() / parallel_time.as_secs_f64();
+            speedups[i].push(speedup);
+
+            println!(
+                "{size:>12} {:>8} {:>14?} {:>14?} {speedup:>7.2}x",
+                names[i], sequential_time, parallel_time
+            );
+        }
+    }
+
+    // The crossover is the smallest size from which the parallel version wins for every larger size as well
+    for (name, speedups) in names.iter().zip(&speedups) {
+        let wins_from = speedups
+            .iter()
+            .rposition(|&speedup| speedup < MIN_SPEEDUP)
+            .map_or(0, |last_loss| last_loss + 1);
+
+        match SIZES.get(wins_from) {
+            Some(size) => {
+                println!("Crossover for {name}: parallel wins from about {size} elements")
+            }
+            None => println!(
+                "Crossover for {name}: parallel never won up to {} elements",
+                SIZES[SIZES.len() - 1]
+            ),
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod spreadsheet;
+pub mod text_stats;
+
+use spreadsheet::csv::{Options, RowReader, Writer};
+use spreadsheet::{Spreadsheet, SpreadsheetCell};
+use text_stats::TextStats;
+
 // Unlike the built-in array and tuple compound types, the data these collections point to is stored on the heap.
 pub fn collections() {
     vectors();
@@ -42,14 +49,7 @@ fn vectors() {
         println!("{i}");
     }
 
-    #[derive(Debug)]
-    enum SpreadsheetCell {
-        Int(i32),
-        Float(f64),
-        Text(String),
-    }
-
-    // Use enum to store values of different types in one vector
+    // Use enum to store values of different types in one vector, see spreadsheet.rs for SpreadsheetCell
     let row = vec![
         SpreadsheetCell::Int(3),
         SpreadsheetCell::Text(String::from("blue")),
@@ -59,6 +59,39 @@ fn vectors() {
     for c in &row {
         println!("{:?}", c);
     }
+
+    // Written as CSV and read back, the cells keep their types (except errors, which become text)
+    let mut writer = Writer::new(Vec::new(), ',');
+    writer.write_record(["count", "color", "size"]).unwrap();
+    writer
+        .write_row(&row.iter().cloned().map(Some).collect::<Vec<_>>())
+        .unwrap();
+    let csv = writer.into_inner();
+    println!("{}", String::from_utf8_lossy(&csv));
+
+    let reader = RowReader::new(csv.as_slice(), Options::default()).unwrap();
+    println!("{:?}", reader.column_types());
+    for row in reader {
+        println!("{:?}", row.unwrap());
+    }
+
+    // The same kind of cells in a grid, where formulas compute cells from other cells
+    let mut sheet = Spreadsheet::new(26, 100);
+    sheet.set("A1", "3").unwrap();
+    sheet.set("A2", "3.14").unwrap();
+    sheet.set("A3", "=SUM(A1:A2)").unwrap();
+    sheet.set("B1", "=IF(A3>5, \"big\", \"small\")").unwrap();
+    println!(
+        "A3 = {}, B1 = {}",
+        sheet.value("A3").unwrap(),
+        sheet.value("B1").unwrap()
+    );
+
+    let recalculated = sheet.set("A1", "1").unwrap();
+    println!(
+        "Recalculated {recalculated:?}, B1 = {}",
+        sheet.value("B1").unwrap()
+    );
 }
 
 // Strings are implemented as a collection of bytes, plus some methods to provide useful functionality when those bytes are interpreted as text
@@ -147,11 +180,22 @@ fn hash_maps() {
 
     // Updating a value based on the old value
     let some_text = "hello world wonderful world";
-    let mut word_count = HashMap::new();
+    println!("{:?}", word_count(some_text));
+
+    // split_whitespace keeps punctuation and case, so "World!" and "world" are different words there
+    let mut stats = TextStats::new();
+    stats.add_text("Hello world! Wonderful world.");
+    println!("{:?}", stats.top_words(2));
+    println!("{:?}", stats.flesch_reading_ease());
+}
+
+// The keys borrow from `text`, so the map can't outlive it
+pub fn word_count(text: &str) -> std::collections::HashMap<&str, i32> {
+    let mut word_count = std::collections::HashMap::new();
 
-    for word in some_text.split_whitespace() {
+    for word in text.split_whitespace() {
         let count = word_count.entry(word).or_insert(0);
         *count += 1;
     }
-    println!("{word_count:?}");
+    word_count
 }
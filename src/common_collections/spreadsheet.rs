@@ -0,0 +1,532 @@
+pub mod csv;
+pub mod formula;
+
+use formula::{Expr, Reference};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// A grid of SpreadsheetCells addressed like "A1" or "AB12", where a cell is either a value or a formula
+// (see formula.rs) computed from other cells.
+//
+// Every formula cell remembers the cells it reads, and every cell the formulas that read it. Changing a
+// cell only recalculates the cells that depend on it, directly or through others, in an order where every
+// formula comes after the cells it reads. Formulas that end up depending on themselves can't be ordered
+// like that and get #CYCLE! instead.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpreadsheetCell {
+    Int(i32),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Error(CellError),
+}
+
+impl fmt::Display for SpreadsheetCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpreadsheetCell::Int(int) => write!(f, "{int}"),
+            SpreadsheetCell::Float(float) => write!(f, "{float}"),
+            SpreadsheetCell::Text(text) => write!(f, "{text}"),
+            SpreadsheetCell::Bool(true) => write!(f, "TRUE"),
+            SpreadsheetCell::Bool(false) => write!(f, "FALSE"),
+            SpreadsheetCell::Error(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+// Formulas that can't be computed show one of these instead of a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellError {
+    DivisionByZero,
+    // A reference outside of the grid
+    Ref,
+    // An operand of the wrong type, like text in arithmetic
+    Value,
+    // An unknown function
+    Name,
+    // A number too large for a cell
+    Num,
+    Cycle,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            CellError::DivisionByZero => "#DIV/0!",
+            CellError::Ref => "#REF!",
+            CellError::Value => "#VALUE!",
+            CellError::Name => "#NAME?",
+            CellError::Num => "#NUM!",
+            CellError::Cycle => "#CYCLE!",
+        };
+        write!(f, "{text}")
+    }
+}
+
+// Ordered row by row, like the cells are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    // Both start at 0, A1 is row 0 and column 0
+    pub row: u32,
+    pub column: u32,
+}
+
+impl CellRef {
+    pub fn new(column: u32, row: u32) -> Self {
+        Self { row, column }
+    }
+
+    // All cells of the rectangle between self and `to`, row by row
+    pub fn range(self, to: CellRef) -> impl Iterator<Item = CellRef> {
+        let rows = self.row.min(to.row)..=self.row.max(to.row);
+        let columns = self.column.min(to.column)..=self.column.max(to.column);
+
+        rows.flat_map(move |row| columns.clone().map(move |column| CellRef { row, column }))
+    }
+}
+
+impl fmt::Display for CellRef {
+    // Columns are A to Z, then AA to AZ, BA and so on
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut letters = Vec::new();
+        let mut column = u64::from(self.column) + 1;
+        while column > 0 {
+            let letter = (column - 1) % 26;
+            letters.push(char::from(b'A' + letter as u8));
+            column = (column - 1) / 26;
+        }
+
+        let column: String = letters.into_iter().rev().collect();
+        write!(f, "{column}{}", u64::from(self.row) + 1)
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cell '{s}'");
+        let digits = s.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (letters, digits) = s.split_at(digits);
+
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+        // Counted from 1 like they are written, so the last column and row fit too
+        let column = letters.bytes().try_fold(0u64, |column, letter| {
+            let letter = u64::from(letter.to_ascii_uppercase() - b'A') + 1;
+            column.checked_mul(26)?.checked_add(letter)
+        });
+        let row = digits.parse::<u64>().ok().filter(|&row| row > 0);
+        let index = |number: u64| u32::try_from(number - 1).ok();
+
+        match (column.and_then(index), row.and_then(index)) {
+            (Some(column), Some(row)) => Ok(CellRef::new(column, row)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SheetError {
+    InvalidAddress(String),
+    Formula { cell: CellRef, message: String },
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::InvalidAddress(address) => {
+                write!(f, "{address} is not a cell of the sheet")
+            }
+            SheetError::Formula { cell, message } => write!(f, "Formula in {cell}: {message}"),
+        }
+    }
+}
+
+impl Error for SheetError {}
+
+struct Cell {
+    // What was typed in, like "=A1*2"
+    input: String,
+    formula: Option<Expr>,
+    value: SpreadsheetCell,
+}
+
+pub struct Spreadsheet {
+    columns: u32,
+    rows: u32,
+    cells: HashMap<CellRef, Cell>,
+    // The cells every formula cell reads
+    dependencies: HashMap<CellRef, HashSet<CellRef>>,
+    // The formula cells that read a cell
+    dependents: HashMap<CellRef, HashSet<CellRef>>,
+}
+
+impl Spreadsheet {
+    pub fn new(columns: u32, rows: u32) -> Self {
+        Self {
+            columns,
+            rows,
+            cells: HashMap::new(),
+            dependencies: HashMap::new(),
+            dependents: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, cell: CellRef) -> bool {
+        cell.column < self.columns && cell.row < self.rows
+    }
+
+    fn cell_ref(&self, address: &str) -> Result<CellRef, SheetError> {
+        address
+            .parse()
+            .ok()
+            .filter(|&cell| self.contains(cell))
+            .ok_or_else(|| SheetError::InvalidAddress(address.to_string()))
+    }
+
+    // The value of a cell, None while it's empty or for addresses outside the sheet
+    pub fn value(&self, address: &str) -> Option<&SpreadsheetCell> {
+        let cell = self.cell_ref(address).ok()?;
+        self.cells.get(&cell).map(|cell| &cell.value)
+    }
+
+    pub fn input(&self, address: &str) -> Option<&str> {
+        let cell = self.cell_ref(address).ok()?;
+        self.cells.get(&cell).map(|cell| cell.input.as_str())
+    }
+
+    // Sets a cell like typing `input` into it: "=" starts a formula, numbers and TRUE or FALSE become
+    // values of their type, everything else is text, and an empty input clears the cell.
+    // Returns every recalculated cell in the order they were computed.
+    pub fn set(&mut self, address: &str, input: &str) -> Result<Vec<CellRef>, SheetError> {
+        let cell = self.cell_ref(address)?;
+
+        let new_cell = if let Some(formula) = input.trim_start().strip_prefix('=') {
+            let formula =
+                formula::parse(formula).map_err(|message| SheetError::Formula { cell, message })?;
+            Some(Cell {
+                input: input.to_string(),
+                formula: Some(formula),
+                value: SpreadsheetCell::Int(0),
+            })
+        } else {
+            literal(input).map(|value| Cell {
+                input: input.to_string(),
+                formula: None,
+                value,
+            })
+        };
+
+        self.unlink(cell);
+        match new_cell {
+            Some(new_cell) => {
+                if let Some(formula) = &new_cell.formula {
+                    self.link(cell, formula);
+                }
+                self.cells.insert(cell, new_cell);
+            }
+            None => {
+                self.cells.remove(&cell);
+            }
+        }
+
+        Ok(self.recalculate(cell))
+    }
+
+    pub fn clear(&mut self, address: &str) -> Result<Vec<CellRef>, SheetError> {
+        self.set(address, "")
+    }
+
+    // The part of the rectangle between `from` and `to` that lies on the sheet, if any
+    fn intersect(&self, from: CellRef, to: CellRef) -> Option<(CellRef, CellRef)> {
+        let first = CellRef::new(from.column.min(to.column), from.row.min(to.row));
+        let last = CellRef::new(from.column.max(to.column), from.row.max(to.row));
+        if !self.contains(first) {
+            return None;
+        }
+
+        let last = CellRef::new(
+            last.column.min(self.columns - 1),
+            last.row.min(self.rows - 1),
+        );
+        Some((first, last))
+    }
+
+    // Records which cells `formula` in `cell` reads. References outside the sheet are left out, they are
+    // #REF! no matter what changes.
+    fn link(&mut self, cell: CellRef, formula: &Expr) {
+        let mut dependencies = HashSet::new();
+        for reference in formula.references() {
+            match reference {
+                Reference::Cell(other) if self.contains(other) => {
+                    dependencies.insert(other);
+                }
+                Reference::Cell(_) => {}
+                Reference::Range(from, to) => {
+                    if let Some((first, last)) = self.intersect(from, to) {
+                        dependencies.extend(first.range(last));
+                    }
+                }
+            }
+        }
+
+        for &other in &dependencies {
+            self.dependents.entry(other).or_default().insert(cell);
+        }
+        self.dependencies.insert(cell, dependencies);
+    }
+
+    fn unlink(&mut self, cell: CellRef) {
+        for other in self.dependencies.remove(&cell).unwrap_or_default() {
+            if let Some(dependents) = self.dependents.get_mut(&other) {
+                dependents.remove(&cell);
+                if dependents.is_empty() {
+                    self.dependents.remove(&other);
+                }
+            }
+        }
+    }
+
+    // `changed` and every cell depending on it, in no particular order
+    fn affected(&self, changed: CellRef) -> HashSet<CellRef> {
+        let mut affected = HashSet::from([changed]);
+        let mut queue = VecDeque::from([changed]);
+
+        while let Some(cell) = queue.pop_front() {
+            for &dependent in self.dependents.get(&cell).into_iter().flatten() {
+                if affected.insert(dependent) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+        affected
+    }
+
+    fn recalculate(&mut self, changed: CellRef) -> Vec<CellRef> {
+        let affected = self.affected(changed);
+
+        // How many of the cells a cell reads still need to be computed first
+        let mut waiting: BTreeMap<CellRef, usize> = affected
+            .iter()
+            .map(|cell| {
+                let dependencies = self.dependencies.get(cell).into_iter().flatten();
+                (*cell, dependencies.filter(|d| affected.contains(d)).count())
+            })
+            .collect();
+
+        let mut ready: VecDeque<CellRef> = waiting
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(cell, _)| *cell)
+            .collect();
+        let mut order = Vec::new();
+
+        while let Some(cell) = ready.pop_front() {
+            waiting.remove(&cell);
+            self.evaluate(cell);
+            order.push(cell);
+
+            for dependent in self.dependents.get(&cell).into_iter().flatten() {
+                if let Some(count) = waiting.get_mut(dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push_back(*dependent);
+                    }
+                }
+            }
+        }
+
+        // Whatever still waits is part of a cycle or depends on one
+        for (cell, _) in waiting {
+            if let Some(cell) = self.cells.get_mut(&cell) {
+                cell.value = SpreadsheetCell::Error(CellError::Cycle);
+            }
+            order.push(cell);
+        }
+        order
+    }
+
+    fn evaluate(&mut self, cell: CellRef) {
+        let Some(formula) = self.cells.get(&cell).and_then(|c| c.formula.as_ref()) else {
+            return;
+        };
+
+        let lookup = |other: CellRef| {
+            if !self.contains(other) {
+                return Some(SpreadsheetCell::Error(CellError::Ref));
+            }
+            self.cells.get(&other).map(|c| c.value.clone())
+        };
+        let value = formula::evaluate(formula, &lookup);
+
+        if let Some(cell) = self.cells.get_mut(&cell) {
+            cell.value = value;
+        }
+    }
+}
+
+fn literal(input: &str) -> Option<SpreadsheetCell> {
+    let trimmed = input.trim();
+
+    if trimmed.is_empty() {
+        None
+    } else if let Ok(int) = trimmed.parse() {
+        Some(SpreadsheetCell::Int(int))
+    } else if let Some(float) = trimmed.parse::<f64>().ok().filter(|f| f.is_finite()) {
+        Some(SpreadsheetCell::Float(float))
+    } else if trimmed.eq_ignore_ascii_case("TRUE") {
+        Some(SpreadsheetCell::Bool(true))
+    } else if trimmed.eq_ignore_ascii_case("FALSE") {
+        Some(SpreadsheetCell::Bool(false))
+    } else {
+        Some(SpreadsheetCell::Text(input.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(addresses: &[&str]) -> Vec<CellRef> {
+        addresses.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn cell_refs() {
+        for (address, column, row) in [("A1", 0, 0), ("Z10", 25, 9), ("AA1", 26, 0), ("BZ3", 77, 2)]
+        {
+            let cell: CellRef = address.parse().unwrap();
+            assert_eq!(cell, CellRef::new(column, row));
+            assert_eq!(cell.to_string(), address);
+        }
+        assert_eq!("ab12".parse(), Ok(CellRef::new(27, 11)));
+
+        let last = CellRef::new(u32::MAX, u32::MAX);
+        assert_eq!(last.to_string(), "MWLQKWV4294967296");
+        assert_eq!(last.to_string().parse(), Ok(last));
+        assert!("MWLQKWW1".parse::<CellRef>().is_err());
+        assert!("A4294967297".parse::<CellRef>().is_err());
+
+        for invalid in ["", "A", "12", "A0", "1A", "A1B", "Ä1"] {
+            assert!(invalid.parse::<CellRef>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn only_dependents_are_recalculated() {
+        let mut sheet = Spreadsheet::new(10, 10);
+        sheet.set("A1", "2").unwrap();
+        sheet.set("A2", "=A1*10").unwrap();
+        sheet.set("B1", "=A2+1").unwrap();
+        sheet.set("C1", "=SUM(A1:B1)").unwrap();
+        sheet.set("D1", "unrelated").unwrap();
+        sheet.set("D2", "=D1&\"!\"").unwrap();
+        assert_eq!(sheet.value("C1"), Some(&SpreadsheetCell::Int(23)));
+
+        // B1 needs A2 and C1 needs B1, whatever order they were entered in
+        let recalculated = sheet.set("A1", "3").unwrap();
+        assert_eq!(recalculated, cells(&["A1", "A2", "B1", "C1"]));
+        assert_eq!(sheet.value("C1"), Some(&SpreadsheetCell::Int(34)));
+        assert_eq!(
+            sheet.value("D2"),
+            Some(&SpreadsheetCell::Text(String::from("unrelated!")))
+        );
+
+        // A2 doesn't read A1 anymore
+        sheet.set("A2", "1.5").unwrap();
+        assert_eq!(sheet.set("A1", "4").unwrap(), cells(&["A1", "C1"]));
+        assert_eq!(sheet.value("C1"), Some(&SpreadsheetCell::Float(6.5)));
+
+        assert_eq!(sheet.input("B1"), Some("=A2+1"));
+        sheet.clear("A1").unwrap();
+        assert_eq!(sheet.value("A1"), None);
+        assert_eq!(sheet.value("C1"), Some(&SpreadsheetCell::Float(2.5)));
+    }
+
+    #[test]
+    fn cycles_are_marked_until_broken() {
+        let mut sheet = Spreadsheet::new(10, 10);
+        sheet.set("A1", "=B1+1").unwrap();
+        sheet.set("B1", "=C1+1").unwrap();
+        sheet.set("D1", "=A1*2").unwrap();
+        sheet.set("C1", "=A1+1").unwrap();
+
+        let cycle = SpreadsheetCell::Error(CellError::Cycle);
+        for address in ["A1", "B1", "C1", "D1"] {
+            assert_eq!(sheet.value(address), Some(&cycle), "{address}");
+        }
+
+        sheet.set("C1", "1").unwrap();
+        assert_eq!(sheet.value("A1"), Some(&SpreadsheetCell::Int(3)));
+        assert_eq!(sheet.value("D1"), Some(&SpreadsheetCell::Int(6)));
+
+        sheet.set("E1", "=E1").unwrap();
+        assert_eq!(sheet.value("E1"), Some(&cycle));
+    }
+
+    #[test]
+    fn errors_are_values() {
+        let mut sheet = Spreadsheet::new(3, 3);
+        sheet.set("A1", "0").unwrap();
+        sheet.set("A2", "=1/A1").unwrap();
+        sheet.set("A3", "=A2+1").unwrap();
+        sheet.set("B1", "=D1").unwrap();
+        sheet.set("B2", "=SUM(A1:A9)").unwrap();
+        sheet.set("B3", "=IF(ISERROR(A2), 0, 1)").unwrap();
+
+        assert_eq!(
+            sheet.value("A3"),
+            Some(&SpreadsheetCell::Error(CellError::DivisionByZero))
+        );
+        assert_eq!(
+            sheet.value("B1"),
+            Some(&SpreadsheetCell::Error(CellError::Ref))
+        );
+        assert_eq!(
+            sheet.value("B2"),
+            Some(&SpreadsheetCell::Error(CellError::DivisionByZero))
+        );
+        assert_eq!(sheet.value("B3").unwrap().to_string(), "#NAME?");
+
+        sheet.set("A1", "4").unwrap();
+        assert_eq!(sheet.value("A3"), Some(&SpreadsheetCell::Float(1.25)));
+        assert_eq!(
+            sheet.value("B2"),
+            Some(&SpreadsheetCell::Error(CellError::Ref))
+        );
+
+        assert_eq!(
+            sheet.set("D1", "1"),
+            Err(SheetError::InvalidAddress(String::from("D1")))
+        );
+        assert!(matches!(
+            sheet.set("C1", "=1+"),
+            Err(SheetError::Formula { .. })
+        ));
+        assert_eq!(sheet.value("C1"), None);
+    }
+
+    #[test]
+    fn ranges_outside_the_sheet_are_ref() {
+        let mut sheet = Spreadsheet::new(3, 3);
+        sheet.set("C3", "1").unwrap();
+        sheet.set("C1", "=SUM(D1:D3)").unwrap();
+        sheet.set("A1", "=SUM(A4:C9)").unwrap();
+
+        let error = Some(&SpreadsheetCell::Error(CellError::Ref));
+        assert_eq!(sheet.value("C1"), error);
+        assert_eq!(sheet.value("A1"), error);
+
+        // Neither range reads the cells at the edge of the sheet
+        assert_eq!(sheet.set("C3", "2").unwrap(), cells(&["C3"]));
+
+        // Only the part of a range that overlaps the sheet is linked
+        sheet.set("B1", "=SUM(C2:D4)").unwrap();
+        assert_eq!(sheet.set("C3", "3").unwrap(), cells(&["C3", "B1"]));
+    }
+}
@@ -0,0 +1,603 @@
+use crate::common_collections::spreadsheet::SpreadsheetCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, Write};
+
+// Rows of SpreadsheetCells as CSV, following RFC 4180.
+//
+// Fields containing the delimiter, quotes or line breaks are put in double quotes, with quotes inside
+// doubled. Records end with CRLF when written, and with either CRLF or LF when read. Every record needs the
+// same number of fields. Reading goes one record at a time, so files of any size can be read.
+//
+// CSV only has text, so RowReader looks at the first rows to pick a type for every column: Bool when all of
+// its values are TRUE or FALSE, Int when they are integers, Float when they are numbers, and Text otherwise.
+// Empty or blank fields are empty cells. Error cells are written like "#DIV/0!" and read back as Text.
+
+#[derive(Debug)]
+pub enum CsvError {
+    Io(io::Error),
+    // Line and column (in characters) both start at 1
+    Malformed {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io(error) => write!(f, "Problem reading the CSV: {error}"),
+            CsvError::Malformed {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Malformed CSV at line {line}, column {column}: {message}"
+            ),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Io(error) => Some(error),
+            CsvError::Malformed { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(error: io::Error) -> Self {
+        CsvError::Io(error)
+    }
+}
+
+fn malformed(line: usize, column: usize, message: impl Into<String>) -> CsvError {
+    CsvError::Malformed {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+// Reads records as plain strings
+pub struct Reader<R> {
+    reader: R,
+    delimiter: char,
+    // The current line, with its line break
+    line: String,
+    line_number: usize,
+    // How many fields every record has, known after the first one
+    fields: Option<usize>,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(reader: R, delimiter: char) -> Self {
+        assert!(
+            !matches!(delimiter, '"' | '\r' | '\n'),
+            "{delimiter:?} can't be a delimiter"
+        );
+
+        Self {
+            reader,
+            delimiter,
+            line: String::new(),
+            line_number: 0,
+            fields: None,
+        }
+    }
+
+    // False at the end of the input
+    fn read_line(&mut self) -> Result<bool, CsvError> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+
+        self.line_number += 1;
+        if self.line_number == 1 && self.line.starts_with('\u{feff}') {
+            self.line.drain(..'\u{feff}'.len_utf8());
+        }
+        Ok(true)
+    }
+
+    // None at the end of the input. Empty lines before and between records are skipped, unless records
+    // have a single field: then an empty line is a record with an empty field.
+    pub fn read_record(&mut self) -> Result<Option<Vec<String>>, CsvError> {
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            let empty = matches!(self.line.as_str(), "\n" | "\r\n");
+            if !empty || self.fields == Some(1) {
+                break;
+            }
+        }
+        let first_line = self.line_number;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut chars: Vec<char> = self.line.chars().collect();
+        let mut i = 0;
+        // Where the quote of the quoted field we're in was opened
+        let mut quoted: Option<(usize, usize)> = None;
+        // After the closing quote only the delimiter or the end of the record may follow
+        let mut closed = false;
+
+        loop {
+            let Some(&c) = chars.get(i) else {
+                let Some((line, column)) = quoted else {
+                    // The last line of the input has no line break
+                    break;
+                };
+                // A line break inside quotes, the field goes on in the next line
+                if !self.read_line()? {
+                    return Err(malformed(line, column, "Quoted field is never closed"));
+                }
+                chars = self.line.chars().collect();
+                i = 0;
+                continue;
+            };
+            let column = i + 1;
+            i += 1;
+
+            if quoted.is_some() {
+                match c {
+                    '"' if chars.get(i) == Some(&'"') => {
+                        field.push('"');
+                        i += 1;
+                    }
+                    '"' => {
+                        quoted = None;
+                        closed = true;
+                    }
+                    c => field.push(c),
+                }
+                continue;
+            }
+
+            match c {
+                '\n' => break,
+                '\r' if chars.get(i) == Some(&'\n') => break,
+                c if c == self.delimiter => {
+                    fields.push(std::mem::take(&mut field));
+                    closed = false;
+                }
+                _ if closed => {
+                    return Err(malformed(
+                        self.line_number,
+                        column,
+                        format!("Expected {:?} after the closing quote", self.delimiter),
+                    ))
+                }
+                '"' if field.is_empty() => quoted = Some((self.line_number, column)),
+                '"' => {
+                    return Err(malformed(
+                        self.line_number,
+                        column,
+                        "Quote inside a field that doesn't start with one",
+                    ))
+                }
+                c => field.push(c),
+            }
+        }
+        fields.push(field);
+
+        match self.fields {
+            None => self.fields = Some(fields.len()),
+            Some(expected) if expected != fields.len() => {
+                return Err(malformed(
+                    first_line,
+                    1,
+                    format!("Expected {expected} fields, found {}", fields.len()),
+                ))
+            }
+            Some(_) => {}
+        }
+        Ok(Some(fields))
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Vec<String>, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Bool,
+    Int,
+    Float,
+    Text,
+}
+
+impl ColumnType {
+    // Empty values fit any type, a column without values is Text
+    fn infer<'a>(values: impl Iterator<Item = &'a str>) -> ColumnType {
+        values
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(ColumnType::of)
+            .reduce(|column_type, value_type| match (column_type, value_type) {
+                (a, b) if a == b => a,
+                (ColumnType::Int | ColumnType::Float, ColumnType::Int | ColumnType::Float) => {
+                    ColumnType::Float
+                }
+                _ => ColumnType::Text,
+            })
+            .unwrap_or(ColumnType::Text)
+    }
+
+    // The narrowest type of a trimmed value
+    fn of(value: &str) -> ColumnType {
+        if bool(value).is_some() {
+            ColumnType::Bool
+        } else if value.parse::<i32>().is_ok() {
+            ColumnType::Int
+        } else if float(value).is_some() {
+            ColumnType::Float
+        } else {
+            ColumnType::Text
+        }
+    }
+
+    // A value that doesn't fit the column, because it came after the rows the type was picked from,
+    // gets the closest type it fits: Float in an Int column, or Text
+    fn cell(self, value: &str) -> Option<SpreadsheetCell> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let typed = match self {
+            ColumnType::Text => None,
+            ColumnType::Bool => bool(trimmed).map(SpreadsheetCell::Bool),
+            ColumnType::Int => trimmed
+                .parse()
+                .ok()
+                .map(SpreadsheetCell::Int)
+                .or_else(|| float(trimmed).map(SpreadsheetCell::Float)),
+            ColumnType::Float => float(trimmed).map(SpreadsheetCell::Float),
+        };
+        Some(typed.unwrap_or_else(|| SpreadsheetCell::Text(value.to_string())))
+    }
+}
+
+// "inf" and "NaN" are text, not numbers
+fn float(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|f| f.is_finite())
+}
+
+// Like the spreadsheet, in any case
+fn bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("TRUE") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("FALSE") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub delimiter: char,
+    // The first record names the columns
+    pub has_headers: bool,
+    // How many rows the column types are picked from
+    pub sample_rows: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_headers: true,
+            sample_rows: 100,
+        }
+    }
+}
+
+pub type Row = Vec<Option<SpreadsheetCell>>;
+
+// Reads rows of typed cells
+pub struct RowReader<R> {
+    records: Reader<R>,
+    headers: Vec<String>,
+    column_types: Vec<ColumnType>,
+    // The rows the types were picked from, handed out before reading on
+    sample: VecDeque<Vec<String>>,
+    // An error that ended the sample, handed out after it
+    error: Option<CsvError>,
+}
+
+impl<R: BufRead> RowReader<R> {
+    pub fn new(reader: R, options: Options) -> Result<Self, CsvError> {
+        let mut records = Reader::new(reader, options.delimiter);
+        let headers = if options.has_headers {
+            records.read_record()?.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let mut sample = VecDeque::new();
+        let mut error = None;
+        while sample.len() < options.sample_rows {
+            match records.read_record() {
+                Ok(Some(record)) => sample.push_back(record),
+                Ok(None) => break,
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let columns = sample.front().map_or(headers.len(), Vec::len);
+        let column_types = (0..columns)
+            .map(|column| ColumnType::infer(sample.iter().map(|record| record[column].as_str())))
+            .collect();
+
+        Ok(Self {
+            records,
+            headers,
+            column_types,
+            sample,
+            error,
+        })
+    }
+
+    // Empty without a header row
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    pub fn column_types(&self) -> &[ColumnType] {
+        &self.column_types
+    }
+}
+
+impl<R: BufRead> Iterator for RowReader<R> {
+    type Item = Result<Row, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.sample.pop_front() {
+            Some(record) => record,
+            None => match self.error.take() {
+                Some(error) => return Some(Err(error)),
+                None => match self.records.read_record() {
+                    Ok(record) => record?,
+                    Err(error) => return Some(Err(error)),
+                },
+            },
+        };
+
+        let row = record
+            .iter()
+            .zip(&self.column_types)
+            .map(|(value, column_type)| column_type.cell(value))
+            .collect();
+        Some(Ok(row))
+    }
+}
+
+pub struct Writer<W> {
+    writer: W,
+    delimiter: char,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W, delimiter: char) -> Self {
+        Self { writer, delimiter }
+    }
+
+    pub fn write_record<S: AsRef<str>>(
+        &mut self,
+        fields: impl IntoIterator<Item = S>,
+    ) -> io::Result<()> {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                write!(self.writer, "{}", self.delimiter)?;
+            }
+
+            let field = field.as_ref();
+            if field.contains([self.delimiter, '"', '\r', '\n']) {
+                write!(self.writer, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                write!(self.writer, "{field}")?;
+            }
+        }
+        write!(self.writer, "\r\n")
+    }
+
+    // Floats keep their decimal point, so a column of whole floats is read back as Float and not as Int
+    pub fn write_row(&mut self, row: &[Option<SpreadsheetCell>]) -> io::Result<()> {
+        self.write_record(row.iter().map(|cell| match cell {
+            None => String::new(),
+            Some(SpreadsheetCell::Float(float)) => format!("{float:?}"),
+            Some(cell) => cell.to_string(),
+        }))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str, delimiter: char) -> Result<Vec<Vec<String>>, CsvError> {
+        Reader::new(input.as_bytes(), delimiter).collect()
+    }
+
+    fn text(text: &str) -> Option<SpreadsheetCell> {
+        Some(SpreadsheetCell::Text(String::from(text)))
+    }
+
+    #[test]
+    fn quoting_and_line_breaks() {
+        let input =
+            "name;note\r\n\"Smith; J.\";\"said \"\"hi\"\"\r\ntwice\"\n\nplain;\"\"\n\"\";last";
+        assert_eq!(
+            records(input, ';').unwrap(),
+            [
+                vec!["name", "note"],
+                vec!["Smith; J.", "said \"hi\"\r\ntwice"],
+                vec!["plain", ""],
+                vec!["", "last"],
+            ]
+        );
+
+        let mut writer = Writer::new(Vec::new(), ';');
+        for record in records(input, ';').unwrap() {
+            writer.write_record(&record).unwrap();
+        }
+        let written = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(written.starts_with("name;note\r\n\"Smith; J.\";\"said \"\"hi\"\"\r\ntwice\"\r\n"));
+        assert_eq!(
+            records(&written, ';').unwrap(),
+            records(input, ';').unwrap()
+        );
+    }
+
+    #[test]
+    fn column_types_are_inferred() {
+        let input = "\u{feff}id,price,color,zip\n1,3.5,blue,01234\n2,4,,x\n3,,red,5\n";
+        let mut rows = RowReader::new(input.as_bytes(), Options::default()).unwrap();
+
+        assert_eq!(rows.headers(), ["id", "price", "color", "zip"]);
+        assert_eq!(
+            rows.column_types(),
+            [
+                ColumnType::Int,
+                ColumnType::Float,
+                ColumnType::Text,
+                ColumnType::Text
+            ]
+        );
+        assert_eq!(
+            rows.next().unwrap().unwrap(),
+            [
+                Some(SpreadsheetCell::Int(1)),
+                Some(SpreadsheetCell::Float(3.5)),
+                text("blue"),
+                text("01234"),
+            ]
+        );
+        assert_eq!(
+            rows.next().unwrap().unwrap()[..3],
+            [
+                Some(SpreadsheetCell::Int(2)),
+                Some(SpreadsheetCell::Float(4.0)),
+                None
+            ]
+        );
+        assert_eq!(rows.count(), 1);
+
+        // Rows after the sample that don't fit their column
+        let options = Options {
+            has_headers: false,
+            sample_rows: 1,
+            ..Options::default()
+        };
+        let rows: Vec<Row> = RowReader::new("1,2\n2.5,x\n".as_bytes(), options)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows[1], [Some(SpreadsheetCell::Float(2.5)), text("x")]);
+    }
+
+    #[test]
+    fn rows_round_trip() {
+        let rows: Vec<Row> = vec![
+            vec![
+                Some(SpreadsheetCell::Int(3)),
+                text("blue"),
+                Some(SpreadsheetCell::Float(3.0)),
+                Some(SpreadsheetCell::Bool(true)),
+            ],
+            vec![
+                None,
+                text("a, \"b\"\nc"),
+                Some(SpreadsheetCell::Float(-0.25)),
+                Some(SpreadsheetCell::Bool(false)),
+            ],
+        ];
+
+        let mut writer = Writer::new(Vec::new(), ',');
+        writer
+            .write_record(["count", "color", "size", "sold"])
+            .unwrap();
+        for row in &rows {
+            writer.write_row(row).unwrap();
+        }
+        let written = writer.into_inner();
+
+        let reader = RowReader::new(written.as_slice(), Options::default()).unwrap();
+        let read: Vec<Row> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, rows);
+    }
+
+    #[test]
+    fn empty_lines_are_records_with_a_single_field() {
+        assert_eq!(
+            records("name\n\nAda\r\n\r\n", ',').unwrap(),
+            [vec!["name"], vec![""], vec!["Ada"], vec![""]]
+        );
+        // Leading empty lines come before the number of fields is known
+        assert_eq!(
+            records("\nname\nAda\n", ',').unwrap(),
+            [vec!["name"], vec!["Ada"]]
+        );
+
+        let options = Options {
+            has_headers: false,
+            ..Options::default()
+        };
+        let rows: Vec<Row> = RowReader::new("true\n\nFALSE\n".as_bytes(), options)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                vec![Some(SpreadsheetCell::Bool(true))],
+                vec![None],
+                vec![Some(SpreadsheetCell::Bool(false))],
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_input_is_located() {
+        for (input, line, column) in [
+            ("a,b\n1,\"2\"x\n", 2, 6),
+            ("a,b\n1,2\"\n", 2, 4),
+            ("a,b\n1,\"2\n3\n", 2, 3),
+            ("a,b\n\n1,2,3\n", 3, 1),
+            ("a,b\n1\n", 2, 1),
+        ] {
+            match records(input, ',') {
+                Err(CsvError::Malformed {
+                    line: l, column: c, ..
+                }) => assert_eq!((l, c), (line, column), "{input:?}"),
+                other => panic!("{input:?} gave {other:?}"),
+            }
+        }
+
+        // Reading goes on after the first error
+        let mut reader = Reader::new("a,b\n1\n2,3\n".as_bytes(), ',');
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert_eq!(reader.next().unwrap().unwrap(), ["2", "3"]);
+    }
+}
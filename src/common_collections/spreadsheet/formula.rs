@@ -0,0 +1,618 @@
+use crate::common_collections::spreadsheet::{CellError, CellRef, SpreadsheetCell};
+use std::cmp::Ordering;
+
+// Formulas like "=A1+B2*2" or "=IF(A1>0, SUM(B1:B10), 0)".
+//
+// Operators from lowest to highest precedence: comparisons (= <> < <= > >=), & (joins text), + -, * /,
+// unary minus. Functions: SUM, AVERAGE, MIN, MAX, COUNT and IF. Empty cells count as 0 in arithmetic and
+// are skipped by the functions. Errors in cells a formula reads become the formula's value.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i32),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Ref(CellRef),
+    // Only allowed as a function argument
+    Range(CellRef, CellRef),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Concat,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+// A cell or range a formula reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference {
+    Cell(CellRef),
+    Range(CellRef, CellRef),
+}
+
+impl Expr {
+    pub fn references(&self) -> Vec<Reference> {
+        let mut references = Vec::new();
+        self.collect_references(&mut references);
+        references
+    }
+
+    fn collect_references(&self, references: &mut Vec<Reference>) {
+        match self {
+            Expr::Ref(cell) => references.push(Reference::Cell(*cell)),
+            Expr::Range(from, to) => references.push(Reference::Range(*from, *to)),
+            Expr::Negate(operand) => operand.collect_references(references),
+            Expr::Binary(_, left, right) => {
+                left.collect_references(references);
+                right.collect_references(references);
+            }
+            Expr::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.collect_references(references);
+                }
+            }
+            Expr::Int(_) | Expr::Float(_) | Expr::Text(_) | Expr::Bool(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Text(String),
+    Name(String),
+    Operator(&'static str),
+    LeftParen,
+    RightParen,
+    Comma,
+    Colon,
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                tokens.push(Token::Number(number));
+            }
+            'A'..='Z' | 'a'..='z' | '_' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                tokens.push(Token::Name(name.to_ascii_uppercase()));
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // "" inside a text is a quote
+                        Some('"') if chars.next_if_eq(&'"').is_some() => text.push('"'),
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err(String::from("Unterminated text")),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            _ => {
+                chars.next();
+                let token = match c {
+                    '(' => Token::LeftParen,
+                    ')' => Token::RightParen,
+                    ',' => Token::Comma,
+                    ':' => Token::Colon,
+                    '+' => Token::Operator("+"),
+                    '-' => Token::Operator("-"),
+                    '*' => Token::Operator("*"),
+                    '/' => Token::Operator("/"),
+                    '&' => Token::Operator("&"),
+                    '=' => Token::Operator("="),
+                    '<' if chars.next_if_eq(&'=').is_some() => Token::Operator("<="),
+                    '<' if chars.next_if_eq(&'>').is_some() => Token::Operator("<>"),
+                    '<' => Token::Operator("<"),
+                    '>' if chars.next_if_eq(&'=').is_some() => Token::Operator(">="),
+                    '>' => Token::Operator(">"),
+                    _ => return Err(format!("Unexpected '{c}'")),
+                };
+                tokens.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+// Parses the formula without its leading '='
+pub fn parse(formula: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(formula)?,
+        position: 0,
+    };
+
+    let expr = parser.comparison()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("Unexpected {token:?}")),
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected:?}, found {token:?}")),
+            None => Err(format!("Expected {expected:?}")),
+        }
+    }
+
+    // The next token if it is one of the operators
+    fn operator(&mut self, operators: &[(&str, Operator)]) -> Option<Operator> {
+        let Some(Token::Operator(symbol)) = self.peek() else {
+            return None;
+        };
+        let (_, operator) = operators.iter().find(|(s, _)| s == symbol)?;
+
+        self.position += 1;
+        Some(*operator)
+    }
+
+    // One precedence level of left-associative binary operators
+    fn binary(
+        &mut self,
+        operators: &[(&str, Operator)],
+        operand: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut left = operand(self)?;
+
+        while let Some(operator) = self.operator(operators) {
+            let right = operand(self)?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[
+                ("=", Operator::Equal),
+                ("<>", Operator::NotEqual),
+                ("<", Operator::Less),
+                ("<=", Operator::LessOrEqual),
+                (">", Operator::Greater),
+                (">=", Operator::GreaterOrEqual),
+            ],
+            Self::concat,
+        )
+    }
+
+    fn concat(&mut self) -> Result<Expr, String> {
+        self.binary(&[("&", Operator::Concat)], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[("+", Operator::Add), ("-", Operator::Subtract)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[("*", Operator::Multiply), ("/", Operator::Divide)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.operator(&[("-", Operator::Subtract), ("+", Operator::Add)]) {
+            Some(Operator::Subtract) => Ok(Expr::Negate(Box::new(self.unary()?))),
+            Some(_) => self.unary(),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => {
+                if let Ok(int) = number.parse() {
+                    Ok(Expr::Int(int))
+                } else {
+                    number
+                        .parse()
+                        .map(Expr::Float)
+                        .map_err(|_| format!("Invalid number '{number}'"))
+                }
+            }
+            Some(Token::Text(text)) => Ok(Expr::Text(text)),
+            Some(Token::LeftParen) => {
+                let expr = self.comparison()?;
+                self.expect(Token::RightParen)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::LeftParen) => {
+                self.position += 1;
+                let arguments = self.arguments()?;
+
+                if name == "IF" && !(2..=3).contains(&arguments.len()) {
+                    return Err(String::from("IF needs 2 or 3 arguments"));
+                }
+                Ok(Expr::Call(name, arguments))
+            }
+            Some(Token::Name(name)) if name == "TRUE" => Ok(Expr::Bool(true)),
+            Some(Token::Name(name)) if name == "FALSE" => Ok(Expr::Bool(false)),
+            Some(Token::Name(name)) => {
+                let from: CellRef = name.parse()?;
+
+                if self.peek() != Some(&Token::Colon) {
+                    return Ok(Expr::Ref(from));
+                }
+                self.position += 1;
+                match self.next() {
+                    Some(Token::Name(name)) => Ok(Expr::Range(from, name.parse()?)),
+                    _ => Err(String::from("Expected the end of the range")),
+                }
+            }
+            Some(token) => Err(format!("Unexpected {token:?}")),
+            None => Err(String::from("Unexpected end of formula")),
+        }
+    }
+
+    // After the opening parenthesis
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        let mut arguments = Vec::new();
+        if self.peek() == Some(&Token::RightParen) {
+            self.position += 1;
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.comparison()?);
+            match self.next() {
+                Some(Token::Comma) => {}
+                Some(Token::RightParen) => return Ok(arguments),
+                _ => return Err(String::from("Expected ',' or ')'")),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Number {
+    Int(i32),
+    Float(f64),
+}
+
+impl Number {
+    fn float(self) -> f64 {
+        match self {
+            Number::Int(int) => f64::from(int),
+            Number::Float(float) => float,
+        }
+    }
+
+    fn cell(self) -> SpreadsheetCell {
+        match self {
+            Number::Int(int) => SpreadsheetCell::Int(int),
+            Number::Float(float) if !float.is_finite() => SpreadsheetCell::Error(CellError::Num),
+            Number::Float(float) => SpreadsheetCell::Float(float),
+        }
+    }
+}
+
+// Integer arithmetic while the result fits into an i32, floating point otherwise
+fn arithmetic(operator: Operator, a: Number, b: Number) -> Result<Number, CellError> {
+    if let (Number::Int(a), Number::Int(b)) = (a, b) {
+        let exact = match operator {
+            Operator::Add => a.checked_add(b),
+            Operator::Subtract => a.checked_sub(b),
+            Operator::Multiply => a.checked_mul(b),
+            Operator::Divide if b == 0 => return Err(CellError::DivisionByZero),
+            Operator::Divide if a.checked_rem(b) == Some(0) => a.checked_div(b),
+            _ => None,
+        };
+        if let Some(result) = exact {
+            return Ok(Number::Int(result));
+        }
+    }
+
+    let (a, b) = (a.float(), b.float());
+    Ok(Number::Float(match operator {
+        Operator::Add => a + b,
+        Operator::Subtract => a - b,
+        Operator::Multiply => a * b,
+        Operator::Divide if b == 0.0 => return Err(CellError::DivisionByZero),
+        Operator::Divide => a / b,
+        _ => unreachable!("Not an arithmetic operator"),
+    }))
+}
+
+fn number(value: Option<SpreadsheetCell>) -> Result<Number, CellError> {
+    match value {
+        None => Ok(Number::Int(0)),
+        Some(SpreadsheetCell::Int(int)) => Ok(Number::Int(int)),
+        Some(SpreadsheetCell::Float(float)) => Ok(Number::Float(float)),
+        Some(SpreadsheetCell::Bool(bool)) => Ok(Number::Int(i32::from(bool))),
+        Some(SpreadsheetCell::Text(_)) => Err(CellError::Value),
+        Some(SpreadsheetCell::Error(error)) => Err(error),
+    }
+}
+
+fn text(value: Option<SpreadsheetCell>) -> Result<String, CellError> {
+    match value {
+        None => Ok(String::new()),
+        Some(SpreadsheetCell::Error(error)) => Err(error),
+        Some(cell) => Ok(cell.to_string()),
+    }
+}
+
+fn compare(a: Option<SpreadsheetCell>, b: Option<SpreadsheetCell>) -> Result<Ordering, CellError> {
+    match (a, b) {
+        (Some(SpreadsheetCell::Error(error)), _) | (_, Some(SpreadsheetCell::Error(error))) => {
+            Err(error)
+        }
+        (Some(SpreadsheetCell::Text(a)), Some(SpreadsheetCell::Text(b))) => {
+            Ok(a.to_lowercase().cmp(&b.to_lowercase()))
+        }
+        (a, b) => {
+            let (a, b) = (number(a)?.float(), number(b)?.float());
+            a.partial_cmp(&b).ok_or(CellError::Num)
+        }
+    }
+}
+
+// Reads a cell, out of bounds references read as #REF!
+pub type Lookup<'a> = dyn Fn(CellRef) -> Option<SpreadsheetCell> + 'a;
+
+pub fn evaluate(expr: &Expr, lookup: &Lookup) -> SpreadsheetCell {
+    match scalar(expr, lookup) {
+        Ok(Some(cell)) => cell,
+        // A formula that only refers to an empty cell shows 0
+        Ok(None) => SpreadsheetCell::Int(0),
+        Err(error) => SpreadsheetCell::Error(error),
+    }
+}
+
+fn scalar(expr: &Expr, lookup: &Lookup) -> Result<Option<SpreadsheetCell>, CellError> {
+    let cell = match expr {
+        Expr::Int(int) => SpreadsheetCell::Int(*int),
+        Expr::Float(float) => SpreadsheetCell::Float(*float),
+        Expr::Text(text) => SpreadsheetCell::Text(text.clone()),
+        Expr::Bool(bool) => SpreadsheetCell::Bool(*bool),
+        Expr::Ref(cell) => match lookup(*cell) {
+            Some(SpreadsheetCell::Error(error)) => return Err(error),
+            value => return Ok(value),
+        },
+        Expr::Range(..) => return Err(CellError::Value),
+        Expr::Negate(operand) => {
+            let operand = number(scalar(operand, lookup)?)?;
+            arithmetic(Operator::Subtract, Number::Int(0), operand)?.cell()
+        }
+        Expr::Binary(Operator::Concat, left, right) => {
+            let left = text(scalar(left, lookup)?)?;
+            SpreadsheetCell::Text(left + &text(scalar(right, lookup)?)?)
+        }
+        Expr::Binary(
+            operator @ (Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide),
+            left,
+            right,
+        ) => {
+            let left = number(scalar(left, lookup)?)?;
+            let right = number(scalar(right, lookup)?)?;
+            arithmetic(*operator, left, right)?.cell()
+        }
+        Expr::Binary(operator, left, right) => {
+            let ordering = compare(scalar(left, lookup)?, scalar(right, lookup)?)?;
+            SpreadsheetCell::Bool(match operator {
+                Operator::Equal => ordering.is_eq(),
+                Operator::NotEqual => ordering.is_ne(),
+                Operator::Less => ordering.is_lt(),
+                Operator::LessOrEqual => ordering.is_le(),
+                Operator::Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        Expr::Call(name, arguments) => return call(name, arguments, lookup),
+    };
+    Ok(Some(cell))
+}
+
+// The numbers among the arguments. Text, booleans and empty cells in ranges are skipped, errors aren't.
+fn numbers(arguments: &[Expr], lookup: &Lookup) -> Result<Vec<Number>, CellError> {
+    let mut numbers = Vec::new();
+
+    for argument in arguments {
+        let Expr::Range(from, to) = argument else {
+            numbers.push(number(scalar(argument, lookup)?)?);
+            continue;
+        };
+        // Stops at the first error, before looking at the rest of a range that might not even fit the sheet
+        for cell in from.range(*to).filter_map(lookup) {
+            match cell {
+                SpreadsheetCell::Int(int) => numbers.push(Number::Int(int)),
+                SpreadsheetCell::Float(float) => numbers.push(Number::Float(float)),
+                SpreadsheetCell::Error(error) => return Err(error),
+                SpreadsheetCell::Text(_) | SpreadsheetCell::Bool(_) => {}
+            }
+        }
+    }
+
+    Ok(numbers)
+}
+
+fn sum(numbers: &[Number]) -> Result<Number, CellError> {
+    numbers
+        .iter()
+        .try_fold(Number::Int(0), |sum, &n| arithmetic(Operator::Add, sum, n))
+}
+
+fn call(
+    name: &str,
+    arguments: &[Expr],
+    lookup: &Lookup,
+) -> Result<Option<SpreadsheetCell>, CellError> {
+    let extreme = |ordering: Ordering| -> Result<Option<SpreadsheetCell>, CellError> {
+        let numbers = numbers(arguments, lookup)?;
+        let extreme = numbers.into_iter().reduce(|best, n| {
+            if n.float().partial_cmp(&best.float()) == Some(ordering) {
+                n
+            } else {
+                best
+            }
+        });
+        Ok(Some(extreme.unwrap_or(Number::Int(0)).cell()))
+    };
+
+    match name {
+        "SUM" => Ok(Some(sum(&numbers(arguments, lookup)?)?.cell())),
+        "AVERAGE" => {
+            let numbers = numbers(arguments, lookup)?;
+            let count = Number::Int(i32::try_from(numbers.len()).map_err(|_| CellError::Num)?);
+            let average = arithmetic(Operator::Divide, sum(&numbers)?, count)?;
+            Ok(Some(average.cell()))
+        }
+        "MIN" => extreme(Ordering::Less),
+        "MAX" => extreme(Ordering::Greater),
+        "COUNT" => {
+            let count = numbers(arguments, lookup)?.len();
+            Ok(Some(SpreadsheetCell::Int(
+                i32::try_from(count).map_err(|_| CellError::Num)?,
+            )))
+        }
+        // Only the chosen branch is evaluated, so an error in the other one doesn't matter
+        "IF" => {
+            // parse() checks the number of arguments, but an Expr can be built by hand too
+            let [condition, then, otherwise @ ..] = arguments else {
+                return Err(CellError::Value);
+            };
+            let condition = match scalar(condition, lookup)? {
+                Some(SpreadsheetCell::Text(_)) => return Err(CellError::Value),
+                condition => number(condition)?.float() != 0.0,
+            };
+            match (condition, otherwise.first()) {
+                (true, _) => scalar(then, lookup),
+                (false, Some(otherwise)) => scalar(otherwise, lookup),
+                (false, None) => Ok(Some(SpreadsheetCell::Bool(false))),
+            }
+        }
+        _ => Err(CellError::Name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(formula: &str) -> SpreadsheetCell {
+        // A1 = 2, A2 = 3.5, A3 = "x", everything else is empty
+        let lookup = |cell: CellRef| match cell.to_string().as_str() {
+            "A1" => Some(SpreadsheetCell::Int(2)),
+            "A2" => Some(SpreadsheetCell::Float(3.5)),
+            "A3" => Some(SpreadsheetCell::Text(String::from("x"))),
+            _ => None,
+        };
+        evaluate(&parse(formula).unwrap(), &lookup)
+    }
+
+    #[test]
+    fn precedence_and_types() {
+        assert_eq!(eval("1+2*3"), SpreadsheetCell::Int(7));
+        assert_eq!(eval("(1+2)*3"), SpreadsheetCell::Int(9));
+        assert_eq!(eval("-A1*2"), SpreadsheetCell::Int(-4));
+        assert_eq!(eval("7/2"), SpreadsheetCell::Float(3.5));
+        assert_eq!(eval("8/2"), SpreadsheetCell::Int(4));
+        assert_eq!(eval("2147483647+1"), SpreadsheetCell::Float(2147483648.0));
+        // i32::MIN / -1 doesn't fit an i32 either
+        assert_eq!(
+            eval("(-2147483647-1)/-1"),
+            SpreadsheetCell::Float(2147483648.0)
+        );
+        assert_eq!(eval("A1+A2"), SpreadsheetCell::Float(5.5));
+        assert_eq!(eval("B7+1"), SpreadsheetCell::Int(1));
+        assert_eq!(
+            eval("A3&\"y\"\"\"&A1"),
+            SpreadsheetCell::Text(String::from("xy\"2"))
+        );
+        assert_eq!(eval("A1>=2"), SpreadsheetCell::Bool(true));
+        assert_eq!(eval("\"ABC\"=\"abc\""), SpreadsheetCell::Bool(true));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(eval("SUM(A1:A5)"), SpreadsheetCell::Float(5.5));
+        assert_eq!(eval("sum(A1, 10)"), SpreadsheetCell::Int(12));
+        assert_eq!(eval("AVERAGE(A1:A3)"), SpreadsheetCell::Float(2.75));
+        assert_eq!(eval("MAX(A1:A3, -1)"), SpreadsheetCell::Float(3.5));
+        assert_eq!(eval("MIN(A1:A3, -1)"), SpreadsheetCell::Int(-1));
+        assert_eq!(eval("COUNT(A1:B9)"), SpreadsheetCell::Int(2));
+        assert_eq!(
+            eval("IF(A1>1, \"big\", 1/0)"),
+            SpreadsheetCell::Text(String::from("big"))
+        );
+        assert_eq!(eval("IF(FALSE, 1)"), SpreadsheetCell::Bool(false));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            eval("1/0"),
+            SpreadsheetCell::Error(CellError::DivisionByZero)
+        );
+        assert_eq!(
+            eval("AVERAGE(B1:B3)"),
+            SpreadsheetCell::Error(CellError::DivisionByZero)
+        );
+        assert_eq!(eval("A3+1"), SpreadsheetCell::Error(CellError::Value));
+        assert_eq!(eval("A1:A2"), SpreadsheetCell::Error(CellError::Value));
+        assert_eq!(eval("VLOOKUP(A1)"), SpreadsheetCell::Error(CellError::Name));
+        assert_eq!(
+            evaluate(&Expr::Call(String::from("IF"), Vec::new()), &|_| None),
+            SpreadsheetCell::Error(CellError::Value)
+        );
+
+        assert!(parse("1+").is_err());
+        assert!(parse("SUM(A1").is_err());
+        assert!(parse("IF(A1)").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("A1 A2").is_err());
+        assert!(parse("FOO").is_err());
+    }
+}
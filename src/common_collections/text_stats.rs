@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Word statistics like word_count() in hash_maps(), for real text.
+//
+// Words are found by the Unicode word boundary rules, so "Straße," and "l'été" are single words and
+// punctuation isn't part of any word. They are counted in lowercase, so "The" and "the" are the same word.
+// Text is read a line at a time and only counts are kept. The word counts grow with the vocabulary, which
+// grows much slower than the text, but almost every new pair or triple of words is one not seen before.
+// So the n-gram counts are limited: when there are more than the limit, the less frequent half of them is
+// dropped. Frequent n-grams survive that, but their counts can miss occurrences from before they were
+// frequent, so for large inputs top_bigrams and top_trigrams are approximate.
+//
+// Readability uses the Flesch formulas, which were made for English: syllables are estimated from groups of
+// vowels, and sentences end at '.', '!' or '?' (so "Dr." ends one too).
+
+#[derive(Debug)]
+pub struct TextStats {
+    words: u64,
+    sentences: u64,
+    syllables: u64,
+    word_counts: HashMap<String, u64>,
+    // Word pairs and triples, joined by spaces like "of the"
+    bigrams: HashMap<String, u64>,
+    trigrams: HashMap<String, u64>,
+    // How many different bigrams or trigrams are kept at most
+    ngram_limit: usize,
+    // The last two words of the current document, for the pairs and triples
+    previous: Vec<String>,
+    // Whether the current sentence has any words yet
+    in_sentence: bool,
+}
+
+impl Default for TextStats {
+    fn default() -> Self {
+        Self::with_ngram_limit(1_000_000)
+    }
+}
+
+impl TextStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ngram_limit(ngram_limit: usize) -> Self {
+        Self {
+            words: 0,
+            sentences: 0,
+            syllables: 0,
+            word_counts: HashMap::new(),
+            bigrams: HashMap::new(),
+            trigrams: HashMap::new(),
+            ngram_limit: ngram_limit.max(1),
+            previous: Vec::new(),
+            in_sentence: false,
+        }
+    }
+
+    // Adds a whole document. N-grams and sentences don't continue from one document into the next.
+    pub fn add_text(&mut self, text: &str) {
+        for line in text.lines() {
+            self.add_line(line);
+        }
+        self.end_document();
+    }
+
+    // Like add_text for everything `reader` returns, one line at a time
+    pub fn add_reader(&mut self, mut reader: impl BufRead) -> io::Result<()> {
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            self.add_line(&line);
+            line.clear();
+        }
+        self.end_document();
+        Ok(())
+    }
+
+    fn add_line(&mut self, line: &str) {
+        // unicode_words() would skip the punctuation we need for sentences, so filter the same way it does
+        for segment in line.split_word_bounds() {
+            if segment.chars().any(char::is_alphanumeric) {
+                self.add_word(segment.to_lowercase());
+            } else if segment.contains(['.', '!', '?']) {
+                if self.in_sentence {
+                    self.sentences += 1;
+                }
+                self.in_sentence = false;
+            }
+        }
+    }
+
+    fn add_word(&mut self, word: String) {
+        self.words += 1;
+        self.syllables += syllables(&word);
+        self.in_sentence = true;
+
+        if let [.., last] = self.previous.as_slice() {
+            *self.bigrams.entry(format!("{last} {word}")).or_insert(0) += 1;
+            prune(&mut self.bigrams, self.ngram_limit);
+        }
+        if let [first, second] = self.previous.as_slice() {
+            *self
+                .trigrams
+                .entry(format!("{first} {second} {word}"))
+                .or_insert(0) += 1;
+            prune(&mut self.trigrams, self.ngram_limit);
+        }
+
+        // The map owns one copy of every distinct word, `previous` the last two
+        match self.word_counts.get_mut(&word) {
+            Some(count) => *count += 1,
+            None => {
+                self.word_counts.insert(word.clone(), 1);
+            }
+        }
+        if self.previous.len() == 2 {
+            self.previous.remove(0);
+        }
+        self.previous.push(word);
+    }
+
+    fn end_document(&mut self) {
+        // Text that doesn't end with a period still ends its last sentence
+        if self.in_sentence {
+            self.sentences += 1;
+        }
+        self.in_sentence = false;
+        self.previous.clear();
+    }
+
+    pub fn words(&self) -> u64 {
+        self.words
+    }
+
+    pub fn unique_words(&self) -> usize {
+        self.word_counts.len()
+    }
+
+    pub fn sentences(&self) -> u64 {
+        self.sentences
+    }
+
+    pub fn syllables(&self) -> u64 {
+        self.syllables
+    }
+
+    pub fn count(&self, word: &str) -> u64 {
+        self.word_counts
+            .get(&word.to_lowercase())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // The `n` most frequent words, alphabetically among equally frequent ones
+    pub fn top_words(&self, n: usize) -> Vec<(&str, u64)> {
+        top(&self.word_counts, n)
+    }
+
+    pub fn top_bigrams(&self, n: usize) -> Vec<(&str, u64)> {
+        top(&self.bigrams, n)
+    }
+
+    pub fn top_trigrams(&self, n: usize) -> Vec<(&str, u64)> {
+        top(&self.trigrams, n)
+    }
+
+    // Distinct words per word, from close to 0 for repetitive text to 1 when no word repeats.
+    // Longer texts repeat more, so only compare texts of similar length.
+    pub fn type_token_ratio(&self) -> Option<f64> {
+        (self.words > 0).then(|| self.unique_words() as f64 / self.words as f64)
+    }
+
+    fn words_per_sentence(&self) -> Option<f64> {
+        (self.sentences > 0).then(|| self.words as f64 / self.sentences as f64)
+    }
+
+    fn syllables_per_word(&self) -> Option<f64> {
+        (self.words > 0).then(|| self.syllables as f64 / self.words as f64)
+    }
+
+    // Around 100 is very easy to read, 60 to 70 is plain English and below 30 is for university graduates
+    pub fn flesch_reading_ease(&self) -> Option<f64> {
+        let (words, syllables) = (self.words_per_sentence()?, self.syllables_per_word()?);
+        Some(206.835 - 1.015 * words - 84.6 * syllables)
+    }
+
+    // The US school grade that can understand the text
+    pub fn flesch_kincaid_grade(&self) -> Option<f64> {
+        let (words, syllables) = (self.words_per_sentence()?, self.syllables_per_word()?);
+        Some(0.39 * words + 11.8 * syllables - 15.59)
+    }
+}
+
+// Keeps at most the more frequent half of `counts` once there are more than `limit`
+fn prune(counts: &mut HashMap<String, u64>, limit: usize) {
+    if counts.len() <= limit {
+        return;
+    }
+
+    let mut frequencies: Vec<u64> = counts.values().copied().collect();
+    let (_, &mut threshold, _) = frequencies.select_nth_unstable_by(limit / 2, |a, b| b.cmp(a));
+    counts.retain(|_, &mut count| count > threshold);
+}
+
+fn top(counts: &HashMap<String, u64>, n: usize) -> Vec<(&str, u64)> {
+    let mut top: Vec<(&str, u64)> = counts
+        .iter()
+        .map(|(key, &count)| (key.as_str(), count))
+        .collect();
+    top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    top.truncate(n);
+    top
+}
+
+// Every group of vowels is a syllable, except a silent "e" at the end like in "make", but not in "table".
+// Words without vowels, like numbers, have one.
+fn syllables(word: &str) -> u64 {
+    let is_vowel = |c: char| "aeiouyàáâäèéêëìíîïòóôöùúûü".contains(c);
+    let chars: Vec<char> = word.chars().collect();
+
+    let mut groups = 0;
+    let mut previous_vowel = false;
+    for &c in &chars {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            groups += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    if let [.., before, 'l', 'e'] = chars.as_slice() {
+        if !is_vowel(*before) {
+            return groups.max(1);
+        }
+    }
+    if let [.., before, 'e'] = chars.as_slice() {
+        if !is_vowel(*before) && groups > 1 {
+            groups -= 1;
+        }
+    }
+    groups.max(1)
+}
+
+#[derive(Debug)]
+pub enum StatsError {
+    Usage(String),
+    Io { path: String, error: io::Error },
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Usage(message) => {
+                write!(
+                    f,
+                    "{message}\nUsage: stats [--top N] FILE... (- reads stdin)"
+                )
+            }
+            StatsError::Io { path, error } => write!(f, "Problem reading {path}: {error}"),
+        }
+    }
+}
+
+impl Error for StatsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StatsError::Usage(_) => None,
+            StatsError::Io { error, .. } => Some(error),
+        }
+    }
+}
+
+// The `stats` command: statistics over all files together, written to `out`
+pub fn run(args: &[String], out: &mut impl io::Write) -> Result<(), StatsError> {
+    let mut top = 10;
+    let mut paths = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--top" {
+            top = args
+                .next()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| StatsError::Usage(String::from("--top needs a number")))?;
+        } else {
+            paths.push(arg);
+        }
+    }
+    if paths.is_empty() {
+        return Err(StatsError::Usage(String::from("No files given")));
+    }
+
+    let mut stats = TextStats::new();
+    for path in paths {
+        let read = if path == "-" {
+            stats.add_reader(io::stdin().lock())
+        } else {
+            File::open(path).and_then(|file| stats.add_reader(BufReader::new(file)))
+        };
+        read.map_err(|error| StatsError::Io {
+            path: path.clone(),
+            error,
+        })?;
+    }
+
+    write_report(&stats, top, out).map_err(|error| StatsError::Io {
+        path: String::from("output"),
+        error,
+    })
+}
+
+fn write_report(stats: &TextStats, top: usize, out: &mut impl io::Write) -> io::Result<()> {
+    let score = |score: Option<f64>| score.map_or(String::from("-"), |s| format!("{s:.1}"));
+
+    writeln!(out, "Words: {}", stats.words())?;
+    writeln!(out, "Unique words: {}", stats.unique_words())?;
+    writeln!(out, "Sentences: {}", stats.sentences())?;
+    writeln!(
+        out,
+        "Type-token ratio: {}",
+        stats
+            .type_token_ratio()
+            .map_or(String::from("-"), |ratio| format!("{ratio:.3}"))
+    )?;
+    writeln!(
+        out,
+        "Flesch reading ease: {}",
+        score(stats.flesch_reading_ease())
+    )?;
+    writeln!(
+        out,
+        "Flesch-Kincaid grade: {}",
+        score(stats.flesch_kincaid_grade())
+    )?;
+
+    for (title, counts) in [
+        ("words", stats.top_words(top)),
+        ("bigrams", stats.top_bigrams(top)),
+        ("trigrams", stats.top_trigrams(top)),
+    ] {
+        writeln!(out, "\nTop {title}:")?;
+        for (text, count) in counts {
+            writeln!(out, "{count:>8}  {text}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_unicode_aware_and_case_folded() {
+        let mut stats = TextStats::new();
+        stats.add_text("The STRASSE, the Straße… «Ünïcode» can't-stop; l'été 3.14 — ok?!");
+
+        assert_eq!(
+            stats.top_words(20),
+            [
+                ("the", 2),
+                ("3.14", 1),
+                ("can't", 1),
+                ("l'été", 1),
+                ("ok", 1),
+                ("stop", 1),
+                ("strasse", 1),
+                ("straße", 1),
+                ("ünïcode", 1),
+            ]
+        );
+        assert_eq!(stats.count("THE"), 2);
+        assert_eq!(stats.words(), 10);
+        assert_eq!(stats.sentences(), 1);
+    }
+
+    #[test]
+    fn ngrams_stay_inside_documents() {
+        let mut stats = TextStats::new();
+        stats.add_text("one fish two fish\nred fish blue fish");
+        stats.add_text("fish one fish");
+
+        assert_eq!(stats.top_bigrams(2), [("one fish", 2), ("blue fish", 1)]);
+        assert_eq!(stats.top_bigrams(20).len(), 8);
+        // "fish fish" would cross from the first document into the second
+        assert!(stats
+            .top_bigrams(20)
+            .iter()
+            .all(|(bigram, _)| *bigram != "fish fish"));
+        assert_eq!(stats.top_trigrams(20).len(), 7);
+        assert_eq!(stats.top_words(1), [("fish", 6)]);
+        assert_eq!(stats.type_token_ratio(), Some(5.0 / 11.0));
+    }
+
+    #[test]
+    fn ngrams_are_limited() {
+        let mut stats = TextStats::with_ngram_limit(4);
+        let text: Vec<String> = (0..100).map(|i| format!("to be or word{i}")).collect();
+        stats.add_text(&text.join(" "));
+
+        assert!(stats.bigrams.len() <= 4, "{:?}", stats.bigrams);
+        assert!(stats.trigrams.len() <= 4, "{:?}", stats.trigrams);
+
+        // Frequent ones survive, with about their counts
+        let top = stats.top_bigrams(2);
+        assert_eq!((top[0].0, top[1].0), ("be or", "to be"));
+        assert!(top.iter().all(|&(_, count)| count > 90), "{top:?}");
+        let (trigram, count) = stats.top_trigrams(1)[0];
+        assert_eq!(trigram, "to be or");
+        assert!(count > 90, "{count}");
+
+        // Words are all kept
+        assert_eq!(stats.unique_words(), 103);
+    }
+
+    #[test]
+    fn readability() {
+        for (word, expected) in [
+            ("cat", 1),
+            ("make", 1),
+            ("table", 2),
+            ("reading", 2),
+            ("beautiful", 3),
+            ("university", 5),
+            ("the", 1),
+            ("42", 1),
+        ] {
+            assert_eq!(syllables(word), expected, "{word}");
+        }
+
+        let mut stats = TextStats::new();
+        stats.add_text("The cat sat on the mat. The dog ran.");
+        assert_eq!(
+            (stats.words(), stats.sentences(), stats.syllables()),
+            (9, 2, 9)
+        );
+        // 206.835 - 1.015 * 4.5 - 84.6 * 1
+        assert!((stats.flesch_reading_ease().unwrap() - 117.6675).abs() < 1e-9);
+        assert!((stats.flesch_kincaid_grade().unwrap() + 2.035).abs() < 1e-9);
+
+        assert_eq!(TextStats::new().flesch_reading_ease(), None);
+        assert_eq!(TextStats::new().type_token_ratio(), None);
+    }
+
+    #[test]
+    fn command_reads_files() {
+        let path = std::env::temp_dir().join(format!("text_stats_{}.txt", std::process::id()));
+        std::fs::write(&path, "Hello world.\nWonderful world!\n").unwrap();
+
+        let args = [
+            String::from("--top"),
+            String::from("1"),
+            path.display().to_string(),
+        ];
+        let mut out = Vec::new();
+        run(&args, &mut out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let report = String::from_utf8(out).unwrap();
+        assert!(
+            report.starts_with("Words: 4\nUnique words: 3\nSentences: 2\n"),
+            "{report}"
+        );
+        assert!(
+            report.contains("Top words:\n       2  world\n\nTop bigrams"),
+            "{report}"
+        );
+
+        assert!(matches!(
+            run(&[], &mut Vec::new()),
+            Err(StatsError::Usage(_))
+        ));
+        assert!(matches!(
+            run(&[String::from("--top")], &mut Vec::new()),
+            Err(StatsError::Usage(_))
+        ));
+        assert!(matches!(
+            run(&[String::from("/no/such/file")], &mut Vec::new()),
+            Err(StatsError::Io { .. })
+        ));
+    }
+}
@@ -1,22 +1,53 @@
-mod lifetimes;
-mod traits;
-
+pub mod excerpts;
+pub mod feed;
+pub mod lifetimes;
+pub mod moderation;
+pub mod numeric;
+pub mod parallel;
+pub mod point;
+pub mod render;
+pub mod search;
+pub mod selection;
+pub mod shapes;
+pub mod summarizer;
+pub mod syndication;
+pub mod text;
+pub mod text_metrics;
+pub mod threads;
+pub mod traits;
+
+use crate::generics::excerpts::Excerpts;
+use crate::generics::feed::Feed;
 use crate::generics::lifetimes::ImportantExcerpt;
-use crate::generics::traits::{Hi, NewsArticle, Summary, Tweet};
-use std::fmt::Display;
+use crate::generics::moderation::{
+    Action, AuthorMute, DuplicateFilter, KeywordFilter, Language, LanguageFilter, Pipeline,
+};
+use crate::generics::parallel::Parallel;
+use crate::generics::point::Point;
+use crate::generics::render::{Html, Markdown, PlainText};
+use crate::generics::search::SearchIndex;
+use crate::generics::text_metrics::Metric;
+use crate::generics::traits::{Hi, NewsArticle, Podcast, Summary, Tweet};
 
 pub fn generics() {
     let number_list = [34, 50, 25, 100, 65];
     let char_list = ['y', 'm', 'a', 'q'];
 
-    let result = largest_i32(&number_list);
-    let result = largest_char(&char_list);
+    // One generic function works for every type that implements PartialOrd
+    // It returns an Option instead of indexing list[0], so empty slices don't panic
+    let result = selection::max(&number_list);
+    let result = selection::max(&char_list);
+
+    let result = selection::max(&number_list[1..]);
+    let result = selection::min(&char_list[..2]);
 
-    let result = largest_i32(&number_list[1..]);
-    let result = largest_char(&char_list[..2]);
+    let result = selection::min_max(&number_list);
+    let result = selection::max::<i32>(&[]);
+    let result = selection::top_k(&number_list, 3);
+    let result = selection::median(&char_list);
 
-    let result = largest_generic(&number_list);
-    let result = largest_generic(&char_list);
+    // Large slices can be reduced on several threads, small ones like this stay sequential
+    let result = Parallel::default().max(&number_list);
 
     let both_integer = Point { x: 5, y: 6 };
     let both_float = Point { x: 1.0, y: 4.0 };
@@ -40,6 +71,16 @@ pub fn generics() {
     both_float.print();
     integer_and_string.print();
 
+    // Numeric points support operators and distances for any numeric coordinate type
+    let start = Point::new(1, 2);
+    let end = start.clone() + Point::new(3, 4) * 2;
+    println!(
+        "Distances = ({},{},{})",
+        start.euclidean_distance(&end),
+        start.manhattan_distance(&end),
+        start.chebyshev_distance(&end),
+    );
+
     // Compiler error: borrow of moved value: `both_integer`
     //     - function `mixup` takes ownership of the receiver `self`, which moves `both_integer`
     // println!(
@@ -63,8 +104,9 @@ pub fn traits() {
     let tweet = Tweet {
         username: String::from("horse_ebooks"),
         content: String::from("of course, as you probably already know, people"),
-        reply: false,
-        retweet: false,
+        id: 1,
+        in_reply_to: None,
+        retweet_of: None,
     };
 
     let summarizable_tweet = traits::summarizable_tweet();
@@ -75,8 +117,52 @@ pub fn traits() {
     article.hi();
     tweet.hi();
 
+    let podcast = Podcast {
+        title: String::from("Power Play"),
+        host: String::from("Iceburgh"),
+        show_notes: String::from("We look back at the season. The playoffs start next week."),
+    };
+    println!("New episode! {}", podcast.summarize());
+    podcast.hi();
+
+    // Items only make it into a bulletin if no moderation filter blocks them, every verdict says why
+    let mut pipeline = Pipeline::new();
+    pipeline.push(Box::new(KeywordFilter::new(&["spoiler"], Action::Block)));
+    pipeline.push(Box::new(AuthorMute::new(&["@spam_bot"])));
+    pipeline.push(Box::new(LanguageFilter::new(
+        &[Language::English],
+        0.05,
+        Action::Flag,
+    )));
+    pipeline.push(Box::new(DuplicateFilter::default()));
+
+    for review in [pipeline.review(&article), pipeline.review(&tweet)] {
+        for verdict in &review.verdicts {
+            println!("Moderation: {verdict}");
+        }
+    }
+
     traits::breaking_news(&article);
     traits::breaking_news(&tweet);
+
+    // The same bulletin as Markdown, HTML or wrapped plain text
+    println!("{}", render::bulletin(&article, &Markdown));
+    println!("{}", render::bulletin(&tweet, &Html));
+    println!("{}", render::bulletin(&article, &PlainText::default()));
+
+    // Both types can be searched through one index, hits refer to the ids chosen here
+    let mut index = SearchIndex::new();
+    index.add(1, &article);
+    index.add(tweet.id + 1, &tweet);
+    if let Ok(hits) = index.search("\"stanley cup\" OR people") {
+        println!("Search hits = {hits:?}");
+    }
+
+    // Trait objects allow different Summary types in one collection
+    let mut feed = Feed::new("digest");
+    feed.push(1_700_000_000, Box::new(article));
+    feed.push(1_700_000_060, Box::new(tweet));
+    print!("{}", feed.render());
 }
 
 // 'a is lifetime of x.as_str() and 'b is lifetime of y.as_str()
@@ -105,100 +191,41 @@ pub fn lifetimes() {
 
     // println!("The longest string is '{l}'"); // Compiler error: borrowed value does not live long enough
 
+    // longest_string compares bytes, text_metrics can compare characters or the width on screen instead
+    let greetings = ["Здравствуйте", "Good morning!"];
+    println!(
+        "Longest greeting = {:?} in bytes, {:?} in characters",
+        text_metrics::longest_by(greetings, Metric::Bytes),
+        text_metrics::longest_by(greetings, Metric::Chars),
+    );
+
     {
         let novel = String::from("Call me Michael. Some years ago...");
         let first_sentence = novel.split('.').next().expect("Could not find a '.'");
 
         let i = ImportantExcerpt {
             part: first_sentence,
+            span: 0..first_sentence.len(),
         };
 
         // The scope here creates an instance of the ImportantExcerpt struct that holds a reference to the first sentence of the String owned by the variable novel.
         // The data in novel exists before the ImportantExcerpt instance is created.
         // In addition, novel doesn’t go out of scope until after the ImportantExcerpt goes out of scope, so the reference in the ImportantExcerpt instance is valid.
         println!("Important excerpt is: {}", i.part);
-    }
 
-    // One special lifetime we need to discuss is 'static, which denotes that the affected reference can live for the entire duration of the program. 
-    // All string literals automatically have the 'static lifetime.
-    let s: &'static str = "I have a static lifetime.";
-}
+        // split('.') cuts "Mr." in half, the extractor knows abbreviations and still only borrows from novel
+        let novel = String::from("Call me Mr. Michael. Some years ago... never mind how long.");
+        let excerpts = Excerpts::new(&novel);
 
-struct Point<T, U> {
-    x: T,
-    y: U,
-}
-
-impl<T, U> Point<T, U> {
-    fn new(x: T, y: U) -> Self {
-        Self { x, y }
-    }
-
-    fn x(&self) -> &T {
-        &self.x
-    }
-
-    fn y(&self) -> &U {
-        &self.y
-    }
-
-    fn mixup<T2, U2>(self, other: Point<T2, U2>) -> Point<T, U2> {
-        Point {
-            x: self.x,
-            y: other.y,
-        }
-    }
-}
-
-// Conditionally implementing a method on a generic type depending on trait bounds (supports impl Trait syntax also)
-impl<T, U> Point<T, U>
-where
-    T: Display,
-    U: Display,
-{
-    fn print(&self) {
-        println!("({},{})", self.x, self.y)
-    }
-}
-
-impl Point<f64, f64> {
-    fn distance_from_origin(&self) -> f64 {
-        (self.x.powi(2) + self.y.powi(2)).sqrt()
-    }
-}
-
-fn largest_i32(list: &[i32]) -> &i32 {
-    let mut largest = &list[0];
-
-    for item in list {
-        if item > largest {
-            largest = item;
-        }
-    }
-
-    largest
-}
-
-fn largest_char(list: &[char]) -> &char {
-    let mut largest = &list[0];
-
-    for item in list {
-        if item > largest {
-            largest = item;
+        if let Some(first) = excerpts.first_sentence() {
+            println!("Important excerpt is: {} at {:?}", first.part, first.span);
         }
-    }
-
-    largest
-}
-
-fn largest_generic<T: std::cmp::PartialOrd>(list: &[T]) -> &T {
-    let mut largest = &list[0];
-
-    for item in list {
-        if item > largest {
-            largest = item
+        for context in excerpts.keyword_in_context("years", 2) {
+            println!("Keyword in context: {}", context.window.part);
         }
     }
 
-    largest
+    // One special lifetime we need to discuss is 'static, which denotes that the affected reference can live for the entire duration of the program. 
+    // All string literals automatically have the 'static lifetime.
+    let s: &'static str = "I have a static lifetime.";
 }
@@ -0,0 +1,171 @@
+use crate::generics::lifetimes::ImportantExcerpt;
+use crate::generics::text;
+use std::ops::Range;
+
+// Finds sentences, paragraphs and keywords in a text and hands them out as ImportantExcerpts.
+// Every excerpt borrows from the source text, nothing is copied, so the excerpts can't outlive the source.
+
+pub struct Excerpts<'a> {
+    source: &'a str,
+}
+
+// A keyword together with a few words around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordInContext<'a> {
+    pub window: ImportantExcerpt<'a>,
+    pub keyword: ImportantExcerpt<'a>,
+}
+
+impl<'a> KeywordInContext<'a> {
+    // The part of the window before the keyword
+    pub fn before(&self) -> &'a str {
+        &self.window.part[..self.keyword.span.start - self.window.span.start]
+    }
+
+    pub fn after(&self) -> &'a str {
+        &self.window.part[self.keyword.span.end - self.window.span.start..]
+    }
+}
+
+impl<'a> Excerpts<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self { source }
+    }
+
+    fn excerpt(&self, span: Range<usize>) -> ImportantExcerpt<'a> {
+        ImportantExcerpt {
+            part: &self.source[span.clone()],
+            span,
+        }
+    }
+
+    // Sentences with text::sentence_spans, which doesn't end sentences at "Mr.", "3.14" or in the middle of "..."
+    pub fn sentences(&self) -> impl Iterator<Item = ImportantExcerpt<'a>> + '_ {
+        text::sentence_spans(self.source)
+            .into_iter()
+            .map(|span| self.excerpt(span))
+    }
+
+    pub fn first_sentence(&self) -> Option<ImportantExcerpt<'a>> {
+        self.sentences().next()
+    }
+
+    // Paragraphs are separated by lines that are empty or only contain whitespace
+    pub fn paragraphs(&self) -> impl Iterator<Item = ImportantExcerpt<'a>> + '_ {
+        let mut spans = Vec::new();
+        let mut start = None;
+        let mut offset = 0;
+
+        for line in self.source.split_inclusive('\n') {
+            let blank = line.trim().is_empty();
+
+            match (blank, start) {
+                (false, None) => start = Some(offset),
+                (true, Some(first)) => {
+                    spans.push(first..offset);
+                    start = None;
+                }
+                _ => {}
+            }
+            offset += line.len();
+        }
+        spans.extend(start.map(|first| first..offset));
+
+        spans.into_iter().map(|span| {
+            let paragraph = &self.source[span.clone()];
+            let start = span.start + (paragraph.len() - paragraph.trim_start().len());
+            self.excerpt(start..start + paragraph.trim().len())
+        })
+    }
+
+    // Every occurrence of `keyword` as a whole word, ignoring case, with up to `context` words on each side.
+    // Windows don't reach into neighbouring sentences.
+    pub fn keyword_in_context(&self, keyword: &str, context: usize) -> Vec<KeywordInContext<'a>> {
+        let mut matches = Vec::new();
+
+        for sentence in text::sentence_spans(self.source) {
+            let words: Vec<Range<usize>> = text::word_spans(&self.source[sentence.clone()])
+                .map(|word| sentence.start + word.start..sentence.start + word.end)
+                .collect();
+
+            for (i, word) in words.iter().enumerate() {
+                if !equal_ignoring_case(&self.source[word.clone()], keyword) {
+                    continue;
+                }
+
+                let first = &words[i.saturating_sub(context)];
+                let last = &words[(i + context).min(words.len() - 1)];
+
+                matches.push(KeywordInContext {
+                    window: self.excerpt(first.start..last.end),
+                    keyword: self.excerpt(word.clone()),
+                });
+            }
+        }
+
+        matches
+    }
+}
+
+// Compares without building lowercase copies of the strings
+fn equal_ignoring_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Call me Mr. Ishmael. Pi is about 3.14 here...\n\
+        \n  Some years ago, never mind how long precisely, I went to sea.  \n\
+        It was years later.\n   \n\nThe end";
+
+    #[test]
+    fn sentences_borrow_from_the_source() {
+        let excerpts = Excerpts::new(TEXT);
+
+        let sentences: Vec<ImportantExcerpt> = excerpts.sentences().collect();
+
+        assert_eq!(sentences[0].part, "Call me Mr. Ishmael.");
+        assert_eq!(sentences[1].part, "Pi is about 3.14 here...");
+        assert_eq!(sentences.len(), 5);
+
+        for sentence in &sentences {
+            assert_eq!(&TEXT[sentence.span.clone()], sentence.part);
+            // Same memory, not a copy
+            assert_eq!(sentence.part.as_ptr(), TEXT[sentence.span.clone()].as_ptr());
+        }
+    }
+
+    #[test]
+    fn paragraphs_are_split_at_blank_lines() {
+        let paragraphs: Vec<&str> = Excerpts::new(TEXT).paragraphs().map(|p| p.part).collect();
+
+        assert_eq!(
+            paragraphs,
+            vec![
+                "Call me Mr. Ishmael. Pi is about 3.14 here...",
+                "Some years ago, never mind how long precisely, I went to sea.  \nIt was years later.",
+                "The end",
+            ]
+        );
+        assert_eq!(Excerpts::new("  \n\n").paragraphs().count(), 0);
+    }
+
+    #[test]
+    fn keywords_in_context_stay_inside_their_sentence() {
+        let matches = Excerpts::new(TEXT).keyword_in_context("YEARS", 2);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].window.part, "Some years ago, never");
+        assert_eq!(matches[0].before(), "Some ");
+        assert_eq!(matches[0].keyword.part, "years");
+        assert_eq!(matches[0].after(), " ago, never");
+        assert_eq!(matches[1].window.part, "It was years later");
+        assert_eq!(&TEXT[matches[1].keyword.span.clone()], "years");
+
+        assert!(Excerpts::new(TEXT).keyword_in_context("year", 2).is_empty());
+    }
+}
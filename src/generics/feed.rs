@@ -0,0 +1,313 @@
+use crate::generics::traits::Summary;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+// Seconds since the Unix epoch
+pub type Timestamp = u64;
+
+// Trait objects let one feed hold NewsArticles, Tweets and any other Summary implementor side by side
+pub struct FeedItem {
+    pub timestamp: Timestamp,
+    pub source: String,
+    pub item: Box<dyn Summary>,
+    // Insertion sequence number, makes the timeline order total so cursors are unambiguous
+    seq: u64,
+}
+
+impl FeedItem {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            timestamp: self.timestamp,
+            seq: self.seq,
+        }
+    }
+}
+
+// Items are kept in reverse-chronological order, newest first
+pub struct Feed {
+    pub name: String,
+    items: Vec<FeedItem>,
+    next_seq: u64,
+}
+
+// Position after the last item of a page.
+// Cursors stay valid when newer items are added at the top of the feed in the meantime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    timestamp: Timestamp,
+    seq: u64,
+}
+
+pub struct Page<'a> {
+    pub items: Vec<&'a FeedItem>,
+    // None on the last page
+    pub next: Option<Cursor>,
+}
+
+impl Feed {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            items: Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &FeedItem> {
+        self.items.iter()
+    }
+
+    pub fn push(&mut self, timestamp: Timestamp, item: Box<dyn Summary>) {
+        let source = self.name.clone();
+        self.insert(timestamp, source, item);
+    }
+
+    fn insert(&mut self, timestamp: Timestamp, source: String, item: Box<dyn Summary>) {
+        let item = FeedItem {
+            timestamp,
+            source,
+            item,
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
+
+        // Items with equal timestamps keep their insertion order
+        let position = self
+            .items
+            .partition_point(|existing| existing.cursor() < item.cursor());
+        self.items.insert(position, item);
+    }
+
+    // Merges several sources into one timeline, every item remembers the feed it came from
+    pub fn merge(name: &str, sources: impl IntoIterator<Item = Feed>) -> Feed {
+        let mut all: Vec<FeedItem> = sources.into_iter().flat_map(|feed| feed.items).collect();
+
+        // Stable sort, so equal timestamps keep the order of the sources
+        all.sort_by_key(|item| Reverse(item.timestamp));
+
+        let mut merged = Feed::new(name);
+        for item in all {
+            merged.insert(item.timestamp, item.source, item.item);
+        }
+        merged
+    }
+
+    // Removes items with the same author and content as a newer item, e.g. the same article from two sources.
+    // Summaries aren't compared, different items can have the same one. Items without content are kept.
+    // Returns the number of removed items.
+    pub fn dedup(&mut self) -> usize {
+        let before = self.items.len();
+        let mut seen = HashSet::new();
+
+        self.items.retain(|item| {
+            let Some(content) = item.item.summarize_content() else {
+                return true;
+            };
+            seen.insert((item.item.summarize_author(), String::from(content)))
+        });
+        before - self.items.len()
+    }
+
+    // Up to `limit` items starting after `cursor`, or from the newest item if there is no cursor.
+    // Fails when `limit` is 0, an empty page couldn't tell whether the feed has ended.
+    pub fn page(&self, cursor: Option<Cursor>, limit: usize) -> Result<Page<'_>, String> {
+        if limit == 0 {
+            return Err(String::from("A page needs room for at least one item"));
+        }
+        let start = match cursor {
+            Some(cursor) => self.items.partition_point(|item| item.cursor() <= cursor),
+            None => 0,
+        };
+        let end = (start + limit).min(self.items.len());
+        let items: Vec<&FeedItem> = self.items[start..end].iter().collect();
+
+        let next = match items.last() {
+            Some(last) if end < self.items.len() => Some(last.cursor()),
+            _ => None,
+        };
+
+        Ok(Page { items, next })
+    }
+
+    // One line per item, newest first
+    pub fn render(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| {
+                format!(
+                    "[{}] {}: {}\n",
+                    item.timestamp,
+                    item.source,
+                    item.item.summarize()
+                )
+            })
+            .collect()
+    }
+}
+
+// Newer items sort first, items with the same timestamp in insertion order
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cursor {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .timestamp
+            .cmp(&self.timestamp)
+            .then(self.seq.cmp(&other.seq))
+    }
+}
+
+// Cursors round-trip through strings like "1700000000-42", so they can be handed out to clients
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.timestamp, self.seq)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor '{s}'");
+        let (timestamp, seq) = s.split_once('-').ok_or_else(invalid)?;
+
+        Ok(Cursor {
+            timestamp: timestamp.parse().map_err(|_| invalid())?,
+            seq: seq.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::traits::{NewsArticle, Tweet};
+
+    fn tweet(username: &str, content: &str) -> Box<dyn Summary> {
+        Box::new(Tweet {
+            username: String::from(username),
+            content: String::from(content),
+            id: 0,
+            in_reply_to: None,
+            retweet_of: None,
+        })
+    }
+
+    fn article(author: &str) -> Box<dyn Summary> {
+        Box::new(NewsArticle {
+            headline: String::from("Headline"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from(author),
+            content: String::from("Content."),
+        })
+    }
+
+    fn sample() -> Feed {
+        let mut tweets = Feed::new("tweets");
+        tweets.push(100, tweet("a", "first"));
+        tweets.push(300, tweet("b", "third"));
+
+        let mut news = Feed::new("news");
+        news.push(200, article("Iceburgh"));
+        news.push(300, article("Penguin"));
+
+        Feed::merge("all", [tweets, news])
+    }
+
+    #[test]
+    fn merge_builds_a_reverse_chronological_timeline() {
+        let feed = sample();
+
+        assert_eq!(
+            feed.render(),
+            "[300] tweets: b: third\n\
+             [300] news: Content. (Read more from Penguin...)\n\
+             [200] news: Content. (Read more from Iceburgh...)\n\
+             [100] tweets: a: first\n"
+        );
+    }
+
+    #[test]
+    fn pages_follow_cursors_until_the_end() {
+        let feed = sample();
+
+        let first = feed.page(None, 3).unwrap();
+        assert_eq!(first.items.len(), 3);
+
+        let second = feed.page(first.next, 3).unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].timestamp, 100);
+        assert_eq!(second.next, None);
+    }
+
+    #[test]
+    fn cursors_survive_newer_items_and_strings() {
+        let mut feed = sample();
+        let first = feed.page(None, 2).unwrap();
+        let cursor: Cursor = first.next.unwrap().to_string().parse().unwrap();
+
+        feed.push(500, tweet("c", "newest"));
+
+        let second = feed.page(Some(cursor), 10).unwrap();
+        let timestamps: Vec<_> = second.items.iter().map(|item| item.timestamp).collect();
+        assert_eq!(timestamps, vec![200, 100]);
+        assert!("garbage".parse::<Cursor>().is_err());
+    }
+
+    #[test]
+    fn dedup_keeps_the_newest_copy() {
+        let mut feed = Feed::new("tweets");
+        feed.push(1, tweet("a", "same"));
+        feed.push(3, tweet("a", "same"));
+        feed.push(2, tweet("a", "other"));
+
+        assert_eq!(feed.dedup(), 1);
+        assert_eq!(feed.render(), "[3] tweets: a: same\n[2] tweets: a: other\n");
+    }
+
+    #[test]
+    fn dedup_compares_content_not_summaries() {
+        let article = |content: &str| -> Box<dyn Summary> {
+            Box::new(NewsArticle {
+                headline: String::from("Headline"),
+                location: String::from("Pittsburgh, PA, USA"),
+                author: String::from("Iceburgh"),
+                content: String::from(content),
+            })
+        };
+
+        let mut news = Feed::new("news");
+        news.push(1, article("Penguins win."));
+        news.push(2, article("Penguins lose."));
+        let mut wire = Feed::new("wire");
+        wire.push(3, article("Penguins win."));
+
+        let mut feed = Feed::merge("all", [news, wire]);
+        assert_eq!(feed.dedup(), 1);
+        let sources: Vec<&str> = feed.items().map(|item| item.source.as_str()).collect();
+        assert_eq!(sources, ["wire", "news"]);
+    }
+
+    #[test]
+    fn pages_need_a_limit() {
+        let feed = sample();
+        assert!(feed.page(None, 0).is_err());
+
+        let first = feed.page(None, 1).unwrap();
+        assert!(feed.page(first.next, 0).is_err());
+    }
+}
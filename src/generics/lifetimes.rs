@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 // Lifetime annotations don’t change how long any of the references live. 
 // Rather, they describe the relationships of the lifetimes of multiple references to each other without affecting the lifetimes. 
 // Just as functions can accept any type when the signature specifies a generic type parameter, functions can accept references with any lifetime by specifying a generic lifetime parameter.
@@ -15,6 +17,7 @@
 // The third rule is that, if there are multiple input lifetime parameters, but one of them is &self or &mut self because this is a method, the lifetime of self is assigned to all output lifetime parameters. This third rule makes methods much nicer to read and write because fewer symbols are necessary.
 
 // Generic lifetime parameter, not a generic type
+// len() counts bytes, text_metrics::longest compares characters, graphemes or display width
 pub fn longest_string<'a>(x: &'a str, y: &'a str) -> &'a str {
     if x.len() > y.len() {
         x
@@ -26,8 +29,11 @@ pub fn longest_string<'a>(x: &'a str, y: &'a str) -> &'a str {
 // This lifetime parameter means an instance of ImportantExcerpt can’t outlive the reference it holds in its part field.
 
 // Generic lifetime parameter, not a generic type
+#[derive(Debug, Clone, PartialEq)]
 pub struct ImportantExcerpt<'a> {
     pub part: &'a str,
+    // Byte range of `part` in the text it was taken from
+    pub span: Range<usize>,
 }
 
 // The lifetime parameter declaration after impl and its use after the type name are required, but we’re not required to annotate the lifetime of the reference to self because of the first elision rule.
@@ -0,0 +1,379 @@
+mod duplicates;
+mod language;
+
+use crate::generics::text;
+use crate::generics::traits::Summary;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+pub use crate::generics::moderation::duplicates::DuplicateFilter;
+pub use crate::generics::moderation::language::{Language, LanguageFilter};
+
+// Checks Summary items before they are published.
+//
+// A Pipeline runs its filters in order, every filter returns a Verdict with the reason for its decision.
+// The first blocking verdict stops the pipeline, so stateful filters that come later (e.g. duplicate
+// detection) never remember items that were rejected anyway.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Allow,
+    // Publish, but have somebody look at it
+    Flag,
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub filter: String,
+    pub action: Action,
+    pub reason: String,
+}
+
+impl Verdict {
+    pub fn new(filter: &str, action: Action, reason: &str) -> Self {
+        Self {
+            filter: String::from(filter),
+            action,
+            reason: String::from(reason),
+        }
+    }
+}
+
+// One log line like "[block] keywords: contains blocked word 'spoiler'"
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            Action::Allow => "allow",
+            Action::Flag => "flag",
+            Action::Block => "block",
+        };
+        write!(f, "[{action}] {}: {}", self.filter, self.reason)
+    }
+}
+
+pub trait Filter {
+    fn name(&self) -> &str;
+
+    // Takes &mut self so filters can remember what they have seen
+    fn check(&mut self, item: &dyn Summary) -> Verdict;
+}
+
+// The text filters look at: the content for types that have one, otherwise the summary
+pub fn item_text(item: &dyn Summary) -> String {
+    match item.summarize_content() {
+        Some(content) => String::from(content),
+        None => item.summarize(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    // The strictest action of all verdicts
+    pub action: Action,
+    pub verdicts: Vec<Verdict>,
+}
+
+impl Review {
+    pub fn is_allowed(&self) -> bool {
+        self.action != Action::Block
+    }
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    filters: Vec<Box<dyn Filter>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, filter: Box<dyn Filter>) {
+        self.filters.push(filter);
+    }
+
+    pub fn review(&mut self, item: &dyn Summary) -> Review {
+        let mut verdicts = Vec::new();
+
+        for filter in &mut self.filters {
+            let verdict = filter.check(item);
+            let blocked = verdict.action == Action::Block;
+            verdicts.push(verdict);
+
+            if blocked {
+                break;
+            }
+        }
+
+        let action = verdicts
+            .iter()
+            .map(|verdict| verdict.action)
+            .max()
+            .unwrap_or(Action::Allow);
+
+        Review { action, verdicts }
+    }
+}
+
+// Whole words, compared case-insensitively, so blocking "ass" doesn't block "class".
+// Entries of several words like "buy now" match when those words come one after another.
+pub struct KeywordFilter {
+    words: HashSet<String>,
+    phrases: Vec<Vec<String>>,
+    action: Action,
+}
+
+impl KeywordFilter {
+    pub fn new(words: &[&str], action: Action) -> Self {
+        let mut filter = Self {
+            words: HashSet::new(),
+            phrases: Vec::new(),
+            action,
+        };
+
+        // Split like the text they are compared with, so "re-post" matches "Re-post" too
+        for entry in words {
+            let mut phrase: Vec<String> = text::words(entry).collect();
+            match phrase.len() {
+                0 => {}
+                1 => {
+                    filter.words.insert(phrase.remove(0));
+                }
+                _ => filter.phrases.push(phrase),
+            }
+        }
+        filter
+    }
+}
+
+impl Filter for KeywordFilter {
+    fn name(&self) -> &str {
+        "keywords"
+    }
+
+    fn check(&mut self, item: &dyn Summary) -> Verdict {
+        let text = item_text(item);
+        let words: Vec<String> = text::words(&text).collect();
+
+        if let Some(word) = words.iter().find(|word| self.words.contains(*word)) {
+            return Verdict::new(
+                self.name(),
+                self.action,
+                &format!("contains blocked word '{word}'"),
+            );
+        }
+
+        let phrase = self.phrases.iter().find(|phrase| {
+            words
+                .windows(phrase.len())
+                .any(|window| window == phrase.as_slice())
+        });
+
+        match phrase {
+            Some(phrase) => Verdict::new(
+                self.name(),
+                self.action,
+                &format!("contains blocked phrase '{}'", phrase.join(" ")),
+            ),
+            None => Verdict::new(self.name(), Action::Allow, "no blocked words"),
+        }
+    }
+}
+
+// Case-insensitive regular expressions, for patterns like links or phone numbers
+pub struct RegexFilter {
+    patterns: Vec<Regex>,
+    action: Action,
+}
+
+impl RegexFilter {
+    pub fn new(patterns: &[&str], action: Action) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| RegexBuilder::new(pattern).case_insensitive(true).build())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { patterns, action })
+    }
+}
+
+impl Filter for RegexFilter {
+    fn name(&self) -> &str {
+        "patterns"
+    }
+
+    fn check(&mut self, item: &dyn Summary) -> Verdict {
+        let text = item_text(item);
+
+        for pattern in &self.patterns {
+            if let Some(found) = pattern.find(&text) {
+                return Verdict::new(
+                    self.name(),
+                    self.action,
+                    &format!("'{}' matches /{}/", found.as_str(), pattern.as_str()),
+                );
+            }
+        }
+
+        Verdict::new(self.name(), Action::Allow, "no blocked patterns")
+    }
+}
+
+// Mutes authors by their summarize_author(), ignoring case and a leading '@'
+pub struct AuthorMute {
+    muted: HashSet<String>,
+}
+
+fn normalize_author(author: &str) -> String {
+    author.trim().trim_start_matches('@').to_lowercase()
+}
+
+impl AuthorMute {
+    pub fn new(authors: &[&str]) -> Self {
+        Self {
+            muted: authors
+                .iter()
+                .map(|author| normalize_author(author))
+                .collect(),
+        }
+    }
+
+    pub fn mute(&mut self, author: &str) {
+        self.muted.insert(normalize_author(author));
+    }
+
+    pub fn unmute(&mut self, author: &str) -> bool {
+        self.muted.remove(&normalize_author(author))
+    }
+}
+
+impl Filter for AuthorMute {
+    fn name(&self) -> &str {
+        "mutes"
+    }
+
+    fn check(&mut self, item: &dyn Summary) -> Verdict {
+        let author = item.summarize_author();
+
+        if self.muted.contains(&normalize_author(&author)) {
+            Verdict::new(self.name(), Action::Block, &format!("{author} is muted"))
+        } else {
+            Verdict::new(self.name(), Action::Allow, &format!("{author} isn't muted"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::traits::{NewsArticle, Tweet};
+
+    fn tweet(username: &str, content: &str) -> Tweet {
+        Tweet {
+            username: String::from(username),
+            content: String::from(content),
+            id: 0,
+            in_reply_to: None,
+            retweet_of: None,
+        }
+    }
+
+    #[test]
+    fn keywords_match_whole_words_only() {
+        let mut filter = KeywordFilter::new(&["Spoiler"], Action::Block);
+
+        let verdict = filter.check(&tweet("fan", "SPOILER: they win"));
+        assert_eq!(verdict.action, Action::Block);
+        assert_eq!(
+            verdict.to_string(),
+            "[block] keywords: contains blocked word 'spoiler'"
+        );
+
+        assert_eq!(
+            filter.check(&tweet("fan", "No spoilers here")).action,
+            Action::Allow
+        );
+    }
+
+    #[test]
+    fn keywords_can_be_phrases() {
+        let mut filter = KeywordFilter::new(&["Buy now", "re-post"], Action::Flag);
+
+        let verdict = filter.check(&tweet("bot", "Great deals, BUY   NOW!"));
+        assert_eq!(verdict.action, Action::Flag);
+        assert_eq!(verdict.reason, "contains blocked phrase 'buy now'");
+        assert_eq!(
+            filter.check(&tweet("bot", "Please Re-post this")).action,
+            Action::Flag
+        );
+
+        // The words have to come one after another
+        assert_eq!(
+            filter.check(&tweet("fan", "Now I buy it")).action,
+            Action::Allow
+        );
+        assert_eq!(
+            filter.check(&tweet("fan", "Buy it now")).action,
+            Action::Allow
+        );
+    }
+
+    #[test]
+    fn regexes_are_case_insensitive() {
+        let mut filter = RegexFilter::new(&[r"https?://\S+"], Action::Flag).unwrap();
+
+        let verdict = filter.check(&tweet("bot", "Win a prize at HTTP://example.com now"));
+        assert_eq!(verdict.action, Action::Flag);
+        assert!(verdict.reason.starts_with("'HTTP://example.com' matches"));
+
+        assert!(RegexFilter::new(&["(unclosed"], Action::Block).is_err());
+    }
+
+    #[test]
+    fn muted_authors_are_blocked() {
+        let mut filter = AuthorMute::new(&["@Horse_Ebooks"]);
+        let article = NewsArticle {
+            headline: String::from("Headline"),
+            location: String::new(),
+            author: String::from("Iceburgh"),
+            content: String::from("Content."),
+        };
+
+        assert_eq!(
+            filter.check(&tweet("horse_ebooks", "hi")).action,
+            Action::Block
+        );
+        assert_eq!(filter.check(&article).action, Action::Allow);
+
+        filter.mute("iceburgh");
+        assert_eq!(filter.check(&article).action, Action::Block);
+        assert!(filter.unmute("ICEBURGH"));
+    }
+
+    #[test]
+    fn pipeline_stops_at_the_first_block() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Box::new(
+            RegexFilter::new(&["prize"], Action::Flag).unwrap(),
+        ));
+        pipeline.push(Box::new(AuthorMute::new(&["spammer"])));
+        pipeline.push(Box::new(DuplicateFilter::default()));
+
+        let review = pipeline.review(&tweet("spammer", "Claim your prize"));
+        assert_eq!(review.action, Action::Block);
+        assert_eq!(review.verdicts.len(), 2);
+        assert!(!review.is_allowed());
+
+        // The blocked tweet never reached the duplicate filter, so this isn't a duplicate
+        let review = pipeline.review(&tweet("fan", "Claim your prize"));
+        assert_eq!(review.action, Action::Flag);
+        assert_eq!(review.verdicts.len(), 3);
+        assert!(review.is_allowed());
+
+        let review = pipeline.review(&tweet("fan", "Claim your prize"));
+        assert_eq!(review.action, Action::Block);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::generics::moderation::{item_text, Action, Filter, Verdict};
+use crate::generics::text;
+use crate::generics::traits::Summary;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// Finds items that repeat an earlier item word for word (ignoring case and punctuation) or nearly so.
+//
+// Near duplicates are found with MinHash: the text is cut into overlapping word n-grams ("shingles"), and for
+// each of HASHES hash functions the signature keeps the smallest hash of any shingle. The share of equal
+// signature entries estimates the Jaccard similarity of the two shingle sets. To avoid comparing every new
+// item with every earlier one, signatures are split into bands (locality sensitive hashing): only items
+// sharing at least one complete band are compared.
+
+const HASHES: usize = 64;
+const ROWS_PER_BAND: usize = 4;
+
+pub struct DuplicateFilter {
+    // Words per shingle
+    shingle_size: usize,
+    // Estimated Jaccard similarity from which on an item counts as a near duplicate
+    threshold: f64,
+    // What happens to near duplicates, exact duplicates are always blocked
+    near_duplicate_action: Action,
+    seeds: Vec<u64>,
+    // Number of items checked so far, used to name earlier items in verdicts
+    checked: usize,
+    exact: HashMap<u64, usize>,
+    signatures: HashMap<usize, Vec<u64>>,
+    bands: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl Default for DuplicateFilter {
+    fn default() -> Self {
+        Self::new(3, 0.7, Action::Flag)
+    }
+}
+
+// SplitMix64, scrambles the bits of `x` well enough to derive many hash functions from one hash
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl DuplicateFilter {
+    pub fn new(shingle_size: usize, threshold: f64, near_duplicate_action: Action) -> Self {
+        Self {
+            shingle_size: shingle_size.max(1),
+            threshold,
+            near_duplicate_action,
+            seeds: (0..HASHES as u64).map(mix).collect(),
+            checked: 0,
+            exact: HashMap::new(),
+            signatures: HashMap::new(),
+            bands: HashMap::new(),
+        }
+    }
+
+    fn signature(&self, words: &[String]) -> Vec<u64> {
+        // Texts shorter than one shingle become a single shingle
+        let shingles: Vec<u64> = words
+            .windows(self.shingle_size.min(words.len()))
+            .map(|shingle| hash(&shingle))
+            .collect();
+
+        self.seeds
+            .iter()
+            .map(|&seed| {
+                shingles
+                    .iter()
+                    .map(|&shingle| mix(shingle ^ seed))
+                    .min()
+                    .unwrap_or(u64::MAX)
+            })
+            .collect()
+    }
+
+    fn band_keys(signature: &[u64]) -> impl Iterator<Item = (usize, u64)> + '_ {
+        signature
+            .chunks(ROWS_PER_BAND)
+            .enumerate()
+            .map(|(band, rows)| (band, hash(&rows)))
+    }
+
+    // The most similar earlier item sharing a band with `signature`
+    fn most_similar(&self, signature: &[u64]) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+
+        for key in Self::band_keys(signature) {
+            for &candidate in self.bands.get(&key).into_iter().flatten() {
+                let similarity = estimate_similarity(signature, &self.signatures[&candidate]);
+
+                if best.is_none_or(|(_, best)| similarity > best) {
+                    best = Some((candidate, similarity));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn remember(&mut self, item: usize, exact: u64, signature: Vec<u64>) {
+        self.exact.insert(exact, item);
+
+        for key in Self::band_keys(&signature) {
+            self.bands.entry(key).or_default().push(item);
+        }
+        self.signatures.insert(item, signature);
+    }
+}
+
+// Share of equal entries, an estimate of the Jaccard similarity of the shingle sets
+pub fn estimate_similarity(a: &[u64], b: &[u64]) -> f64 {
+    let equal = a.iter().zip(b).filter(|(a, b)| a == b).count();
+    equal as f64 / a.len().max(1) as f64
+}
+
+impl Filter for DuplicateFilter {
+    fn name(&self) -> &str {
+        "duplicates"
+    }
+
+    fn check(&mut self, item: &dyn Summary) -> Verdict {
+        let words: Vec<String> = text::words(&item_text(item)).collect();
+        self.checked += 1;
+        let number = self.checked;
+
+        if words.is_empty() {
+            return Verdict::new(self.name(), Action::Allow, "no text to compare");
+        }
+
+        let exact = hash(&words);
+        if let Some(original) = self.exact.get(&exact) {
+            return Verdict::new(
+                self.name(),
+                Action::Block,
+                &format!("duplicate of item {original}"),
+            );
+        }
+
+        let signature = self.signature(&words);
+
+        match self.most_similar(&signature) {
+            Some((original, similarity)) if similarity >= self.threshold => Verdict::new(
+                self.name(),
+                self.near_duplicate_action,
+                &format!("near duplicate of item {original} (similarity {similarity:.2})"),
+            ),
+            _ => {
+                self.remember(number, exact, signature);
+                Verdict::new(self.name(), Action::Allow, "no earlier item is similar")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::traits::Tweet;
+
+    fn tweet(content: &str) -> Tweet {
+        Tweet {
+            username: String::from("fan"),
+            content: String::from(content),
+            id: 0,
+            in_reply_to: None,
+            retweet_of: None,
+        }
+    }
+
+    const ORIGINAL: &str = "The Pittsburgh Penguins won the Stanley Cup tonight after a thrilling \
+        overtime goal in game seven, and thousands of fans celebrated downtown until morning";
+
+    #[test]
+    fn exact_duplicates_ignore_case_and_punctuation() {
+        let mut filter = DuplicateFilter::default();
+
+        assert_eq!(filter.check(&tweet("Go Pens, go!")).action, Action::Allow);
+
+        let verdict = filter.check(&tweet("go pens go"));
+        assert_eq!(verdict.action, Action::Block);
+        assert_eq!(verdict.reason, "duplicate of item 1");
+    }
+
+    #[test]
+    fn near_duplicates_are_flagged() {
+        let mut filter = DuplicateFilter::default();
+        filter.check(&tweet(ORIGINAL));
+
+        let edited = ORIGINAL.replace("thousands", "hundreds");
+        let verdict = filter.check(&tweet(&edited));
+        assert_eq!(verdict.action, Action::Flag, "{verdict}");
+        assert!(verdict.reason.starts_with("near duplicate of item 1"));
+
+        let unrelated =
+            "Rain is expected for the whole weekend, so bring an umbrella to the farmers market";
+        assert_eq!(filter.check(&tweet(unrelated)).action, Action::Allow);
+    }
+
+    #[test]
+    fn signatures_estimate_jaccard_similarity() {
+        let filter = DuplicateFilter::new(1, 0.5, Action::Flag);
+        let words = |text: &str| text::words(text).collect::<Vec<_>>();
+
+        // 3 shared words out of 5 distinct ones
+        let a = filter.signature(&words("one two three four"));
+        let b = filter.signature(&words("one two three five"));
+
+        let similarity = estimate_similarity(&a, &b);
+        assert!((similarity - 0.6).abs() < 0.2, "{similarity}");
+        assert_eq!(estimate_similarity(&a, &a), 1.0);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::generics::moderation::{item_text, Action, Filter, Verdict};
+use crate::generics::traits::Summary;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+// Language detection with character trigram profiles (Cavnar & Trenkle, "N-Gram-Based Text Categorization").
+//
+// A profile is the list of the most frequent trigrams of a text, most frequent first. The distance between
+// two profiles adds up how far every trigram of the text is from its rank in the language profile, trigrams
+// the language doesn't have cost the maximum. The language with the smallest distance wins.
+// The language profiles are built from the sample texts below the first time they are needed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::English => "English",
+            Language::German => "German",
+            Language::French => "French",
+            Language::Spanish => "Spanish",
+        };
+        write!(f, "{name}")
+    }
+}
+
+const SAMPLES: [(Language, &str); 4] = [
+    (
+        Language::English,
+        "All human beings are born free and equal in dignity and rights. They are endowed with reason \
+         and conscience and should act towards one another in a spirit of brotherhood. The team played \
+         with great energy tonight, and the fans who were waiting outside the arena cheered when the \
+         players finally came out. Everyone is entitled to all the rights and freedoms set forth in this \
+         declaration, without distinction of any kind. There is nothing that the weather could have done \
+         to stop them from celebrating through the night with their friends and their families.",
+    ),
+    (
+        Language::German,
+        "Alle Menschen sind frei und gleich an Würde und Rechten geboren. Sie sind mit Vernunft und \
+         Gewissen begabt und sollen einander im Geist der Brüderlichkeit begegnen. Die Mannschaft hat \
+         heute Abend mit großer Energie gespielt, und die Fans, die draußen vor der Halle gewartet \
+         haben, jubelten, als die Spieler endlich herauskamen. Jeder hat Anspruch auf die in dieser \
+         Erklärung verkündeten Rechte und Freiheiten ohne irgendeinen Unterschied. Das Wetter konnte \
+         sie nicht davon abhalten, mit ihren Freunden und Familien die ganze Nacht zu feiern.",
+    ),
+    (
+        Language::French,
+        "Tous les êtres humains naissent libres et égaux en dignité et en droits. Ils sont doués de \
+         raison et de conscience et doivent agir les uns envers les autres dans un esprit de fraternité. \
+         L'équipe a joué avec beaucoup d'énergie ce soir, et les supporters qui attendaient devant la \
+         patinoire ont applaudi quand les joueurs sont enfin sortis. Chacun peut se prévaloir de tous \
+         les droits et de toutes les libertés proclamés dans la présente déclaration, sans distinction \
+         aucune. Le temps n'a pas pu les empêcher de fêter toute la nuit avec leurs amis et leurs familles.",
+    ),
+    (
+        Language::Spanish,
+        "Todos los seres humanos nacen libres e iguales en dignidad y derechos y, dotados como están de \
+         razón y conciencia, deben comportarse fraternalmente los unos con los otros. El equipo jugó con \
+         mucha energía esta noche, y los aficionados que esperaban fuera del estadio aplaudieron cuando \
+         los jugadores por fin salieron. Toda persona tiene los derechos y libertades proclamados en esta \
+         declaración, sin distinción alguna. El tiempo no pudo impedir que celebraran toda la noche con \
+         sus amigos y sus familias.",
+    ),
+];
+
+const PROFILE_SIZE: usize = 300;
+
+// Texts with fewer letters than this are too short to tell languages apart
+const MIN_LETTERS: usize = 20;
+
+// Trigrams of the lowercased words padded with spaces, most frequent first, equally frequent ones sorted
+fn profile(text: &str) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+    {
+        let padded: Vec<char> = format!(" {} ", word.to_lowercase()).chars().collect();
+
+        for trigram in padded.windows(3) {
+            *counts.entry(trigram.iter().collect()).or_insert(0) += 1;
+        }
+    }
+
+    let mut trigrams: Vec<(String, usize)> = counts.into_iter().collect();
+    trigrams.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    trigrams
+        .into_iter()
+        .take(PROFILE_SIZE)
+        .map(|(trigram, _)| trigram)
+        .collect()
+}
+
+fn language_profiles() -> &'static [(Language, HashMap<String, usize>)] {
+    static PROFILES: OnceLock<Vec<(Language, HashMap<String, usize>)>> = OnceLock::new();
+
+    PROFILES.get_or_init(|| {
+        SAMPLES
+            .iter()
+            .map(|&(language, sample)| {
+                let ranks = profile(sample)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(rank, trigram)| (trigram, rank))
+                    .collect();
+                (language, ranks)
+            })
+            .collect()
+    })
+}
+
+fn out_of_place_distance(text: &[String], language: &HashMap<String, usize>) -> usize {
+    text.iter()
+        .enumerate()
+        .map(|(rank, trigram)| match language.get(trigram) {
+            Some(&language_rank) => rank.abs_diff(language_rank),
+            None => PROFILE_SIZE,
+        })
+        .sum()
+}
+
+// The most likely language and a confidence between 0 and 1: how much closer the best language is than
+// the runner-up. None for texts that are too short.
+pub fn detect_language(text: &str) -> Option<(Language, f64)> {
+    if text.chars().filter(|c| c.is_alphabetic()).count() < MIN_LETTERS {
+        return None;
+    }
+
+    let text = profile(text);
+    let mut distances: Vec<(Language, usize)> = language_profiles()
+        .iter()
+        .map(|(language, ranks)| (*language, out_of_place_distance(&text, ranks)))
+        .collect();
+    distances.sort_by_key(|&(_, distance)| distance);
+
+    let (language, best) = distances[0];
+    let (_, second) = distances[1];
+    let confidence = (second - best) as f64 / second.max(1) as f64;
+
+    Some((language, confidence))
+}
+
+pub struct LanguageFilter {
+    allowed: Vec<Language>,
+    // Below this confidence items are allowed, guessing wrong would block good items
+    min_confidence: f64,
+    action: Action,
+}
+
+impl LanguageFilter {
+    pub fn new(allowed: &[Language], min_confidence: f64, action: Action) -> Self {
+        Self {
+            allowed: allowed.to_vec(),
+            min_confidence,
+            action,
+        }
+    }
+}
+
+impl Filter for LanguageFilter {
+    fn name(&self) -> &str {
+        "language"
+    }
+
+    fn check(&mut self, item: &dyn Summary) -> Verdict {
+        let Some((language, confidence)) = detect_language(&item_text(item)) else {
+            return Verdict::new(
+                self.name(),
+                Action::Allow,
+                "too short to detect the language",
+            );
+        };
+
+        if self.allowed.contains(&language) {
+            Verdict::new(
+                self.name(),
+                Action::Allow,
+                &format!("{language} (confidence {confidence:.2})"),
+            )
+        } else if confidence < self.min_confidence {
+            Verdict::new(
+                self.name(),
+                Action::Allow,
+                &format!("probably {language}, but only with confidence {confidence:.2}"),
+            )
+        } else {
+            Verdict::new(
+                self.name(),
+                self.action,
+                &format!("{language} (confidence {confidence:.2}) isn't an allowed language"),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::traits::Tweet;
+
+    #[test]
+    fn detects_languages_of_unseen_sentences() {
+        let cases = [
+            (
+                "The weather is nice today and we are going to the park with the children",
+                Language::English,
+            ),
+            (
+                "Das Wetter ist heute schön und wir gehen mit den Kindern in den Park",
+                Language::German,
+            ),
+            (
+                "Il fait beau aujourd'hui et nous allons au parc avec les enfants",
+                Language::French,
+            ),
+            (
+                "Hoy hace buen tiempo y vamos al parque con los niños",
+                Language::Spanish,
+            ),
+        ];
+
+        for (text, expected) in cases {
+            let (language, confidence) = detect_language(text).unwrap();
+            assert_eq!(language, expected, "{text}");
+            assert!(confidence > 0.0);
+        }
+
+        assert_eq!(detect_language("Go Pens!"), None);
+    }
+
+    #[test]
+    fn blocks_other_languages() {
+        let mut filter = LanguageFilter::new(&[Language::English], 0.02, Action::Block);
+        let tweet = |content: &str| Tweet {
+            username: String::from("fan"),
+            content: String::from(content),
+            id: 0,
+            in_reply_to: None,
+            retweet_of: None,
+        };
+
+        let verdict = filter.check(&tweet(
+            "Die Mannschaft hat gestern Abend das Spiel gewonnen",
+        ));
+        assert_eq!(verdict.action, Action::Block, "{verdict}");
+        assert!(verdict.reason.starts_with("German"));
+
+        let verdict = filter.check(&tweet("The team won the game last night"));
+        assert_eq!(verdict.action, Action::Allow, "{verdict}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// A small numeric trait so geometry types can be generic over both integer and floating point types.
+// The standard library has no such trait, so we define one and implement it for the primitive types with a macro.
+pub trait Numeric:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn to_f64(self) -> f64;
+
+    // Converts with `as` semantics: floats are truncated towards zero and saturate at the type bounds
+    fn from_f64(value: f64) -> Self;
+}
+
+// Floating point types additionally support square roots and trigonometry
+pub trait Float: Numeric + Neg<Output = Self> {
+    const EPSILON: Self;
+
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_numeric {
+    ($zero:expr, $one:expr; $($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const ZERO: Self = $zero;
+                const ONE: Self = $one;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_float {
+    ($($t:ident),*) => {
+        $(
+            impl Float for $t {
+                const EPSILON: Self = $t::EPSILON;
+
+                fn sqrt(self) -> Self {
+                    $t::sqrt(self)
+                }
+
+                fn sin(self) -> Self {
+                    $t::sin(self)
+                }
+
+                fn cos(self) -> Self {
+                    $t::cos(self)
+                }
+
+                fn abs(self) -> Self {
+                    $t::abs(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric!(0, 1; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_numeric!(0.0, 1.0; f32, f64);
+impl_float!(f32, f64);
@@ -0,0 +1,248 @@
+use crate::generics::numeric::Numeric;
+use crate::generics::selection;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::thread;
+
+// Parallel reductions over slices.
+// The slice is split into one contiguous chunk per worker, every worker reduces its chunk on a scoped thread
+// and the partial results are combined in chunk order, so ties and NaN handling match the sequential
+// functions in `selection` exactly.
+// Scoped threads may borrow the slice because `thread::scope` joins all of them before it returns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parallel {
+    // Number of worker threads, 0 and 1 both mean sequential
+    pub threads: usize,
+    // Inputs shorter than this are reduced on the calling thread, because spawning threads costs more than it saves
+    pub sequential_threshold: usize,
+}
+
+// See `benches/parallel.rs` for measuring the crossover point on a given machine
+pub const DEFAULT_SEQUENTIAL_THRESHOLD: usize = 1 << 17;
+
+// Every worker gets at least this many items whatever the threshold, so tiny inputs never pay for a thread
+// per item
+const MIN_CHUNK_LEN: usize = 1024;
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            sequential_threshold: DEFAULT_SEQUENTIAL_THRESHOLD,
+        }
+    }
+}
+
+impl Parallel {
+    pub fn with_threads(threads: usize) -> Self {
+        Self {
+            threads,
+            ..Self::default()
+        }
+    }
+
+    // Applies `reduce` to every chunk together with the chunk's offset into `list`, results are in chunk order
+    fn map_chunks<'a, T, R, F>(&self, list: &'a [T], reduce: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(usize, &'a [T]) -> R + Sync,
+    {
+        let workers = self.threads.min(list.len() / MIN_CHUNK_LEN);
+        if workers <= 1 || list.len() < self.sequential_threshold {
+            return vec![reduce(0, list)];
+        }
+
+        let chunk_size = list.len().div_ceil(workers);
+        let reduce = &reduce;
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = list
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(i, chunk)| scope.spawn(move || reduce(i * chunk_size, chunk)))
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("Reduction worker panicked"))
+                .collect()
+        })
+    }
+
+    pub fn max<'a, T: PartialOrd + Sync>(&self, list: &'a [T]) -> Option<&'a T> {
+        let partial: Vec<&T> = self
+            .map_chunks(list, |_, chunk| selection::max(chunk))
+            .into_iter()
+            .flatten()
+            .collect();
+
+        selection::max(&partial).copied()
+    }
+
+    pub fn min<'a, T: PartialOrd + Sync>(&self, list: &'a [T]) -> Option<&'a T> {
+        let partial: Vec<&T> = self
+            .map_chunks(list, |_, chunk| selection::min(chunk))
+            .into_iter()
+            .flatten()
+            .collect();
+
+        selection::min(&partial).copied()
+    }
+
+    pub fn argmax<T: PartialOrd + Sync>(&self, list: &[T]) -> Option<usize> {
+        let partial: Vec<usize> = self
+            .map_chunks(list, |offset, chunk| {
+                selection::argmax(chunk).map(|index| offset + index)
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+
+        partial.into_iter().reduce(|largest, index| {
+            if list[index] > list[largest] {
+                index
+            } else {
+                largest
+            }
+        })
+    }
+
+    // Integer overflow behaves like the sequential `+` (panics in debug builds, wraps around in release builds).
+    // Float sums may differ in the last bits from a sequential sum, because the additions happen in a different order.
+    pub fn sum<T: Numeric + Send + Sync>(&self, list: &[T]) -> T {
+        self.map_chunks(list, |_, chunk| {
+            chunk.iter().fold(T::ZERO, |sum, &x| sum + x)
+        })
+        .into_iter()
+        .fold(T::ZERO, |sum, x| sum + x)
+    }
+
+    // Counts values into `bins` equally wide bins covering `range`, the upper bound falls into the last bin.
+    // Values outside the range and NaNs are not counted.
+    pub fn histogram<T: Numeric + Sync>(
+        &self,
+        list: &[T],
+        range: RangeInclusive<f64>,
+        bins: usize,
+    ) -> Vec<usize> {
+        if bins == 0 || range.start() >= range.end() {
+            return vec![0; bins];
+        }
+
+        self.map_chunks(list, |_, chunk| histogram(chunk, &range, bins))
+            .into_iter()
+            .reduce(|mut total, partial| {
+                total.iter_mut().zip(partial).for_each(|(t, p)| *t += p);
+                total
+            })
+            .unwrap_or_else(|| vec![0; bins])
+    }
+}
+
+fn histogram<T: Numeric>(list: &[T], range: &RangeInclusive<f64>, bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    let (start, end) = (*range.start(), *range.end());
+    let width = (end - start) / bins as f64;
+
+    for value in list.iter().map(|x| x.to_f64()) {
+        if range.contains(&value) {
+            let bin = ((value - start) / width) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A threshold of 0 forces the parallel path for inputs of at least two chunks
+    fn parallel(threads: usize) -> Parallel {
+        Parallel {
+            threads,
+            sequential_threshold: 0,
+        }
+    }
+
+    #[test]
+    fn matches_sequential_results() {
+        let numbers: Vec<i64> = (0..10_000).map(|i| (i * 7919) % 1009).collect();
+
+        for threads in [1, 2, 3, 8, 2000] {
+            let p = parallel(threads);
+
+            assert_eq!(p.max(&numbers), selection::max(&numbers));
+            assert_eq!(p.min(&numbers), selection::min(&numbers));
+            assert_eq!(p.argmax(&numbers), selection::argmax(&numbers));
+            assert_eq!(p.sum(&numbers), numbers.iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn argmax_returns_the_first_maximum_across_chunks() {
+        let mut numbers = vec![0; 3 * MIN_CHUNK_LEN];
+        for i in [MIN_CHUNK_LEN + 1, 2 * MIN_CHUNK_LEN, 2 * MIN_CHUNK_LEN + 3] {
+            numbers[i] = 5;
+        }
+        let first = MIN_CHUNK_LEN + 1;
+
+        assert_eq!(parallel(3).argmax(&numbers), Some(first));
+        assert_eq!(parallel(3).max(&numbers), Some(&numbers[first]));
+        assert!(std::ptr::eq(
+            parallel(3).max(&numbers).unwrap(),
+            &numbers[first]
+        ));
+    }
+
+    #[test]
+    fn empty_and_nan_inputs() {
+        let empty: [f64; 0] = [];
+        let mut floats = vec![f64::NAN; 3 * MIN_CHUNK_LEN];
+        floats[1] = 1.0;
+        floats[MIN_CHUNK_LEN + 3] = 3.0;
+
+        assert_eq!(parallel(4).max(&empty), None);
+        assert_eq!(parallel(4).sum(&empty), 0.0);
+        assert_eq!(parallel(3).max(&floats), Some(&3.0));
+        assert_eq!(parallel(3).min(&floats), Some(&1.0));
+        assert_eq!(parallel(3).argmax(&floats), Some(MIN_CHUNK_LEN + 3));
+        assert_eq!(parallel(6).max(&vec![f64::NAN; 2 * MIN_CHUNK_LEN]), None);
+    }
+
+    #[test]
+    fn histogram_counts_values_in_range() {
+        let values = [0.0, 0.5, 1.0, 2.5, 3.9, 4.0, 4.5, -1.0, f64::NAN].repeat(MIN_CHUNK_LEN);
+        let expected: Vec<usize> = [2, 1, 1, 2].map(|count| count * MIN_CHUNK_LEN).into();
+
+        assert_eq!(parallel(3).histogram(&values, 0.0..=4.0, 4), expected);
+        assert_eq!(parallel(1).histogram(&values, 0.0..=4.0, 4), expected);
+        assert_eq!(
+            parallel(3).histogram(&values, 0.0..=4.0, 0),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn small_inputs_stay_on_the_calling_thread() {
+        let p = Parallel {
+            threads: 4,
+            sequential_threshold: 10,
+        };
+
+        assert_eq!(p.map_chunks(&[1, 2, 3], |_, chunk| chunk.len()), vec![3]);
+
+        // Even without a threshold, no thread gets fewer than MIN_CHUNK_LEN items
+        let chunks = |len: usize| {
+            parallel(4)
+                .map_chunks(&vec![0; len], |_, chunk| chunk.len())
+                .len()
+        };
+        assert_eq!(chunks(3), 1);
+        assert_eq!(chunks(2 * MIN_CHUNK_LEN - 1), 1);
+        assert_eq!(chunks(2 * MIN_CHUNK_LEN), 2);
+        assert_eq!(chunks(100 * MIN_CHUNK_LEN), 4);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::generics::numeric::Numeric;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point<T, U> {
+    pub x: T,
+    pub y: U,
+}
+
+impl<T, U> Point<T, U> {
+    pub fn new(x: T, y: U) -> Self {
+        Self { x, y }
+    }
+
+    pub fn x(&self) -> &T {
+        &self.x
+    }
+
+    pub fn y(&self) -> &U {
+        &self.y
+    }
+
+    pub fn mixup<T2, U2>(self, other: Point<T2, U2>) -> Point<T, U2> {
+        Point {
+            x: self.x,
+            y: other.y,
+        }
+    }
+}
+
+// Conditionally implementing a method on a generic type depending on trait bounds (supports impl Trait syntax also)
+impl<T, U> Point<T, U>
+where
+    T: Display,
+    U: Display,
+{
+    pub fn print(&self) {
+        println!("({},{})", self.x, self.y)
+    }
+}
+
+// Distances are computed in f64 for every numeric type, so unsigned coordinates can't underflow and integers can't overflow
+impl<T, U> Point<T, U>
+where
+    T: Numeric,
+    U: Numeric,
+{
+    pub fn distance_from_origin(&self) -> f64 {
+        self.x.to_f64().hypot(self.y.to_f64())
+    }
+
+    pub fn euclidean_distance(&self, other: &Self) -> f64 {
+        let (dx, dy) = self.deltas(other);
+        dx.hypot(dy)
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> f64 {
+        let (dx, dy) = self.deltas(other);
+        dx.abs() + dy.abs()
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> f64 {
+        let (dx, dy) = self.deltas(other);
+        dx.abs().max(dy.abs())
+    }
+
+    pub fn midpoint(&self, other: &Self) -> Point<f64, f64> {
+        self.lerp(other, 0.5)
+    }
+
+    // Linear interpolation: t = 0.0 gives self, t = 1.0 gives other, values outside [0, 1] extrapolate
+    pub fn lerp(&self, other: &Self, t: f64) -> Point<f64, f64> {
+        let (dx, dy) = self.deltas(other);
+
+        Point {
+            x: self.x.to_f64() + dx * t,
+            y: self.y.to_f64() + dy * t,
+        }
+    }
+
+    fn deltas(&self, other: &Self) -> (f64, f64) {
+        (
+            other.x.to_f64() - self.x.to_f64(),
+            other.y.to_f64() - self.y.to_f64(),
+        )
+    }
+}
+
+// Operator overloading works component-wise, each coordinate type only needs to support the operator itself
+impl<T, U> Add for Point<T, U>
+where
+    T: Add<Output = T>,
+    U: Add<Output = U>,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl<T, U> Sub for Point<T, U>
+where
+    T: Sub<Output = T>,
+    U: Sub<Output = U>,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+// Scaling by a scalar: point * factor
+impl<T, U, S> Mul<S> for Point<T, U>
+where
+    T: Mul<S, Output = T>,
+    U: Mul<S, Output = U>,
+    S: Copy,
+{
+    type Output = Self;
+
+    fn mul(self, factor: S) -> Self {
+        Point {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+}
+
+// Every i32 is exactly representable as f64, so this direction can't fail
+impl From<Point<i32, i32>> for Point<f64, f64> {
+    fn from(point: Point<i32, i32>) -> Self {
+        Point {
+            x: f64::from(point.x),
+            y: f64::from(point.y),
+        }
+    }
+}
+
+// The other direction fails for fractional, out of range or non-finite coordinates instead of silently truncating
+impl TryFrom<Point<f64, f64>> for Point<i32, i32> {
+    type Error = LossyConversion;
+
+    fn try_from(point: Point<f64, f64>) -> Result<Self, Self::Error> {
+        match (exact_i32(point.x), exact_i32(point.y)) {
+            (Some(x), Some(y)) => Ok(Point { x, y }),
+            _ => Err(LossyConversion {
+                x: point.x,
+                y: point.y,
+            }),
+        }
+    }
+}
+
+fn exact_i32(value: f64) -> Option<i32> {
+    let in_range = value >= f64::from(i32::MIN) && value <= f64::from(i32::MAX);
+
+    if in_range && value.fract() == 0.0 {
+        Some(value as i32)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LossyConversion {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Display for LossyConversion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Point ({}, {}) can't be converted to Point<i32, i32> without loss",
+            self.x, self.y
+        )
+    }
+}
+
+impl Error for LossyConversion {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators_on_integers_and_floats() {
+        assert_eq!(Point::new(1, 2) + Point::new(3, 4), Point::new(4, 6));
+        assert_eq!(
+            Point::new(1.5, 2.0) - Point::new(0.5, 1.0),
+            Point::new(1.0, 1.0)
+        );
+        assert_eq!(Point::new(2u8, 3u8) * 2u8, Point::new(4, 6));
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1u32, 5u32);
+        let b = Point::new(4u32, 1u32);
+
+        assert_eq!(a.euclidean_distance(&b), 5.0);
+        assert_eq!(a.manhattan_distance(&b), 7.0);
+        assert_eq!(a.chebyshev_distance(&b), 4.0);
+        assert_eq!(b.euclidean_distance(&a), 5.0);
+        assert_eq!(Point::new(3, -4).distance_from_origin(), 5.0);
+    }
+
+    #[test]
+    fn midpoint_and_lerp() {
+        let a = Point::new(0, 0);
+        let b = Point::new(3, 10);
+
+        assert_eq!(a.midpoint(&b), Point::new(1.5, 5.0));
+        assert_eq!(a.lerp(&b, 0.0), Point::new(0.0, 0.0));
+        assert_eq!(a.lerp(&b, 2.0), Point::new(6.0, 20.0));
+    }
+
+    #[test]
+    fn conversions_report_loss() {
+        let float: Point<f64, f64> = Point::new(-7, 8).into();
+        assert_eq!(float, Point::new(-7.0, 8.0));
+        assert_eq!(Point::<i32, i32>::try_from(float), Ok(Point::new(-7, 8)));
+
+        assert!(Point::<i32, i32>::try_from(Point::new(1.5, 2.0)).is_err());
+        assert!(Point::<i32, i32>::try_from(Point::new(1e10, 2.0)).is_err());
+        assert!(Point::<i32, i32>::try_from(Point::new(f64::NAN, 2.0)).is_err());
+    }
+}
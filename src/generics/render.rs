@@ -0,0 +1,295 @@
+mod html;
+mod markdown;
+mod plain;
+
+use crate::generics::feed::Feed;
+use crate::generics::syndication::rfc3339;
+use crate::generics::threads::ThreadNode;
+use crate::generics::traits::{NewsArticle, Tweet};
+
+pub use crate::generics::render::html::Html;
+pub use crate::generics::render::markdown::Markdown;
+pub use crate::generics::render::plain::PlainText;
+
+// Human readable output for Summary items in several formats.
+//
+// Items describe themselves as a list of blocks (headings, paragraphs, lists...) once, and every backend
+// turns blocks into its own format and takes care of escaping. Adding a format means adding a backend,
+// adding a renderable type means implementing `Render::blocks`.
+
+// Text inside a block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Strong(String),
+    Emphasis(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    // Level 1 is the page title, items use level 2 and below
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    Quote(Vec<Inline>),
+    List(Vec<Vec<Inline>>),
+    Rule,
+}
+
+pub trait Backend {
+    fn block(&self, block: &Block) -> String;
+
+    // Wraps rendered blocks into a complete document
+    fn page(&self, title: &str, body: &str) -> String;
+
+    // Blocks are separated by an empty line in every format
+    fn blocks(&self, blocks: &[Block]) -> String {
+        blocks
+            .iter()
+            .map(|block| self.block(block))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+pub trait Render {
+    fn blocks(&self) -> Vec<Block>;
+
+    fn render(&self, backend: &dyn Backend) -> String {
+        backend.blocks(&self.blocks())
+    }
+}
+
+fn text(text: &str) -> Inline {
+    Inline::Text(String::from(text))
+}
+
+// Plain text of inlines, used where a format has no markup
+pub fn plain(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) | Inline::Strong(text) | Inline::Emphasis(text) => text.as_str(),
+        })
+        .collect()
+}
+
+impl Render for NewsArticle {
+    fn blocks(&self) -> Vec<Block> {
+        let mut byline = vec![text("By "), Inline::Strong(self.author.clone())];
+        if !self.location.is_empty() {
+            byline.push(text(", "));
+            byline.push(Inline::Emphasis(self.location.clone()));
+        }
+
+        vec![
+            Block::Heading(2, vec![text(&self.headline)]),
+            Block::Paragraph(byline),
+            Block::Paragraph(vec![text(&self.content)]),
+        ]
+    }
+}
+
+impl Render for Tweet {
+    fn blocks(&self) -> Vec<Block> {
+        let mut blocks = vec![Block::Quote(vec![
+            Inline::Strong(format!("@{}", self.username)),
+            text(": "),
+            text(&self.content),
+        ])];
+
+        let context = match (self.in_reply_to, self.retweet_of) {
+            (Some(id), _) => Some(format!("In reply to tweet {id}")),
+            (None, Some(id)) => Some(format!("Retweet of tweet {id}")),
+            (None, None) => None,
+        };
+        blocks.extend(context.map(|context| Block::Paragraph(vec![Inline::Emphasis(context)])));
+
+        blocks
+    }
+}
+
+// Replies become nested list entries, indented by one level per reply depth
+impl Render for ThreadNode<'_> {
+    fn blocks(&self) -> Vec<Block> {
+        let entries = self
+            .walk()
+            .map(|(level, node)| {
+                let mut entry = Vec::new();
+                if level > 0 {
+                    entry.push(text(&format!("{} ", "↳".repeat(level))));
+                }
+                entry.push(Inline::Strong(format!("@{}", node.tweet.username)));
+                entry.push(text(": "));
+                entry.push(text(&node.tweet.content));
+                entry
+            })
+            .collect();
+        vec![Block::List(entries)]
+    }
+}
+
+// Feed items are only known as Summary trait objects, so every item is rendered as its summary
+impl Render for Feed {
+    fn blocks(&self) -> Vec<Block> {
+        let entries = self
+            .items()
+            .map(|item| {
+                vec![
+                    Inline::Emphasis(rfc3339(item.timestamp)),
+                    text(" "),
+                    Inline::Strong(item.source.clone()),
+                    text(": "),
+                    text(&item.item.summarize()),
+                ]
+            })
+            .collect();
+
+        vec![
+            Block::Heading(2, vec![text(&self.name)]),
+            Block::List(entries),
+        ]
+    }
+}
+
+// A breaking_news style announcement of a single item as a complete document
+pub fn bulletin(item: &dyn Render, backend: &dyn Backend) -> String {
+    let mut blocks = vec![Block::Paragraph(vec![Inline::Strong(String::from(
+        "Breaking news!",
+    ))])];
+    blocks.extend(item.blocks());
+
+    backend.page("Breaking news", &backend.blocks(&blocks))
+}
+
+// Several items as one page, separated by horizontal rules
+pub fn newsletter(title: &str, items: &[&dyn Render], backend: &dyn Backend) -> String {
+    let mut blocks = Vec::new();
+
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            blocks.push(Block::Rule);
+        }
+        blocks.extend(item.blocks());
+    }
+
+    backend.page(title, &backend.blocks(&blocks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::threads::build_threads;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // Compares with src/generics/render/snapshots/<name>.
+    // Run the tests with UPDATE_SNAPSHOTS=1 to write the current output instead, then review the diff.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "src/generics/render/snapshots",
+            name,
+        ]
+        .iter()
+        .collect();
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|error| panic!("Missing snapshot {}: {error}", path.display()));
+        assert_eq!(actual, expected, "Output differs from snapshot {name}");
+    }
+
+    fn article() -> NewsArticle {
+        NewsArticle {
+            headline: String::from("Penguins win the <Stanley Cup> & more!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(
+                "The Pittsburgh Penguins once again are the *best* hockey team in the NHL. \
+                 Fans celebrated with \"chants\" until the early morning hours across the city.",
+            ),
+        }
+    }
+
+    fn tweets() -> Vec<Tweet> {
+        let tweet = |id, in_reply_to, retweet_of, content: &str| Tweet {
+            username: format!("fan_{id}"),
+            content: String::from(content),
+            id,
+            in_reply_to,
+            retweet_of,
+        };
+
+        vec![
+            tweet(1, None, None, "What a game! #LetsGoPens"),
+            tweet(2, Some(1), None, "Best <3rd> period ever"),
+            tweet(3, Some(2), None, "Agreed_100%"),
+            tweet(4, None, Some(1), "What a game! #LetsGoPens"),
+        ]
+    }
+
+    fn newsletter_page(backend: &dyn Backend) -> String {
+        let tweets = tweets();
+        let threads = build_threads(&tweets);
+
+        let mut feed = Feed::new("digest");
+        feed.push(1_700_000_000, Box::new(article()));
+        feed.push(1_700_000_060, Box::new(self::tweets().remove(0)));
+
+        newsletter(
+            "Hockey weekly",
+            &[&article(), &tweets[3], &threads.roots[0], &feed],
+            backend,
+        )
+    }
+
+    #[test]
+    fn markdown_snapshot() {
+        assert_snapshot("newsletter.md", &newsletter_page(&Markdown));
+        assert_snapshot("bulletin.md", &bulletin(&article(), &Markdown));
+    }
+
+    // Text that would turn into markup at the start of any of its lines
+    #[test]
+    fn markdown_escapes_every_line() {
+        let article = NewsArticle {
+            headline: String::from("1. Penguins\n# win"),
+            location: String::from("Pittsburgh"),
+            author: String::from("Iceburgh"),
+            content: String::from(
+                "2) Final score:\n# 4 to 2\n- Crosby scored\r\n===\n\n```\n~~~\n  + 10. goal",
+            ),
+        };
+        let tweet = Tweet {
+            username: String::from("fan"),
+            content: String::from("Line one\n> not a quote\n3. not a list"),
+            id: 1,
+            in_reply_to: None,
+            retweet_of: None,
+        };
+
+        assert_snapshot(
+            "escaping.md",
+            &newsletter("Escaping", &[&article, &tweet], &Markdown),
+        );
+    }
+
+    #[test]
+    fn html_snapshot() {
+        assert_snapshot("newsletter.html", &newsletter_page(&Html));
+        assert_snapshot("bulletin.html", &bulletin(&article(), &Html));
+    }
+
+    #[test]
+    fn plain_text_snapshot() {
+        let backend = PlainText { width: 40 };
+
+        assert_snapshot("newsletter.txt", &newsletter_page(&backend));
+        assert_snapshot("bulletin.txt", &bulletin(&article(), &backend));
+    }
+}
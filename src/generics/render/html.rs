@@ -0,0 +1,72 @@
+use crate::generics::render::{Backend, Block, Inline};
+
+// A standalone HTML5 document, all text is escaped
+pub struct Html;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn inlines(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Strong(text) => format!("<strong>{}</strong>", escape(text)),
+            Inline::Emphasis(text) => format!("<em>{}</em>", escape(text)),
+        })
+        .collect()
+}
+
+impl Backend for Html {
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading(level, text) => {
+                // HTML only has six heading levels
+                let level = (*level).clamp(1, 6);
+                format!("<h{level}>{}</h{level}>", inlines(text))
+            }
+            Block::Paragraph(text) => format!("<p>{}</p>", inlines(text)),
+            Block::Quote(text) => format!("<blockquote><p>{}</p></blockquote>", inlines(text)),
+            Block::List(entries) => {
+                let items: String = entries
+                    .iter()
+                    .map(|entry| format!("  <li>{}</li>\n", inlines(entry)))
+                    .collect();
+                format!("<ul>\n{items}</ul>")
+            }
+            Block::Rule => String::from("<hr>"),
+        }
+    }
+
+    fn page(&self, title: &str, body: &str) -> String {
+        let title = escape(title);
+
+        format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n\
+             </head>\n\
+             <body>\n\
+             <h1>{title}</h1>\n\
+             {body}\n\
+             </body>\n\
+             </html>\n"
+        )
+    }
+}
@@ -0,0 +1,88 @@
+use crate::generics::render::{Backend, Block, Inline};
+
+// CommonMark output, text is escaped so it never turns into markup
+pub struct Markdown;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Characters that start a heading, list, quote or code fence at the beginning of a line must be escaped
+// there too, as must the "1." or "1)" of an ordered list
+fn escape_line_start(line: &str) -> String {
+    match line.chars().next() {
+        Some('#' | '-' | '+' | '=' | '~') => format!("\\{line}"),
+        Some('0'..='9') => {
+            let number = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let (number, rest) = line.split_at(number);
+
+            if rest.starts_with(['.', ')']) {
+                format!("{number}\\{rest}")
+            } else {
+                line.to_string()
+            }
+        }
+        _ => line.to_string(),
+    }
+}
+
+// Every line of the text is escaped and the lines are joined with `separator`. Empty lines are left out,
+// they would end the block.
+fn inline_lines(inlines: &[Inline], separator: &str) -> String {
+    let text: String = inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Strong(text) => format!("**{}**", escape(text)),
+            Inline::Emphasis(text) => format!("*{}*", escape(text)),
+        })
+        .collect();
+
+    text.lines()
+        .map(str::trim_start)
+        .filter(|line| !line.trim_end().is_empty())
+        .map(escape_line_start)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+// Line breaks in the text become hard breaks, a backslash at the end of the line
+fn inlines(inlines: &[Inline]) -> String {
+    inline_lines(inlines, "\\\n")
+}
+
+impl Backend for Markdown {
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading(level, text) => {
+                // A heading can't go on in the next line
+                let text = inline_lines(text, " ");
+                format!("{} {text}", "#".repeat(usize::from(*level)))
+            }
+            Block::Paragraph(text) => inlines(text),
+            Block::Quote(text) => format!("> {}", inlines(text)),
+            Block::List(entries) => entries
+                .iter()
+                .map(|entry| format!("- {}", inlines(entry)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Rule => String::from("---"),
+        }
+    }
+
+    fn page(&self, title: &str, body: &str) -> String {
+        format!("# {}\n\n{body}\n", escape(title))
+    }
+}
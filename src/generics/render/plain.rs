@@ -0,0 +1,81 @@
+use crate::generics::render::{plain, Backend, Block};
+
+// Plain text wrapped at `width` characters, headings are underlined
+pub struct PlainText {
+    pub width: usize,
+}
+
+impl Default for PlainText {
+    fn default() -> Self {
+        Self { width: 72 }
+    }
+}
+
+impl PlainText {
+    // Greedy word wrapping. Continuation lines start with `indent`, words longer than a line get a line of their own.
+    fn wrap(&self, text: &str, first_prefix: &str, indent: &str) -> String {
+        let mut lines = Vec::new();
+        let mut line = String::from(first_prefix);
+        let mut empty = true;
+
+        for word in text.split_whitespace() {
+            let length = line.chars().count() + usize::from(!empty) + word.chars().count();
+
+            if !empty && length > self.width {
+                lines.push(line);
+                line = String::from(indent);
+                empty = true;
+            }
+
+            if !empty {
+                line.push(' ');
+            }
+            line.push_str(word);
+            empty = false;
+        }
+
+        lines.push(line);
+        lines.join("\n")
+    }
+
+    fn heading(&self, text: &str, underline: Option<char>) -> String {
+        let heading = self.wrap(text, "", "");
+        let Some(underline) = underline else {
+            return heading;
+        };
+
+        let length = heading
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        format!("{heading}\n{}", underline.to_string().repeat(length))
+    }
+}
+
+impl Backend for PlainText {
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading(level, text) => {
+                let underline = match level {
+                    0 | 1 => Some('='),
+                    2 => Some('-'),
+                    _ => None,
+                };
+                self.heading(&plain(text), underline)
+            }
+            Block::Paragraph(text) => self.wrap(&plain(text), "", ""),
+            Block::Quote(text) => self.wrap(&plain(text), "| ", "| "),
+            Block::List(entries) => entries
+                .iter()
+                .map(|entry| self.wrap(&plain(entry), "- ", "  "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Rule => "-".repeat(self.width),
+        }
+    }
+
+    fn page(&self, title: &str, body: &str) -> String {
+        format!("{}\n\n{body}\n", self.heading(title, Some('=')))
+    }
+}
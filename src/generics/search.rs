@@ -0,0 +1,376 @@
+mod porter;
+mod query;
+pub mod storage;
+
+use crate::generics::text;
+use crate::generics::traits::{NewsArticle, Tweet};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub use crate::generics::search::porter::stem;
+pub use crate::generics::search::query::{parse, Query, QueryError};
+
+// Inverted index over the text of NewsArticles and Tweets with BM25 ranking.
+//
+// Text is split into lowercase words, stopwords are dropped and the rest is stemmed, so "Penguins" matches
+// "penguin". Every term maps to the documents containing it and the positions of the term in each document,
+// the positions make phrase queries possible. Documents can be added and removed at any time.
+
+// Chosen by the caller, e.g. the tweet id or the position of an article in a list
+pub type DocId = u64;
+
+// Word number inside a document, stopwords count too so phrases with stopwords keep their spacing
+pub type Position = u32;
+
+// Types whose text can be indexed
+pub trait Searchable {
+    // Text fields in order, phrases never match across two fields
+    fn searchable_fields(&self) -> Vec<&str>;
+}
+
+impl Searchable for NewsArticle {
+    fn searchable_fields(&self) -> Vec<&str> {
+        vec![&self.headline, &self.content]
+    }
+}
+
+impl Searchable for Tweet {
+    fn searchable_fields(&self) -> Vec<&str> {
+        vec![&self.content]
+    }
+}
+
+// Gap between the positions of two fields, larger than any reasonable phrase
+const FIELD_GAP: Position = 100;
+
+// Usual BM25 parameters: term frequency saturation and document length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub id: DocId,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Document {
+    // Number of indexed terms, stopwords not included
+    length: u32,
+    // Distinct terms, so removing a document doesn't need to scan the whole vocabulary
+    terms: Vec<String>,
+}
+
+// BTreeMaps keep the saved index and the order of equally scored hits deterministic
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchIndex {
+    postings: BTreeMap<String, BTreeMap<DocId, Vec<Position>>>,
+    documents: BTreeMap<DocId, Document>,
+    total_length: u64,
+}
+
+// Terms of `text` with their positions
+pub fn analyze(text: &str) -> Vec<(Position, String)> {
+    text::words(text)
+        .zip(0..)
+        .filter(|(word, _)| !text::is_stopword(word))
+        .map(|(word, position)| {
+            let word = word.strip_suffix("'s").unwrap_or(&word);
+            (position, stem(word))
+        })
+        .collect()
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn contains(&self, id: DocId) -> bool {
+        self.documents.contains_key(&id)
+    }
+
+    // Indexes `item` under `id`, replacing whatever was indexed under that id before
+    pub fn add(&mut self, id: DocId, item: &impl Searchable) {
+        self.remove(id);
+
+        let mut positions: BTreeMap<String, Vec<Position>> = BTreeMap::new();
+        let mut offset = 0;
+        let mut length = 0;
+
+        for field in item.searchable_fields() {
+            let terms = analyze(field);
+            let mut end = offset;
+
+            for (position, term) in terms {
+                positions.entry(term).or_default().push(offset + position);
+                end = offset + position + 1;
+                length += 1;
+            }
+            offset = end + FIELD_GAP;
+        }
+
+        let terms = positions.keys().cloned().collect();
+        for (term, positions) in positions {
+            self.postings.entry(term).or_default().insert(id, positions);
+        }
+
+        self.total_length += u64::from(length);
+        self.documents.insert(id, Document { length, terms });
+    }
+
+    // Returns false if nothing was indexed under `id`
+    pub fn remove(&mut self, id: DocId) -> bool {
+        let Some(document) = self.documents.remove(&id) else {
+            return false;
+        };
+
+        for term in &document.terms {
+            if let Some(documents) = self.postings.get_mut(term) {
+                documents.remove(&id);
+
+                if documents.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+
+        self.total_length -= u64::from(document.length);
+        true
+    }
+
+    // Parses `query` and returns the matching documents, best first
+    pub fn search(&self, query: &str) -> Result<Vec<Hit>, QueryError> {
+        Ok(self.search_query(&parse(query)?))
+    }
+
+    pub fn search_query(&self, query: &Query) -> Vec<Hit> {
+        let mut scored_terms = BTreeSet::new();
+        collect_scored_terms(query, &mut scored_terms);
+
+        let mut hits: Vec<Hit> = self
+            .matches(query)
+            .into_iter()
+            .map(|id| Hit {
+                id,
+                score: scored_terms.iter().map(|term| self.bm25(term, id)).sum(),
+            })
+            .collect();
+
+        // Best score first, lower ids win ties
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits
+    }
+
+    fn matches(&self, query: &Query) -> BTreeSet<DocId> {
+        match query {
+            Query::Term(term) => self
+                .postings
+                .get(term)
+                .map(|documents| documents.keys().copied().collect())
+                .unwrap_or_default(),
+            Query::Phrase(terms) => self.phrase_matches(terms),
+            Query::And(parts) => {
+                let mut parts = parts.iter().map(|part| self.matches(part));
+                let first = parts.next().unwrap_or_default();
+
+                parts.fold(first, |all, part| &all & &part)
+            }
+            Query::Or(parts) => parts.iter().flat_map(|part| self.matches(part)).collect(),
+            Query::Not(part) => {
+                let excluded = self.matches(part);
+
+                self.documents
+                    .keys()
+                    .copied()
+                    .filter(|id| !excluded.contains(id))
+                    .collect()
+            }
+        }
+    }
+
+    // Documents where every term appears at its offset from the first term
+    fn phrase_matches(&self, terms: &[(Position, String)]) -> BTreeSet<DocId> {
+        let Some(postings) = terms
+            .iter()
+            .map(|(offset, term)| Some((*offset, self.postings.get(term)?)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return BTreeSet::new();
+        };
+
+        let Some(&(first_offset, first)) = postings.first() else {
+            return BTreeSet::new();
+        };
+
+        first
+            .iter()
+            .filter(|(id, starts)| {
+                starts.iter().any(|&start| {
+                    postings.iter().all(|&(offset, documents)| {
+                        let Some(position) = (start + offset).checked_sub(first_offset) else {
+                            return false;
+                        };
+                        documents
+                            .get(id)
+                            .is_some_and(|positions| positions.binary_search(&position).is_ok())
+                    })
+                })
+            })
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    fn bm25(&self, term: &str, id: DocId) -> f64 {
+        let Some(documents) = self.postings.get(term) else {
+            return 0.0;
+        };
+        let Some(positions) = documents.get(&id) else {
+            return 0.0;
+        };
+
+        let count = self.documents.len() as f64;
+        let frequency = documents.len() as f64;
+        let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+
+        let tf = positions.len() as f64;
+        let length = f64::from(self.documents[&id].length);
+        let average_length = self.total_length as f64 / count;
+
+        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length))
+    }
+}
+
+// Terms that contribute to the score, terms under a NOT only filter
+fn collect_scored_terms<'a>(query: &'a Query, terms: &mut BTreeSet<&'a str>) {
+    match query {
+        Query::Term(term) => {
+            terms.insert(term);
+        }
+        Query::Phrase(phrase) => terms.extend(phrase.iter().map(|(_, term)| term.as_str())),
+        Query::And(parts) | Query::Or(parts) => {
+            for part in parts {
+                collect_scored_terms(part, terms);
+            }
+        }
+        Query::Not(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(headline: &str, content: &str) -> NewsArticle {
+        NewsArticle {
+            headline: String::from(headline),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(content),
+        }
+    }
+
+    fn tweet(id: u64, content: &str) -> Tweet {
+        Tweet {
+            username: String::from("fan"),
+            content: String::from(content),
+            id,
+            in_reply_to: None,
+            retweet_of: None,
+        }
+    }
+
+    fn sample() -> SearchIndex {
+        let mut index = SearchIndex::new();
+        index.add(
+            1,
+            &article(
+                "Penguins win the Stanley Cup",
+                "The Pittsburgh Penguins are the best hockey team in the NHL.",
+            ),
+        );
+        index.add(2, &tweet(2, "Watching the Flyers play hockey tonight"));
+        index.add(3, &tweet(3, "Penguins! Penguins! The cup is ours"));
+        index.add(
+            4,
+            &article("Zoo news", "A new penguin was born at the zoo."),
+        );
+        index
+    }
+
+    fn ids(hits: &[Hit]) -> Vec<DocId> {
+        hits.iter().map(|hit| hit.id).collect()
+    }
+
+    #[test]
+    fn stemmed_terms_match_word_forms() {
+        let index = sample();
+
+        let hits = index.search("penguin").unwrap();
+
+        assert_eq!(hits.len(), 3);
+        assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        // Two mentions in a short tweet beat one mention in a longer article
+        assert_eq!(hits[0].id, 3);
+    }
+
+    #[test]
+    fn boolean_operators() {
+        let index = sample();
+
+        assert_eq!(ids(&index.search("penguins hockey").unwrap()), vec![1]);
+        assert_eq!(ids(&index.search("flyers OR zoo").unwrap()).len(), 2);
+        assert_eq!(
+            ids(&index.search("penguins NOT zoo NOT cup").unwrap()),
+            Vec::<DocId>::new()
+        );
+        assert_eq!(ids(&index.search("hockey NOT penguins").unwrap()), vec![2]);
+    }
+
+    #[test]
+    fn phrases_need_adjacent_terms_in_one_field() {
+        let index = sample();
+
+        assert_eq!(ids(&index.search("\"stanley cup\"").unwrap()), vec![1]);
+        assert_eq!(
+            ids(&index.search("\"cup stanley\"").unwrap()),
+            Vec::<DocId>::new()
+        );
+        // Stopwords aren't indexed, but they still keep the other words apart
+        assert_eq!(
+            ids(&index.search("\"best of the NHL\"").unwrap()),
+            Vec::<DocId>::new()
+        );
+        assert_eq!(ids(&index.search("\"best hockey team\"").unwrap()), vec![1]);
+        // Last word of the headline and first word of the content
+        assert_eq!(
+            ids(&index.search("\"cup pittsburgh\"").unwrap()),
+            Vec::<DocId>::new()
+        );
+    }
+
+    #[test]
+    fn documents_can_be_replaced_and_removed() {
+        let mut index = sample();
+
+        index.add(2, &tweet(2, "Now watching the Penguins"));
+        assert_eq!(index.len(), 4);
+        assert!(index.search("flyers").unwrap().is_empty());
+
+        assert!(index.remove(3));
+        assert!(!index.remove(3));
+        assert_eq!(ids(&index.search("penguins").unwrap()), vec![2, 1, 4]);
+
+        for id in [1, 2, 4] {
+            index.remove(id);
+        }
+        assert_eq!(index, SearchIndex::new());
+    }
+}
@@ -0,0 +1,287 @@
+// The Porter stemming algorithm for English (M.F. Porter, 1980), following the reference implementation.
+// Stemming maps related words onto one index term: "connected", "connecting" and "connection" all become "connect".
+// Stems aren't always words themselves ("happy" -> "happi"), they only need to be the same for related words.
+
+// Only lowercase ASCII words are stemmed, anything else is returned unchanged
+pub fn stem(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return String::from(word);
+    }
+
+    let mut word = word.as_bytes().to_vec();
+
+    step_1a(&mut word);
+    step_1b(&mut word);
+    step_1c(&mut word);
+    step_2(&mut word);
+    step_3(&mut word);
+    step_4(&mut word);
+    step_5(&mut word);
+
+    String::from_utf8(word).expect("Stemming only removes or adds ASCII letters")
+}
+
+// 'y' is a consonant at the start of a word or after a vowel
+fn is_consonant(word: &[u8], i: usize) -> bool {
+    match word[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(word, i - 1),
+        _ => true,
+    }
+}
+
+// Every word has the form [C](VC)^m[V] with C and V runs of consonants and vowels, this returns m
+fn measure(word: &[u8]) -> usize {
+    let n = word.len();
+    let mut i = 0;
+    let mut m = 0;
+
+    while i < n && is_consonant(word, i) {
+        i += 1;
+    }
+
+    loop {
+        while i < n && !is_consonant(word, i) {
+            i += 1;
+        }
+        if i == n {
+            return m;
+        }
+
+        while i < n && is_consonant(word, i) {
+            i += 1;
+        }
+        m += 1;
+        if i == n {
+            return m;
+        }
+    }
+}
+
+fn contains_vowel(word: &[u8]) -> bool {
+    (0..word.len()).any(|i| !is_consonant(word, i))
+}
+
+fn ends_with_double_consonant(word: &[u8]) -> bool {
+    let n = word.len();
+    n >= 2 && word[n - 1] == word[n - 2] && is_consonant(word, n - 1)
+}
+
+// Consonant-vowel-consonant where the last consonant isn't w, x or y, as in "hop" but not "snow"
+fn ends_with_cvc(word: &[u8]) -> bool {
+    let n = word.len();
+    n >= 3
+        && is_consonant(word, n - 3)
+        && !is_consonant(word, n - 2)
+        && is_consonant(word, n - 1)
+        && !matches!(word[n - 1], b'w' | b'x' | b'y')
+}
+
+fn stem_before<'a>(word: &'a [u8], suffix: &str) -> &'a [u8] {
+    &word[..word.len() - suffix.len()]
+}
+
+fn replace_suffix(word: &mut Vec<u8>, suffix: &str, replacement: &str) {
+    word.truncate(word.len() - suffix.len());
+    word.extend_from_slice(replacement.as_bytes());
+}
+
+// Replaces the longest matching suffix if the remaining stem satisfies `condition`.
+// Only the longest match is considered, when its condition fails the word stays as it is.
+fn replace_longest(word: &mut Vec<u8>, rules: &[(&str, &str)], condition: impl Fn(&[u8]) -> bool) {
+    let longest = rules
+        .iter()
+        .filter(|(suffix, _)| word.ends_with(suffix.as_bytes()) && word.len() > suffix.len())
+        .max_by_key(|(suffix, _)| suffix.len());
+
+    if let Some(&(suffix, replacement)) = longest {
+        if condition(stem_before(word, suffix)) {
+            replace_suffix(word, suffix, replacement);
+        }
+    }
+}
+
+// Plurals: "caresses" -> "caress", "ponies" -> "poni", "cats" -> "cat"
+fn step_1a(word: &mut Vec<u8>) {
+    if word.ends_with(b"sses") || word.ends_with(b"ies") {
+        word.truncate(word.len() - 2);
+    } else if word.ends_with(b"s") && !word.ends_with(b"ss") {
+        word.pop();
+    }
+}
+
+// Past tense and progressive: "agreed" -> "agree", "hopping" -> "hop", "filing" -> "file"
+fn step_1b(word: &mut Vec<u8>) {
+    if word.ends_with(b"eed") {
+        if measure(stem_before(word, "eed")) > 0 {
+            word.pop();
+        }
+        return;
+    }
+
+    let suffix = if word.ends_with(b"ed") {
+        "ed"
+    } else if word.ends_with(b"ing") {
+        "ing"
+    } else {
+        return;
+    };
+
+    if !contains_vowel(stem_before(word, suffix)) {
+        return;
+    }
+    replace_suffix(word, suffix, "");
+
+    if word.ends_with(b"at") || word.ends_with(b"bl") || word.ends_with(b"iz") {
+        word.push(b'e');
+    } else if ends_with_double_consonant(word) && !matches!(word.last(), Some(b'l' | b's' | b'z')) {
+        word.pop();
+    } else if measure(word) == 1 && ends_with_cvc(word) {
+        word.push(b'e');
+    }
+}
+
+// "happy" -> "happi", but "sky" stays
+fn step_1c(word: &mut [u8]) {
+    let n = word.len();
+    if word[n - 1] == b'y' && contains_vowel(&word[..n - 1]) {
+        word[n - 1] = b'i';
+    }
+}
+
+// Double suffixes to single ones: "relational" -> "relate", "hopefulness" -> "hopeful"
+fn step_2(word: &mut Vec<u8>) {
+    const RULES: [(&str, &str); 21] = [
+        ("ational", "ate"),
+        ("tional", "tion"),
+        ("enci", "ence"),
+        ("anci", "ance"),
+        ("izer", "ize"),
+        ("bli", "ble"),
+        ("alli", "al"),
+        ("entli", "ent"),
+        ("eli", "e"),
+        ("ousli", "ous"),
+        ("ization", "ize"),
+        ("ation", "ate"),
+        ("ator", "ate"),
+        ("alism", "al"),
+        ("iveness", "ive"),
+        ("fulness", "ful"),
+        ("ousness", "ous"),
+        ("aliti", "al"),
+        ("iviti", "ive"),
+        ("biliti", "ble"),
+        ("logi", "log"),
+    ];
+
+    replace_longest(word, &RULES, |stem| measure(stem) > 0);
+}
+
+// "-ic-", "-full", "-ness" and similar: "hopeful" -> "hope", "goodness" -> "good"
+fn step_3(word: &mut Vec<u8>) {
+    const RULES: [(&str, &str); 7] = [
+        ("icate", "ic"),
+        ("ative", ""),
+        ("alize", "al"),
+        ("iciti", "ic"),
+        ("ical", "ic"),
+        ("ful", ""),
+        ("ness", ""),
+    ];
+
+    replace_longest(word, &RULES, |stem| measure(stem) > 0);
+}
+
+// Remaining suffixes of longer words: "adjustment" -> "adjust", "adoption" -> "adopt"
+fn step_4(word: &mut Vec<u8>) {
+    const SUFFIXES: [&str; 19] = [
+        "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent", "ion",
+        "ou", "ism", "ate", "iti", "ous", "ive", "ize",
+    ];
+
+    let rules = SUFFIXES.map(|suffix| (suffix, ""));
+    let is_ion = word.ends_with(b"ion");
+
+    replace_longest(word, &rules, |stem| {
+        measure(stem) > 1 && (!is_ion || matches!(stem.last(), Some(b's' | b't')))
+    });
+}
+
+// A final "e" and double "l": "probate" -> "probat", "controll" -> "control"
+fn step_5(word: &mut Vec<u8>) {
+    if word.ends_with(b"e") {
+        let stem = stem_before(word, "e");
+        let m = measure(stem);
+
+        if m > 1 || (m == 1 && !ends_with_cvc(stem)) {
+            word.pop();
+        }
+    }
+
+    if word.ends_with(b"ll") && measure(word) > 1 {
+        word.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stems_the_examples_from_the_paper() {
+        let examples = [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("feed", "feed"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("motoring", "motor"),
+            ("sing", "sing"),
+            ("conflated", "conflat"),
+            ("hopping", "hop"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("sky", "sky"),
+            ("relational", "relat"),
+            ("generalization", "gener"),
+            ("hopeful", "hope"),
+            ("goodness", "good"),
+            ("adjustment", "adjust"),
+            ("adoption", "adopt"),
+            ("probate", "probat"),
+            ("rate", "rate"),
+            ("controlling", "control"),
+            ("roll", "roll"),
+        ];
+
+        for (word, expected) in examples {
+            assert_eq!(stem(word), expected, "stem of {word}");
+        }
+    }
+
+    #[test]
+    fn related_words_share_a_stem() {
+        let stems: Vec<String> = [
+            "connect",
+            "connected",
+            "connecting",
+            "connection",
+            "connections",
+        ]
+        .iter()
+        .map(|word| stem(word))
+        .collect();
+
+        assert!(stems.iter().all(|s| s == "connect"), "{stems:?}");
+    }
+
+    #[test]
+    fn leaves_short_and_non_ascii_words_alone() {
+        assert_eq!(stem("is"), "is");
+        assert_eq!(stem("don't"), "don't");
+        assert_eq!(stem("café"), "café");
+        assert_eq!(stem("2024"), "2024");
+    }
+}
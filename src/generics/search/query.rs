@@ -0,0 +1,266 @@
+use crate::generics::search::{analyze, Position};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+// Query syntax:
+//     penguins cup            both terms, adjacent terms are combined with AND
+//     penguins OR flyers      either term
+//     penguins NOT flyers     the first term but not the second
+//     "stanley cup"           the words next to each other in this order
+//     (penguins OR flyers) AND cup
+// NOT binds tighter than AND, which binds tighter than OR. The operators must be written in uppercase,
+// lowercase "and", "or" and "not" are ordinary words. Stopwords are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    // A stemmed term
+    Term(String),
+    // Stemmed terms with their offset from the first term, stopwords leave gaps in the offsets
+    Phrase(Vec<(Position, String)>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    // Byte offset into the query string
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query at {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Quoted(&'a str),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn error(position: usize, message: &str) -> QueryError {
+    QueryError {
+        position,
+        message: String::from(message),
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<(usize, Token<'_>)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push((start, Token::Open)),
+            ')' => tokens.push((start, Token::Close)),
+            '"' => {
+                let end = query[start + 1..]
+                    .find('"')
+                    .map(|i| start + 1 + i)
+                    .ok_or_else(|| error(start, "Unterminated phrase"))?;
+
+                tokens.push((start, Token::Quoted(&query[start + 1..end])));
+                while chars.next_if(|&(i, _)| i <= end).is_some() {}
+            }
+            _ => {
+                let mut end = query.len();
+                while let Some(&(i, next)) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '(' | ')' | '"') {
+                        end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+
+                let token = match &query[start..end] {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    word => Token::Word(word),
+                };
+                tokens.push((start, token));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Recursive descent parser, each precedence level has its own method.
+// Parts without searchable terms (only stopwords) parse to None and are left out.
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    next: usize,
+    length: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.length, |&(position, _)| position)
+    }
+
+    fn or(&mut self) -> Result<Option<Query>, QueryError> {
+        let mut parts = Vec::new();
+        parts.extend(self.and()?);
+
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            parts.extend(self.and()?);
+        }
+
+        Ok(combine(parts, Query::Or))
+    }
+
+    fn and(&mut self) -> Result<Option<Query>, QueryError> {
+        let mut parts = Vec::new();
+        parts.extend(self.unary()?);
+
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.next += 1,
+                Some(Token::Word(_) | Token::Quoted(_) | Token::Not | Token::Open) => {}
+                _ => break,
+            }
+            parts.extend(self.unary()?);
+        }
+
+        Ok(combine(parts, Query::And))
+    }
+
+    fn unary(&mut self) -> Result<Option<Query>, QueryError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next += 1;
+            return Ok(self.unary()?.map(|query| Query::Not(Box::new(query))));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Option<Query>, QueryError> {
+        let position = self.position();
+        let token = self
+            .tokens
+            .get(self.next)
+            .map(|(_, token)| token.clone())
+            .ok_or_else(|| error(position, "Expected a term"))?;
+        self.next += 1;
+
+        match token {
+            Token::Word(text) | Token::Quoted(text) => Ok(terms(text)),
+            Token::Open => {
+                let query = self.or()?;
+
+                if self.peek() != Some(&Token::Close) {
+                    return Err(error(self.position(), "Expected ')'"));
+                }
+                self.next += 1;
+                Ok(query)
+            }
+            _ => Err(error(position, "Expected a term")),
+        }
+    }
+}
+
+fn combine(mut parts: Vec<Query>, operator: fn(Vec<Query>) -> Query) -> Option<Query> {
+    match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => Some(operator(parts)),
+    }
+}
+
+// A word like "e-mail" analyzes to several terms and is searched as a phrase, just like quoted text
+fn terms(text: &str) -> Option<Query> {
+    let mut tokens = analyze(text);
+    let first = tokens.first()?.0;
+
+    if tokens.len() == 1 {
+        return tokens.pop().map(|(_, term)| Query::Term(term));
+    }
+
+    Some(Query::Phrase(
+        tokens
+            .into_iter()
+            .map(|(position, term)| (position - first, term))
+            .collect(),
+    ))
+}
+
+pub fn parse(query: &str) -> Result<Query, QueryError> {
+    let mut parser = Parser {
+        tokens: tokenize(query)?,
+        next: 0,
+        length: query.len(),
+    };
+
+    let parsed = parser.or()?;
+
+    if parser.next < parser.tokens.len() {
+        return Err(error(parser.position(), "Unexpected ')'"));
+    }
+    parsed.ok_or_else(|| error(0, "Query has no searchable terms"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: &str) -> Query {
+        Query::Term(String::from(term))
+    }
+
+    #[test]
+    fn operators_have_precedence() {
+        assert_eq!(
+            parse("penguins cups OR NOT flyers").unwrap(),
+            Query::Or(vec![
+                Query::And(vec![term("penguin"), term("cup")]),
+                Query::Not(Box::new(term("flyer"))),
+            ])
+        );
+        assert_eq!(
+            parse("(penguins OR flyers) AND hockey").unwrap(),
+            Query::And(vec![
+                Query::Or(vec![term("penguin"), term("flyer")]),
+                term("hockei"),
+            ])
+        );
+    }
+
+    #[test]
+    fn phrases_keep_stopword_gaps() {
+        assert_eq!(
+            parse("\"best of the NHL\"").unwrap(),
+            Query::Phrase(vec![(0, String::from("best")), (3, String::from("nhl"))])
+        );
+        assert_eq!(parse("\"the\" cup").unwrap(), term("cup"));
+        assert_eq!(
+            parse("and").unwrap_err().message,
+            "Query has no searchable terms"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_positions() {
+        assert_eq!(parse("\"open").unwrap_err().position, 0);
+        assert_eq!(parse("(cup").unwrap_err().position, 4);
+        assert_eq!(parse("cup)").unwrap_err().position, 3);
+        assert_eq!(parse("cup OR").unwrap_err().position, 6);
+        assert!(parse("").is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::generics::search::{DocId, Document, Position, SearchIndex};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// The index is saved as UTF-8 text, one record per line:
+//
+//     search-index 1
+//     doc <id> <length>
+//     term <term> <id>:<position>,<position>,... <id>:<position>,...
+//
+// All documents come before the terms. Terms never contain whitespace, since whitespace separates words.
+// Only the postings and lengths are saved, everything else is rebuilt when loading.
+
+const HEADER: &str = "search-index 1";
+
+#[derive(Debug)]
+pub enum IndexError {
+    Io(io::Error),
+    // The file isn't a saved index or has been damaged
+    Malformed { line: usize, message: String },
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Io(error) => write!(f, "Problem accessing the index: {error}"),
+            IndexError::Malformed { line, message } => {
+                write!(f, "Malformed index at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IndexError::Io(error) => Some(error),
+            IndexError::Malformed { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for IndexError {
+    fn from(error: io::Error) -> Self {
+        IndexError::Io(error)
+    }
+}
+
+fn malformed(line: usize, message: &str) -> IndexError {
+    IndexError::Malformed {
+        line,
+        message: String::from(message),
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(
+    text: Option<&str>,
+    line: usize,
+    what: &str,
+) -> Result<T, IndexError> {
+    text.and_then(|text| text.parse().ok())
+        .ok_or_else(|| malformed(line, &format!("Expected {what}")))
+}
+
+impl SearchIndex {
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{HEADER}")?;
+
+        for (id, document) in &self.documents {
+            writeln!(writer, "doc {id} {}", document.length)?;
+        }
+
+        for (term, documents) in &self.postings {
+            write!(writer, "term {term}")?;
+
+            for (id, positions) in documents {
+                let positions: Vec<String> = positions.iter().map(Position::to_string).collect();
+                write!(writer, " {id}:{}", positions.join(","))?;
+            }
+            writeln!(writer)?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn read_from(reader: impl Read) -> Result<SearchIndex, IndexError> {
+        let mut lines = BufReader::new(reader).lines().zip(1..);
+
+        let header = match lines.next() {
+            Some((line, _)) => line?,
+            None => String::new(),
+        };
+        if header != HEADER {
+            return Err(malformed(1, "Not a search index or an unsupported version"));
+        }
+
+        let mut index = SearchIndex::new();
+        // Counted while reading the postings, must match the saved document lengths in the end
+        let mut counted: BTreeMap<DocId, u32> = BTreeMap::new();
+        let mut document_lines: BTreeMap<DocId, usize> = BTreeMap::new();
+
+        for (line, line_number) in lines {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+
+            match fields.next() {
+                Some("doc") => {
+                    if !index.postings.is_empty() {
+                        return Err(malformed(line_number, "Documents must come before terms"));
+                    }
+
+                    let id: DocId = parse_field(fields.next(), line_number, "a document id")?;
+                    let length: u32 = parse_field(fields.next(), line_number, "a document length")?;
+
+                    let document = Document {
+                        length,
+                        terms: Vec::new(),
+                    };
+                    if index.documents.insert(id, document).is_some() {
+                        return Err(malformed(line_number, &format!("Duplicate document {id}")));
+                    }
+                    index.total_length += u64::from(length);
+                    document_lines.insert(id, line_number);
+                }
+                Some("term") => {
+                    let term = fields
+                        .next()
+                        .ok_or_else(|| malformed(line_number, "Expected a term"))?;
+                    let mut documents = BTreeMap::new();
+
+                    for posting in fields {
+                        let (id, positions) = posting
+                            .split_once(':')
+                            .ok_or_else(|| malformed(line_number, "Expected <id>:<positions>"))?;
+                        let id: DocId = parse_field(Some(id), line_number, "a document id")?;
+
+                        let Some(document) = index.documents.get_mut(&id) else {
+                            return Err(malformed(line_number, &format!("Unknown document {id}")));
+                        };
+
+                        let positions = positions
+                            .split(',')
+                            .map(|position| parse_field(Some(position), line_number, "a position"))
+                            .collect::<Result<Vec<Position>, _>>()?;
+
+                        if !positions.windows(2).all(|pair| pair[0] < pair[1]) {
+                            return Err(malformed(line_number, "Positions must be increasing"));
+                        }
+
+                        document.terms.push(String::from(term));
+                        let count = counted.entry(id).or_insert(0);
+                        *count = u32::try_from(positions.len())
+                            .ok()
+                            .and_then(|length| count.checked_add(length))
+                            .ok_or_else(|| {
+                                malformed(line_number, &format!("Document {id} is too long"))
+                            })?;
+
+                        if documents.insert(id, positions).is_some() {
+                            return Err(malformed(
+                                line_number,
+                                &format!("Duplicate document {id} for '{term}'"),
+                            ));
+                        }
+                    }
+
+                    if documents.is_empty() || index.postings.contains_key(term) {
+                        return Err(malformed(
+                            line_number,
+                            &format!("Invalid entry for '{term}'"),
+                        ));
+                    }
+                    index.postings.insert(String::from(term), documents);
+                }
+                None => {}
+                Some(other) => {
+                    return Err(malformed(line_number, &format!("Unknown record '{other}'")));
+                }
+            }
+        }
+
+        for (id, document) in &index.documents {
+            if counted.get(id).copied().unwrap_or(0) != document.length {
+                return Err(malformed(
+                    document_lines[id],
+                    &format!("Length of document {id} doesn't match its terms"),
+                ));
+            }
+        }
+
+        Ok(index)
+    }
+
+    // Writes to a temporary file first, so a crash never leaves a half written index behind
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), IndexError> {
+        let path = path.as_ref();
+        let temporary = path.with_extension("tmp");
+
+        self.write_to(fs::File::create(&temporary)?)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<SearchIndex, IndexError> {
+        SearchIndex::read_from(fs::File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generics::traits::Tweet;
+
+    fn tweet(content: &str) -> Tweet {
+        Tweet {
+            username: String::from("fan"),
+            content: String::from(content),
+            id: 0,
+            in_reply_to: None,
+            retweet_of: None,
+        }
+    }
+
+    fn sample() -> SearchIndex {
+        let mut index = SearchIndex::new();
+        index.add(7, &tweet("Penguins win the cup"));
+        index.add(9, &tweet("Go penguins go"));
+        index
+    }
+
+    #[test]
+    fn saved_indexes_load_unchanged() {
+        let index = sample();
+        let mut saved = Vec::new();
+        index.write_to(&mut saved).unwrap();
+
+        assert_eq!(
+            String::from_utf8(saved.clone()).unwrap(),
+            "search-index 1\n\
+             doc 7 3\n\
+             doc 9 3\n\
+             term cup 7:3\n\
+             term go 9:0,2\n\
+             term penguin 7:0 9:1\n\
+             term win 7:1\n"
+        );
+
+        let mut loaded = SearchIndex::read_from(saved.as_slice()).unwrap();
+        assert_eq!(loaded, index);
+
+        // Loaded indexes keep working incrementally
+        loaded.remove(9);
+        assert_eq!(loaded.search("penguins").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn files_round_trip() {
+        let path = std::env::temp_dir().join(format!("search-index-{}.idx", std::process::id()));
+        let index = sample();
+
+        index.save(&path).unwrap();
+        let loaded = SearchIndex::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, index);
+    }
+
+    #[test]
+    fn damaged_indexes_are_rejected() {
+        let cases = [
+            ("", 1),
+            ("search-index 2\n", 1),
+            ("search-index 1\ndoc x 1\n", 2),
+            ("search-index 1\ndoc 1 1\nterm cup 2:0\n", 3),
+            ("search-index 1\ndoc 1 1\nterm cup 1:3,2\n", 3),
+            ("search-index 1\ndoc 1 1\nterm cup 1:0\ndoc 2 0\n", 4),
+            ("search-index 1\ndoc 1 2\nterm cup 1:0\n", 2),
+        ];
+
+        for (input, expected) in cases {
+            match SearchIndex::read_from(input.as_bytes()) {
+                Err(IndexError::Malformed { line, .. }) => assert_eq!(line, expected, "{input:?}"),
+                other => panic!("{input:?} gave {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use crate::generics::numeric::Numeric;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+// Selection functions over slices of any PartialOrd type.
+// All of them return None (or an empty Vec) instead of panicking on empty input.
+//
+// NaN handling: values that are not comparable with themselves (NaN for f32/f64) are skipped,
+// so a slice of floats behaves as if its NaNs were removed first. A slice that only holds NaNs counts as empty.
+// Ties are resolved in favor of the element that comes first in the slice.
+
+fn comparable<T: PartialOrd>(value: &T) -> bool {
+    value.partial_cmp(value).is_some()
+}
+
+// After skipping NaNs every remaining pair is comparable, so falling back to Equal is never observable
+fn compare<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+pub fn max<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    list.iter()
+        .filter(|item| comparable(*item))
+        .reduce(|largest, item| if item > largest { item } else { largest })
+}
+
+pub fn min<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    list.iter()
+        .filter(|item| comparable(*item))
+        .reduce(|smallest, item| if item < smallest { item } else { smallest })
+}
+
+// Finds both extremes in a single pass, comparing elements in pairs (about 1.5 comparisons per element instead of 2)
+pub fn min_max<T: PartialOrd>(list: &[T]) -> Option<(&T, &T)> {
+    let mut items = list.iter().filter(|item| comparable(*item));
+    let first = items.next()?;
+    let (mut smallest, mut largest) = (first, first);
+
+    while let Some(a) = items.next() {
+        let (low, high) = match items.next() {
+            Some(b) if b < a => (b, a),
+            Some(b) if b > a => (a, b),
+            _ => (a, a),
+        };
+
+        if low < smallest {
+            smallest = low;
+        }
+        if high > largest {
+            largest = high;
+        }
+    }
+
+    Some((smallest, largest))
+}
+
+// Index of the largest element, the first one if there are several
+pub fn argmax<T: PartialOrd>(list: &[T]) -> Option<usize> {
+    list.iter()
+        .enumerate()
+        .filter(|(_, item)| comparable(*item))
+        .reduce(|largest, item| if item.1 > largest.1 { item } else { largest })
+        .map(|(index, _)| index)
+}
+
+// Largest element according to a key extracted by the closure, elements with a NaN key are skipped
+pub fn largest_by_key<T, K, F>(list: &[T], mut key: F) -> Option<&T>
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    let mut largest: Option<(&T, K)> = None;
+
+    for item in list {
+        let item_key = key(item);
+
+        if !comparable(&item_key) {
+            continue;
+        }
+
+        match &largest {
+            Some((_, largest_key)) if item_key <= *largest_key => {}
+            _ => largest = Some((item, item_key)),
+        }
+    }
+
+    largest.map(|(item, _)| item)
+}
+
+// Wrapper that gives already filtered PartialOrd values the total order a BinaryHeap needs
+struct Ranked<'a, T> {
+    value: &'a T,
+    index: usize,
+}
+
+impl<T: PartialOrd> Ord for Ranked<'_, T> {
+    // Among equal values the earlier one ranks higher, so it survives eviction from the heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self.value, other.value).then(other.index.cmp(&self.index))
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Ranked<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> PartialEq for Ranked<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for Ranked<'_, T> {}
+
+// The k largest elements in descending order.
+// Keeps a min-heap of at most k elements, so it runs in O(n log k) time and O(k) extra memory.
+pub fn top_k<T: PartialOrd>(list: &[T], k: usize) -> Vec<&T> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+
+    for (index, value) in list.iter().enumerate().filter(|(_, v)| comparable(*v)) {
+        heap.push(Reverse(Ranked { value, index }));
+
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| ranked.value)
+        .collect()
+}
+
+// The k-th smallest element (k = 0 is the minimum) using quickselect, in expected O(n) time.
+// The slice itself is left untouched, only a Vec of references is partitioned.
+pub fn kth_smallest<T: PartialOrd>(list: &[T], k: usize) -> Option<&T> {
+    let mut items: Vec<&T> = list.iter().filter(|item| comparable(*item)).collect();
+
+    if k >= items.len() {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, items.len() - 1);
+
+    while low < high {
+        let equal = partition(&mut items[low..=high]);
+        let (start, end) = (low + equal.start, low + equal.end);
+
+        if k < start {
+            high = start - 1;
+        } else if k >= end {
+            low = end;
+        } else {
+            break;
+        }
+    }
+
+    Some(items[k])
+}
+
+// Three-way (Dutch national flag) partition around a median-of-three pivot. Returns the range of the
+// items equal to the pivot, smaller ones end up before it and larger ones after it. Keeping equal items
+// together keeps quickselect linear when there are many of them, e.g. when taking the median of ratings.
+fn partition<T: PartialOrd>(items: &mut [&T]) -> Range<usize> {
+    let mut candidates = [items[0], items[items.len() / 2], items[items.len() - 1]];
+    candidates.sort_by(|a, b| compare(*a, *b));
+    let pivot = candidates[1];
+
+    // items[..lower] are smaller, items[lower..i] equal and items[upper..] larger than the pivot
+    let (mut lower, mut i, mut upper) = (0, 0, items.len());
+    while i < upper {
+        match compare(items[i], pivot) {
+            Ordering::Less => {
+                items.swap(lower, i);
+                lower += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                upper -= 1;
+                items.swap(i, upper);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+
+    lower..upper
+}
+
+// For an even number of elements this is the lower of the two middle elements, because
+// PartialOrd alone can't average values. Use `median_numeric` to average them instead.
+pub fn median<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    let count = list.iter().filter(|item| comparable(*item)).count();
+
+    kth_smallest(list, count.checked_sub(1)? / 2)
+}
+
+pub fn median_numeric<T: Numeric>(list: &[T]) -> Option<f64> {
+    let count = list.iter().filter(|item| comparable(*item)).count();
+    let lower = kth_smallest(list, count.checked_sub(1)? / 2)?.to_f64();
+
+    if count % 2 == 1 {
+        Some(lower)
+    } else {
+        let upper = kth_smallest(list, count / 2)?.to_f64();
+        Some((lower + upper) / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_slices_return_none() {
+        let empty: [i32; 0] = [];
+
+        assert_eq!(max(&empty), None);
+        assert_eq!(min(&empty), None);
+        assert_eq!(min_max(&empty), None);
+        assert_eq!(median(&empty), None);
+        assert_eq!(median_numeric(&empty), None);
+        assert_eq!(kth_smallest(&empty, 0), None);
+        assert!(top_k(&empty, 3).is_empty());
+    }
+
+    #[test]
+    fn min_and_max() {
+        let numbers = [34, 50, 25, 100, 65];
+        let chars = ['y', 'm', 'a', 'q'];
+
+        assert_eq!(max(&numbers), Some(&100));
+        assert_eq!(min(&numbers), Some(&25));
+        assert_eq!(min_max(&numbers), Some((&25, &100)));
+        assert_eq!(min_max(&chars), Some((&'a', &'y')));
+        assert_eq!(min_max(&[7]), Some((&7, &7)));
+        assert_eq!(argmax(&[3, 9, 1, 9]), Some(1));
+    }
+
+    #[test]
+    fn ties_resolve_to_the_first_element() {
+        let words = ["bb", "a", "cc", "dd"];
+
+        assert!(std::ptr::eq(
+            largest_by_key(&words, |w| w.len()).unwrap(),
+            &words[0]
+        ));
+        assert_eq!(largest_by_key(&words, |w| w.chars().next()), Some(&"dd"));
+    }
+
+    #[test]
+    fn top_k_returns_descending_order() {
+        let numbers = [5, 1, 9, 3, 9, 7];
+
+        assert_eq!(top_k(&numbers, 3), vec![&9, &9, &7]);
+        assert_eq!(top_k(&numbers, 10).len(), 6);
+        assert!(top_k(&numbers, 0).is_empty());
+    }
+
+    #[test]
+    fn kth_smallest_and_median() {
+        let numbers = [9, 2, 7, 4, 5, 1, 8, 3, 6];
+
+        for k in 0..numbers.len() {
+            assert_eq!(kth_smallest(&numbers, k), Some(&(k as i32 + 1)));
+        }
+        assert_eq!(kth_smallest(&numbers, 9), None);
+        assert_eq!(median(&numbers), Some(&5));
+        assert_eq!(median(&[4, 1, 3, 2]), Some(&2));
+        assert_eq!(median_numeric(&[4, 1, 3, 2]), Some(2.5));
+        assert_eq!(median(&[3, 3, 3, 1, 3]), Some(&3));
+    }
+
+    #[test]
+    fn many_equal_keys() {
+        // Quadratic with a two-way partition
+        let ratings: Vec<u8> = (0..200_000).map(|i| (i % 3 == 0) as u8 * 4 + 1).collect();
+        assert_eq!(median(&ratings), Some(&1));
+        assert_eq!(kth_smallest(&ratings, 199_999), Some(&5));
+        assert_eq!(median(&vec![7; 100_000]), Some(&7));
+
+        let mut sorted: Vec<u32> = (0..1000).map(|i| i * 7919 % 5).collect();
+        let unsorted = sorted.clone();
+        sorted.sort_unstable();
+        for k in [0, 1, 199, 200, 500, 999] {
+            assert_eq!(kth_smallest(&unsorted, k), Some(&sorted[k]), "{k}");
+        }
+    }
+
+    #[test]
+    fn nan_values_are_skipped() {
+        let floats = [f64::NAN, 2.5, -1.0, f64::NAN, 7.0, 0.5];
+
+        assert_eq!(max(&floats), Some(&7.0));
+        assert_eq!(argmax(&floats), Some(4));
+        assert_eq!(min(&floats), Some(&-1.0));
+        assert_eq!(min_max(&floats), Some((&-1.0, &7.0)));
+        assert_eq!(top_k(&floats, 2), vec![&7.0, &2.5]);
+        assert_eq!(kth_smallest(&floats, 3), Some(&7.0));
+        assert_eq!(kth_smallest(&floats, 4), None);
+        assert_eq!(median_numeric(&floats), Some(1.5));
+        assert_eq!(largest_by_key(&floats, |f| -f), Some(&-1.0));
+    }
+
+    #[test]
+    fn only_nan_counts_as_empty() {
+        let floats = [f64::NAN, f64::NAN];
+
+        assert_eq!(max(&floats), None);
+        assert_eq!(min_max(&floats), None);
+        assert_eq!(median(&floats), None);
+    }
+
+    #[test]
+    fn infinities_are_ordinary_values() {
+        let floats = [1.0, f64::INFINITY, f64::NEG_INFINITY];
+
+        assert_eq!(min_max(&floats), Some((&f64::NEG_INFINITY, &f64::INFINITY)));
+    }
+}
@@ -0,0 +1,412 @@
+use crate::generics::numeric::Numeric;
+use crate::generics::point::Point;
+use std::f64::consts::PI;
+
+// Common behavior of all 2D shapes, so area() and perimeter() work uniformly through generics or trait objects
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn perimeter(&self) -> f64;
+}
+
+// An open chain of line segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point<f64, f64>>,
+}
+
+// A closed polygon, the last vertex is implicitly connected to the first one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub vertices: Vec<Point<f64, f64>>,
+}
+
+impl Polyline {
+    pub fn new<T: Numeric, U: Numeric>(points: impl IntoIterator<Item = Point<T, U>>) -> Self {
+        Self {
+            points: to_f64_points(points),
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|segment| segment[0].euclidean_distance(&segment[1]))
+            .sum()
+    }
+
+    // Ramer–Douglas–Peucker: drops points that are closer than `epsilon` to the simplified line, keeping both end points
+    pub fn simplify(&self, epsilon: f64) -> Polyline {
+        Polyline {
+            points: douglas_peucker(&self.points, epsilon),
+        }
+    }
+}
+
+impl Polygon {
+    pub fn new<T: Numeric, U: Numeric>(vertices: impl IntoIterator<Item = Point<T, U>>) -> Self {
+        Self {
+            vertices: to_f64_points(vertices),
+        }
+    }
+
+    // Iterates over the edges including the closing edge from the last vertex back to the first one
+    fn edges(&self) -> impl Iterator<Item = (&Point<f64, f64>, &Point<f64, f64>)> {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().zip(next)
+    }
+
+    // Shoelace formula, positive for counter-clockwise and negative for clockwise vertex order
+    pub fn signed_area(&self) -> f64 {
+        self.edges().map(|(a, b)| cross(a, b)).sum::<f64>() / 2.0
+    }
+
+    // None for degenerate polygons without area (fewer than 3 vertices or all vertices on a line)
+    pub fn centroid(&self) -> Option<Point<f64, f64>> {
+        let area = self.signed_area();
+
+        if area.abs() <= f64::EPSILON {
+            return None;
+        }
+
+        let (x, y) = self.edges().fold((0.0, 0.0), |(x, y), (a, b)| {
+            let factor = cross(a, b);
+            (x + (a.x + b.x) * factor, y + (a.y + b.y) * factor)
+        });
+
+        Some(Point::new(x / (6.0 * area), y / (6.0 * area)))
+    }
+
+    // Convex if all turns along the boundary go in the same direction and add up to one full turn,
+    // collinear vertices are allowed. A star like a pentagram turns one way only, but twice around.
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+
+        if n < 3 {
+            return false;
+        }
+
+        let mut direction = 0.0;
+        let mut turning = 0.0;
+
+        for i in 0..n {
+            let (a, b, c) = (
+                &self.vertices[i],
+                &self.vertices[(i + 1) % n],
+                &self.vertices[(i + 2) % n],
+            );
+            let turn = orientation(a, b, c);
+
+            if turn != 0.0 {
+                if direction * turn < 0.0 {
+                    return false;
+                }
+                direction = turn;
+            }
+
+            // Angle between the edges a -> b and b -> c
+            let dot = (b.x - a.x) * (c.x - b.x) + (b.y - a.y) * (c.y - b.y);
+            turning += turn.atan2(dot);
+        }
+
+        direction != 0.0 && (turning.abs() - 2.0 * PI).abs() < 1e-6
+    }
+
+    // Points on the boundary count as inside, everything else uses the even-odd ray casting rule
+    pub fn contains(&self, point: &Point<f64, f64>) -> bool {
+        let mut inside = false;
+
+        for (a, b) in self.edges() {
+            if on_segment(point, a, b) {
+                return true;
+            }
+
+            if (a.y > point.y) != (b.y > point.y) {
+                let crossing_x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+
+                if point.x < crossing_x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        inside
+    }
+
+    // Simplifies the closed ring by splitting it at the vertex farthest from the first one
+    pub fn simplify(&self, epsilon: f64) -> Polygon {
+        if self.vertices.len() <= 3 {
+            return self.clone();
+        }
+
+        let first = &self.vertices[0];
+        let (farthest, _) = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, first.euclidean_distance(p)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+
+        let mut closed = self.vertices.clone();
+        closed.push(first.clone());
+
+        let mut vertices = douglas_peucker(&closed[..=farthest], epsilon);
+        vertices.pop();
+        vertices.extend(douglas_peucker(&closed[farthest..], epsilon));
+        vertices.pop();
+
+        Polygon { vertices }
+    }
+
+    // Andrew's monotone chain algorithm, returns the hull in counter-clockwise order without collinear points
+    pub fn convex_hull(points: &[Point<f64, f64>]) -> Polygon {
+        let mut sorted = points.to_vec();
+        sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        sorted.dedup();
+
+        if sorted.len() < 3 {
+            return Polygon { vertices: sorted };
+        }
+
+        let mut lower: Vec<Point<f64, f64>> = Vec::new();
+        for p in &sorted {
+            push_hull_point(&mut lower, p);
+        }
+
+        let mut upper: Vec<Point<f64, f64>> = Vec::new();
+        for p in sorted.iter().rev() {
+            push_hull_point(&mut upper, p);
+        }
+
+        // The last point of each chain is the first point of the other one
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+
+        Polygon { vertices: lower }
+    }
+}
+
+impl Shape for Polygon {
+    fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    fn perimeter(&self) -> f64 {
+        if self.vertices.len() < 2 {
+            return 0.0;
+        }
+
+        self.edges().map(|(a, b)| a.euclidean_distance(b)).sum()
+    }
+}
+
+fn to_f64_points<T: Numeric, U: Numeric>(
+    points: impl IntoIterator<Item = Point<T, U>>,
+) -> Vec<Point<f64, f64>> {
+    points
+        .into_iter()
+        .map(|p| Point::new(p.x.to_f64(), p.y.to_f64()))
+        .collect()
+}
+
+// z component of the cross product of the vectors from the origin to a and b
+fn cross(a: &Point<f64, f64>, b: &Point<f64, f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+// Positive for a counter-clockwise turn a -> b -> c, negative for clockwise and zero for collinear points
+fn orientation(a: &Point<f64, f64>, b: &Point<f64, f64>, c: &Point<f64, f64>) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(p: &Point<f64, f64>, a: &Point<f64, f64>, b: &Point<f64, f64>) -> bool {
+    orientation(a, b, p).abs() <= f64::EPSILON
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+fn push_hull_point(chain: &mut Vec<Point<f64, f64>>, p: &Point<f64, f64>) {
+    while chain.len() >= 2
+        && orientation(&chain[chain.len() - 2], &chain[chain.len() - 1], p) <= 0.0
+    {
+        chain.pop();
+    }
+    chain.push(p.clone());
+}
+
+fn distance_to_line(p: &Point<f64, f64>, a: &Point<f64, f64>, b: &Point<f64, f64>) -> f64 {
+    let length = a.euclidean_distance(b);
+
+    if length == 0.0 {
+        a.euclidean_distance(p)
+    } else {
+        orientation(a, b, p).abs() / length
+    }
+}
+
+fn douglas_peucker(points: &[Point<f64, f64>], epsilon: f64) -> Vec<Point<f64, f64>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let (first, last) = (&points[0], &points[points.len() - 1]);
+    let (index, distance) = points
+        .iter()
+        .enumerate()
+        .skip(1)
+        .take(points.len() - 2)
+        .map(|(i, p)| (i, distance_to_line(p, first, last)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0));
+
+    if distance > epsilon {
+        let mut left = douglas_peucker(&points[..=index], epsilon);
+        left.pop();
+        left.extend(douglas_peucker(&points[index..], epsilon));
+        left
+    } else {
+        vec![first.clone(), last.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::rectangle::Rectangle;
+
+    fn square() -> Polygon {
+        Polygon::new([
+            Point::new(0, 0),
+            Point::new(4, 0),
+            Point::new(4, 4),
+            Point::new(0, 4),
+        ])
+    }
+
+    #[test]
+    fn area_perimeter_and_centroid() {
+        let triangle = Polygon::new([Point::new(0, 0), Point::new(4, 0), Point::new(0, 3)]);
+
+        assert_eq!(square().area(), 16.0);
+        assert_eq!(square().perimeter(), 16.0);
+        assert_eq!(square().centroid(), Some(Point::new(2.0, 2.0)));
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(triangle.perimeter(), 12.0);
+        assert_eq!(
+            Polygon::new([Point::new(0, 0), Point::new(1, 1)]).centroid(),
+            None
+        );
+    }
+
+    #[test]
+    fn clockwise_polygons_have_negative_signed_area() {
+        let mut clockwise = square();
+        clockwise.vertices.reverse();
+
+        assert_eq!(clockwise.signed_area(), -16.0);
+        assert_eq!(clockwise.area(), 16.0);
+    }
+
+    #[test]
+    fn convexity() {
+        let arrow = Polygon::new([
+            Point::new(0, 0),
+            Point::new(2, 1),
+            Point::new(4, 0),
+            Point::new(2, 4),
+        ]);
+
+        assert!(square().is_convex());
+        assert!(!arrow.is_convex());
+
+        // Every turn goes the same way, but the boundary crosses itself
+        let pentagram = Polygon::new((0..5).map(|i| {
+            let angle = f64::from(i * 2) * 2.0 * PI / 5.0;
+            Point::new(angle.cos(), angle.sin())
+        }));
+        assert!(!pentagram.is_convex());
+
+        let pentagon = Polygon::new((0..5).map(|i| {
+            let angle = f64::from(i) * 2.0 * PI / 5.0;
+            Point::new(angle.cos(), angle.sin())
+        }));
+        assert!(pentagon.is_convex());
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let polygon = square();
+
+        assert!(polygon.contains(&Point::new(1.0, 1.0)));
+        assert!(polygon.contains(&Point::new(4.0, 2.0)));
+        assert!(!polygon.contains(&Point::new(5.0, 2.0)));
+        assert!(!polygon.contains(&Point::new(-0.1, 0.0)));
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_and_collinear_points() {
+        let mut points = square().vertices;
+        points.push(Point::new(2.0, 2.0));
+        points.push(Point::new(2.0, 0.0));
+        points.push(Point::new(0.0, 0.0));
+
+        let hull = Polygon::convex_hull(&points);
+
+        assert_eq!(
+            hull,
+            Polygon::new([
+                Point::new(0, 0),
+                Point::new(4, 0),
+                Point::new(4, 4),
+                Point::new(0, 4),
+            ])
+        );
+    }
+
+    #[test]
+    fn simplification_keeps_significant_points() {
+        let line = Polyline::new([
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.1),
+            Point::new(2.0, -0.1),
+            Point::new(3.0, 5.0),
+            Point::new(4.0, 6.0),
+            Point::new(5.0, 7.0),
+        ]);
+
+        let simplified = line.simplify(0.5);
+
+        assert_eq!(
+            simplified.points,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(2.0, -0.1),
+                Point::new(3.0, 5.0),
+                Point::new(5.0, 7.0),
+            ]
+        );
+        assert!(simplified.length() <= line.length());
+
+        let mut noisy_square = square();
+        noisy_square.vertices.insert(1, Point::new(2.0, 0.01));
+        assert_eq!(noisy_square.simplify(0.1), square());
+    }
+
+    #[test]
+    fn shapes_are_usable_as_trait_objects() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(square()),
+            Box::new(Rectangle {
+                width: 2,
+                height: 3,
+            }),
+        ];
+
+        let total: f64 = shapes.iter().map(|shape| shape.area()).sum();
+        assert_eq!(total, 22.0);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::generics::text;
+use std::collections::{HashMap, HashSet};
+
+// Extractive summarization: the text is split into sentences, every sentence gets a score and the best
+// sentences are returned in their original order. Nothing is rephrased, so the summary only contains
+// sentences of the source text.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scoring {
+    // Sentences made of words that are frequent in the whole text but not spread over every sentence score high
+    TfIdf,
+    // Sentences that share many words with many other sentences score high (PageRank over a similarity graph)
+    TextRank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Sentences(usize),
+    // As many of the best sentences as fit, but always at least one
+    Characters(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summarizer {
+    pub scoring: Scoring,
+    pub length: Length,
+}
+
+impl Default for Summarizer {
+    fn default() -> Self {
+        Self {
+            scoring: Scoring::TfIdf,
+            length: Length::Sentences(2),
+        }
+    }
+}
+
+impl Summarizer {
+    pub fn summarize(&self, content: &str) -> String {
+        self.select(content).join(" ")
+    }
+
+    // The selected sentences as slices of `content`, in the order in which they appear there
+    pub fn select<'a>(&self, content: &'a str) -> Vec<&'a str> {
+        let sentences = text::sentences(content);
+        let words: Vec<Vec<String>> = sentences
+            .iter()
+            .map(|sentence| text::content_words(sentence).collect())
+            .collect();
+
+        let scores = match self.scoring {
+            Scoring::TfIdf => tf_idf_scores(&words),
+            Scoring::TextRank => text_rank_scores(&words),
+        };
+
+        // Best score first, earlier sentences win ties
+        let mut ranking: Vec<usize> = (0..sentences.len()).collect();
+        ranking.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+
+        let mut chosen = match self.length {
+            Length::Sentences(count) => ranking.into_iter().take(count).collect(),
+            Length::Characters(limit) => within_characters(&sentences, &ranking, limit),
+        };
+        chosen.sort_unstable();
+
+        chosen.into_iter().map(|i| sentences[i]).collect()
+    }
+}
+
+fn within_characters(sentences: &[&str], ranking: &[usize], limit: usize) -> Vec<usize> {
+    let mut chosen = Vec::new();
+    let mut used = 0;
+
+    for &i in ranking {
+        // Selected sentences are joined with a space
+        let length = sentences[i].chars().count() + usize::from(!chosen.is_empty());
+
+        if chosen.is_empty() || used + length <= limit {
+            chosen.push(i);
+            used += length;
+        }
+    }
+
+    chosen
+}
+
+// Every word is weighted by its frequency in the whole text times its inverse sentence frequency,
+// a sentence scores the average weight of its words, so long sentences aren't favored
+fn tf_idf_scores(sentences: &[Vec<String>]) -> Vec<f64> {
+    let mut term_frequency: HashMap<&str, usize> = HashMap::new();
+    let mut sentence_frequency: HashMap<&str, usize> = HashMap::new();
+
+    for words in sentences {
+        for word in words {
+            *term_frequency.entry(word).or_insert(0) += 1;
+        }
+
+        let unique: HashSet<&str> = words.iter().map(String::as_str).collect();
+        for word in unique {
+            *sentence_frequency.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    let count = sentences.len() as f64;
+
+    // Smoothed idf, so words that appear in every sentence still count a little
+    let weight = |word: &str| {
+        let idf = 1.0 + (count / sentence_frequency[word] as f64).ln();
+        term_frequency[word] as f64 * idf
+    };
+
+    sentences
+        .iter()
+        .map(|words| {
+            if words.is_empty() {
+                0.0
+            } else {
+                words.iter().map(|word| weight(word)).sum::<f64>() / words.len() as f64
+            }
+        })
+        .collect()
+}
+
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-6;
+
+fn text_rank_scores(sentences: &[Vec<String>]) -> Vec<f64> {
+    let n = sentences.len();
+    let sets: Vec<HashSet<&str>> = sentences
+        .iter()
+        .map(|words| words.iter().map(String::as_str).collect())
+        .collect();
+
+    // Similarity from the TextRank paper: shared words normalized by the log of the sentence lengths
+    let mut weights = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let shared = sets[i].intersection(&sets[j]).count() as f64;
+            let norm = (sets[i].len() as f64).ln() + (sets[j].len() as f64).ln();
+
+            if shared > 0.0 && norm > 0.0 {
+                weights[i][j] = shared / norm;
+                weights[j][i] = shared / norm;
+            }
+        }
+    }
+
+    let out_weight: Vec<f64> = weights.iter().map(|row| row.iter().sum()).collect();
+    let mut scores = vec![1.0; n];
+
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<f64> = (0..n)
+            .map(|i| {
+                let incoming: f64 = (0..n)
+                    .filter(|&j| out_weight[j] > 0.0)
+                    .map(|j| weights[j][i] / out_weight[j] * scores[j])
+                    .sum();
+
+                (1.0 - DAMPING) + DAMPING * incoming
+            })
+            .collect();
+
+        let change: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+        scores = next;
+
+        if change < TOLERANCE {
+            break;
+        }
+    }
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "The Pittsburgh Penguins won the Stanley Cup. \
+        Fans celebrated the Penguins all night in Pittsburgh. \
+        The weather was mild. \
+        Penguins captain Crosby lifted the Stanley Cup for Pittsburgh.";
+
+    #[test]
+    fn picks_sentences_in_original_order() {
+        for scoring in [Scoring::TfIdf, Scoring::TextRank] {
+            let summarizer = Summarizer {
+                scoring,
+                length: Length::Sentences(2),
+            };
+
+            let selected = summarizer.select(TEXT);
+
+            assert_eq!(selected.len(), 2);
+            assert!(
+                !selected.contains(&"The weather was mild."),
+                "{scoring:?}: {selected:?}"
+            );
+
+            let positions: Vec<usize> = selected.iter().map(|s| TEXT.find(s).unwrap()).collect();
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn text_rank_prefers_central_sentences() {
+        let summarizer = Summarizer {
+            scoring: Scoring::TextRank,
+            length: Length::Sentences(1),
+        };
+
+        assert_eq!(
+            summarizer.summarize(TEXT),
+            "The Pittsburgh Penguins won the Stanley Cup."
+        );
+    }
+
+    #[test]
+    fn character_budget_limits_the_summary() {
+        let summarizer = Summarizer {
+            scoring: Scoring::TfIdf,
+            length: Length::Characters(60),
+        };
+
+        let summary = summarizer.summarize(TEXT);
+
+        assert!(summary.chars().count() <= 60, "{summary}");
+        assert!(!summary.is_empty());
+
+        let tiny = Summarizer {
+            length: Length::Characters(1),
+            ..summarizer
+        };
+        assert_eq!(tiny.select(TEXT).len(), 1);
+    }
+
+    #[test]
+    fn short_and_empty_texts() {
+        let summarizer = Summarizer::default();
+
+        assert_eq!(summarizer.summarize(""), "");
+        assert_eq!(
+            summarizer.summarize("Just one sentence."),
+            "Just one sentence."
+        );
+        assert_eq!(summarizer.summarize("Alpha. Beta. Gamma."), "Alpha. Beta.");
+    }
+
+    #[test]
+    fn empty_content_gets_the_teaser_only() {
+        use crate::generics::traits::{NewsArticle, Summary};
+
+        for content in ["", "  \n "] {
+            let article = NewsArticle {
+                headline: String::from("Headline"),
+                location: String::from("Pittsburgh, PA, USA"),
+                author: String::from("Iceburgh"),
+                content: String::from(content),
+            };
+            assert_eq!(article.summarize(), "(Read more from Iceburgh...)");
+        }
+    }
+}
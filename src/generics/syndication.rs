@@ -0,0 +1,554 @@
+mod xml;
+
+use crate::generics::feed::Timestamp;
+use crate::generics::syndication::xml::{Element, XmlError};
+use crate::generics::traits::NewsArticle;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+// Imports RSS 2.0 and Atom feeds into NewsArticles and exports NewsArticles as an Atom feed.
+//
+// Field mapping:
+//     headline <- RSS <title> / Atom <title>
+//     author   <- RSS <author> or <dc:creator> / Atom <author><name>, falling back to the channel or feed author
+//     content  <- RSS <content:encoded> or <description> / Atom <content> or <summary>, with HTML markup removed
+//     location <- <georss:featureName> if present, feeds have no standard location field
+//
+// The dc:, content: and georss: prefixes above stand for their namespaces, feeds can bind those to any prefix.
+// Plain text content keeps its line breaks, so it survives an export and import. Other fields are one line.
+
+const GEORSS_NAMESPACE: &str = "http://www.georss.org/georss";
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+const CONTENT_NAMESPACE: &str = "http://purl.org/rss/1.0/modules/content/";
+
+// The prefixes used for the extension namespaces in this file
+const PREFIXES: [(&str, &str); 3] = [
+    ("dc", DC_NAMESPACE),
+    ("content", CONTENT_NAMESPACE),
+    ("georss", GEORSS_NAMESPACE),
+];
+
+#[derive(Debug)]
+pub enum FeedError {
+    Io(io::Error),
+    // The document isn't well-formed XML or isn't a valid RSS or Atom feed
+    Malformed { line: usize, message: String },
+}
+
+impl Display for FeedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::Io(error) => write!(f, "Problem reading the feed: {error}"),
+            FeedError::Malformed { line, message } => {
+                write!(f, "Malformed feed at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for FeedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FeedError::Io(error) => Some(error),
+            FeedError::Malformed { .. } => None,
+        }
+    }
+}
+
+// Implementing From lets the ? operator convert the errors automatically
+impl From<io::Error> for FeedError {
+    fn from(error: io::Error) -> Self {
+        FeedError::Io(error)
+    }
+}
+
+impl From<XmlError> for FeedError {
+    fn from(error: XmlError) -> Self {
+        FeedError::Malformed {
+            line: error.line,
+            message: error.message,
+        }
+    }
+}
+
+fn malformed(element: &Element, message: &str) -> FeedError {
+    FeedError::Malformed {
+        line: element.line,
+        message: String::from(message),
+    }
+}
+
+fn read_root(mut reader: impl Read) -> Result<Element, FeedError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    Ok(xml::parse(&input)?)
+}
+
+// Detects the feed format from the root element
+pub fn read_feed(reader: impl Read) -> Result<Vec<NewsArticle>, FeedError> {
+    let root = read_root(reader)?;
+
+    match root.name.as_str() {
+        "rss" => rss_articles(&root),
+        "feed" => atom_articles(&root),
+        other => Err(malformed(
+            &root,
+            &format!("Expected an <rss> or <feed> root element, found <{other}>"),
+        )),
+    }
+}
+
+pub fn read_feed_file(path: impl AsRef<Path>) -> Result<Vec<NewsArticle>, FeedError> {
+    read_feed(fs::File::open(path)?)
+}
+
+pub fn read_rss(reader: impl Read) -> Result<Vec<NewsArticle>, FeedError> {
+    let root = read_root(reader)?;
+
+    if root.name != "rss" {
+        return Err(malformed(&root, "Expected an <rss> root element"));
+    }
+    rss_articles(&root)
+}
+
+pub fn read_atom(reader: impl Read) -> Result<Vec<NewsArticle>, FeedError> {
+    let root = read_root(reader)?;
+
+    if root.name != "feed" {
+        return Err(malformed(&root, "Expected a <feed> root element"));
+    }
+    atom_articles(&root)
+}
+
+fn rss_articles(root: &Element) -> Result<Vec<NewsArticle>, FeedError> {
+    let channel = root
+        .child("channel")
+        .ok_or_else(|| malformed(root, "RSS feed has no <channel>"))?;
+
+    let channel_author = text_of(channel, &["managingEditor", "dc:creator"]);
+
+    channel
+        .children_named("item")
+        .map(|item| {
+            let headline = text_of(item, &["title"]);
+            let content = html_to_text(&text_of(item, &["content:encoded", "description"]));
+
+            if headline.is_empty() && content.is_empty() {
+                return Err(malformed(item, "RSS item needs a <title> or <description>"));
+            }
+
+            Ok(NewsArticle {
+                headline,
+                location: text_of(item, &["georss:featureName"]),
+                author: non_empty_or(text_of(item, &["author", "dc:creator"]), &channel_author),
+                content,
+            })
+        })
+        .collect()
+}
+
+fn atom_articles(root: &Element) -> Result<Vec<NewsArticle>, FeedError> {
+    let feed_author = root
+        .child("author")
+        .map(|author| text_of(author, &["name"]))
+        .unwrap_or_default();
+
+    root.children_named("entry")
+        .map(|entry| {
+            let title = entry
+                .child("title")
+                .ok_or_else(|| malformed(entry, "Atom entry has no <title>"))?;
+
+            let author = entry
+                .child("author")
+                .map(|author| text_of(author, &["name"]))
+                .unwrap_or_default();
+
+            let content = entry
+                .child("content")
+                .or_else(|| entry.child("summary"))
+                .map(|content| atom_text(content, true))
+                .unwrap_or_default();
+
+            Ok(NewsArticle {
+                headline: atom_text(title, false),
+                location: text_of(entry, &["georss:featureName"]),
+                author: non_empty_or(author, &feed_author),
+                content,
+            })
+        })
+        .collect()
+}
+
+// Atom text constructs are plain text by default, or (escaped) HTML or inline XHTML markup.
+// Plain text keeps its line breaks when it's `multi_line`.
+fn atom_text(element: &Element, multi_line: bool) -> String {
+    match element.attribute("type") {
+        Some("html") | Some("xhtml") => html_to_text(&element.text()),
+        _ if multi_line => trim_lines(&element.text()),
+        _ => collapse_whitespace(&element.text()),
+    }
+}
+
+// A name like "dc:creator" is looked up by the namespace of its prefix, other names as they are
+fn child<'a>(element: &'a Element, name: &str) -> Option<&'a Element> {
+    let in_namespace = name.split_once(':').and_then(|(prefix, local_name)| {
+        let (_, namespace) = PREFIXES.iter().find(|(known, _)| *known == prefix)?;
+        Some((*namespace, local_name))
+    });
+
+    match in_namespace {
+        Some((namespace, local_name)) => element.child_in(namespace, local_name),
+        None => element.child(name),
+    }
+}
+
+// Trimmed text of the first child that exists out of `names`, or an empty string
+fn text_of(element: &Element, names: &[&str]) -> String {
+    names
+        .iter()
+        .filter_map(|name| child(element, name))
+        .map(|child| collapse_whitespace(&child.text()))
+        .find(|text| !text.is_empty())
+        .unwrap_or_default()
+}
+
+fn non_empty_or(text: String, fallback: &str) -> String {
+    if text.is_empty() {
+        String::from(fallback)
+    } else {
+        text
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Trims every line and drops the empty lines at the start and end, e.g. the indentation of pretty printed XML
+fn trim_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    lines.join("\n").trim().to_string()
+}
+
+// Feed content is usually HTML that was escaped once for XML, so after XML parsing the markup still has to go.
+// Block level tags become spaces, inline tags are dropped. Entities are decoded leniently, HTML in the wild is rarely strict.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // A '<' that doesn't start a tag is just text
+        let starts_tag =
+            rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+
+        match rest.find('>') {
+            Some(end) if starts_tag => {
+                let name = rest[1..end]
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or("")
+                    .to_ascii_lowercase();
+
+                if BLOCK_TAGS.contains(&name.as_str()) {
+                    text.push(' ');
+                }
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                text.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    text.push_str(rest);
+    collapse_whitespace(&xml::decode_entities_lenient(&text))
+}
+
+const BLOCK_TAGS: [&str; 16] = [
+    "p",
+    "br",
+    "div",
+    "li",
+    "ul",
+    "ol",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "tr",
+    "td",
+    "blockquote",
+    "hr",
+];
+
+// Writes the articles as an Atom 1.0 feed.
+// Atom requires an id and an update time for the feed and every entry, the entry ids are derived from the feed id.
+pub fn write_atom(
+    mut writer: impl Write,
+    title: &str,
+    id: &str,
+    updated: Timestamp,
+    articles: &[NewsArticle],
+) -> io::Result<()> {
+    let updated = rfc3339(updated);
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<feed xmlns="{ATOM_NAMESPACE}" xmlns:georss="{GEORSS_NAMESPACE}">"#
+    )?;
+    writeln!(writer, "  <title>{}</title>", xml::escape(title))?;
+    writeln!(writer, "  <id>{}</id>", xml::escape(id))?;
+    writeln!(writer, "  <updated>{updated}</updated>")?;
+
+    for (i, article) in articles.iter().enumerate() {
+        writeln!(writer, "  <entry>")?;
+        writeln!(
+            writer,
+            "    <title>{}</title>",
+            xml::escape(&article.headline)
+        )?;
+        writeln!(writer, "    <id>{}/{}</id>", xml::escape(id), i + 1)?;
+        writeln!(writer, "    <updated>{updated}</updated>")?;
+        writeln!(
+            writer,
+            "    <author><name>{}</name></author>",
+            xml::escape(&article.author)
+        )?;
+        if !article.location.is_empty() {
+            writeln!(
+                writer,
+                "    <georss:featureName>{}</georss:featureName>",
+                xml::escape(&article.location)
+            )?;
+        }
+        writeln!(
+            writer,
+            r#"    <content type="text">{}</content>"#,
+            xml::escape(&article.content)
+        )?;
+        writeln!(writer, "  </entry>")?;
+    }
+
+    writeln!(writer, "</feed>")
+}
+
+// Formats seconds since the Unix epoch as a UTC date-time like 2023-11-14T22:13:20Z
+pub fn rfc3339(timestamp: Timestamp) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+
+    // Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<!-- Sample feed -->
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:georss="http://www.georss.org/georss">
+  <channel>
+    <title>Hockey News</title>
+    <item>
+      <title>Penguins win the Stanley Cup Championship!</title>
+      <dc:creator>Iceburgh</dc:creator>
+      <description>&lt;p&gt;The Pittsburgh Penguins once again are the best &amp;amp; brightest.&lt;/p&gt;</description>
+      <georss:featureName>Pittsburgh, PA, USA</georss:featureName>
+    </item>
+    <item>
+      <title>Caf&#233; &amp; rink &#x2014; open</title>
+      <description><![CDATA[<b>Fans</b> say "a < b" & more]]></description>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example</title>
+  <author><name>Feed Author</name></author>
+  <entry>
+    <title type="html">Hello &lt;em&gt;world&lt;/em&gt;</title>
+    <author><name>Entry Author</name></author>
+    <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml"><p>Some <b>bold</b> text</p></div></content>
+  </entry>
+  <entry>
+    <title>Second</title>
+    <summary>Just a summary</summary>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn reads_rss_items() {
+        let articles = read_feed(RSS.as_bytes()).unwrap();
+
+        assert_eq!(articles.len(), 2);
+        assert_eq!(
+            articles[0].headline,
+            "Penguins win the Stanley Cup Championship!"
+        );
+        assert_eq!(articles[0].author, "Iceburgh");
+        assert_eq!(articles[0].location, "Pittsburgh, PA, USA");
+        assert_eq!(
+            articles[0].content,
+            "The Pittsburgh Penguins once again are the best & brightest."
+        );
+        assert_eq!(articles[1].headline, "Café & rink — open");
+        assert_eq!(articles[1].author, "");
+        assert_eq!(articles[1].content, r#"Fans say "a < b" & more"#);
+    }
+
+    #[test]
+    fn reads_atom_entries() {
+        let articles = read_feed(ATOM.as_bytes()).unwrap();
+
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].headline, "Hello world");
+        assert_eq!(articles[0].author, "Entry Author");
+        assert_eq!(articles[0].content, "Some bold text");
+        assert_eq!(articles[1].author, "Feed Author");
+        assert_eq!(articles[1].content, "Just a summary");
+    }
+
+    #[test]
+    fn malformed_feeds_report_line_numbers() {
+        let error_line = |input: &str| match read_feed(input.as_bytes()) {
+            Err(FeedError::Malformed { line, .. }) => line,
+            other => panic!("Expected a malformed feed error, got {other:?}"),
+        };
+
+        assert_eq!(
+            error_line("<rss>\n<channel>\n<item>\n</channel>\n</rss>"),
+            4
+        );
+        assert_eq!(
+            error_line("<rss>\n<channel><title>A & B</title></channel></rss>"),
+            2
+        );
+        assert_eq!(
+            error_line("<rss>\n\n<channel><title>&bogus;</title></channel></rss>"),
+            3
+        );
+        assert_eq!(error_line("<rss>\n<channel>\n"), 3);
+        assert_eq!(error_line("\n<html></html>"), 2);
+        assert_eq!(error_line("<rss>\n  <nochannel/>\n</rss>"), 1);
+        assert_eq!(
+            error_line("<feed>\n<entry>\n<summary>x</summary></entry></feed>"),
+            2
+        );
+    }
+
+    #[test]
+    fn format_specific_readers_check_the_root() {
+        assert_eq!(read_rss(RSS.as_bytes()).unwrap().len(), 2);
+        assert_eq!(read_atom(ATOM.as_bytes()).unwrap().len(), 2);
+        assert!(read_rss(ATOM.as_bytes()).is_err());
+        assert!(read_atom(RSS.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn extension_elements_are_found_by_namespace() {
+        let rss = r#"<rss version="2.0" xmlns:creator="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <item xmlns:c="http://purl.org/rss/1.0/modules/content/">
+      <title>Renamed prefixes</title>
+      <creator:creator>Iceburgh</creator:creator>
+      <c:encoded>&lt;p&gt;Full text&lt;/p&gt;</c:encoded>
+      <description>Teaser</description>
+      <where xmlns="http://www.georss.org/georss"><featureName>Pittsburgh</featureName></where>
+      <featureName xmlns="http://www.georss.org/georss">Pittsburgh, PA</featureName>
+    </item>
+    <item xmlns:dc="urn:something-else">
+      <title>A dc: prefix in another namespace</title>
+      <dc:creator>Nobody</dc:creator>
+    </item>
+  </channel>
+</rss>"#;
+
+        let articles = read_feed(rss.as_bytes()).unwrap();
+        assert_eq!(articles[0].author, "Iceburgh");
+        assert_eq!(articles[0].content, "Full text");
+        assert_eq!(articles[0].location, "Pittsburgh, PA");
+        assert_eq!(articles[1].author, "");
+    }
+
+    #[test]
+    fn html_markup_is_removed() {
+        assert_eq!(
+            html_to_text("<p>One</p><p>Two<br/>Three</p>"),
+            "One Two Three"
+        );
+        assert_eq!(html_to_text("<b>Bold</b>, <i>italic</i>"), "Bold, italic");
+        assert_eq!(
+            html_to_text("a < b &amp; c &unknown; d"),
+            "a < b & c &unknown; d"
+        );
+    }
+
+    #[test]
+    fn atom_export_round_trips() {
+        let mut articles = read_feed(RSS.as_bytes()).unwrap();
+        articles[1].content =
+            String::from("Quotes \" and <tags> & 'apostrophes'\n\nSecond  paragraph");
+
+        let mut output = Vec::new();
+        write_atom(
+            &mut output,
+            "Export",
+            "urn:example:feed",
+            1_700_000_000,
+            &articles,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("<updated>2023-11-14T22:13:20Z</updated>"));
+        assert!(output.contains("<id>urn:example:feed/2</id>"));
+
+        let imported = read_feed(output.as_bytes()).unwrap();
+        assert_eq!(imported.len(), articles.len());
+        for (imported, original) in imported.iter().zip(&articles) {
+            assert_eq!(imported.headline, original.headline);
+            assert_eq!(imported.author, original.author);
+            assert_eq!(imported.location, original.location);
+            assert_eq!(imported.content, original.content);
+        }
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(1_709_251_199), "2024-02-29T23:59:59Z");
+    }
+}
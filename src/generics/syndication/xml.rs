@@ -0,0 +1,445 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+// A minimal XML reader, just enough for syndication feeds: elements, attributes, text, CDATA and entities.
+// Comments, processing instructions and DOCTYPE declarations are skipped. Element names keep their prefix,
+// and the namespace the prefix is bound to by an xmlns attribute is resolved when parsing.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    // With the prefix, like "dc:creator"
+    pub name: String,
+    // The namespace URI of the prefix, or of the default namespace for names without one
+    pub namespace: Option<String>,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+    // 1-based line of the start tag, for error messages about the element's content
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for XmlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for XmlError {}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |element| element.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    // The name without its prefix
+    pub fn local_name(&self) -> &str {
+        self.name
+            .split_once(':')
+            .map_or(self.name.as_str(), |(_, local)| local)
+    }
+
+    // The first child in `namespace` called `local_name`, whatever prefix the document uses for it
+    pub fn child_in(&self, namespace: &str, local_name: &str) -> Option<&Element> {
+        self.elements().find(|element| {
+            element.namespace.as_deref() == Some(namespace) && element.local_name() == local_name
+        })
+    }
+
+    // Concatenated text of the element and all of its descendants
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    fn collect_text(&self, text: &mut String) {
+        for node in &self.children {
+            match node {
+                Node::Text(t) => text.push_str(t),
+                Node::Element(element) => element.collect_text(text),
+            }
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Element, XmlError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        line: 1,
+        namespaces: Vec::new(),
+    };
+
+    parser.skip_misc()?;
+    if parser.at_end() {
+        return Err(parser.error("Document has no root element"));
+    }
+
+    let root = parser.element()?;
+
+    parser.skip_misc()?;
+    if !parser.at_end() {
+        return Err(parser.error("Unexpected content after the root element"));
+    }
+
+    Ok(root)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+    // Prefixes ("" for the default namespace) and their URIs declared by the open elements, innermost last
+    namespaces: Vec<(String, String)>,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn error(&self, message: &str) -> XmlError {
+        XmlError {
+            line: self.line,
+            message: String::from(message),
+        }
+    }
+
+    // Advances by `len` bytes, counting the newlines that are skipped
+    fn advance(&mut self, len: usize) {
+        let skipped = &self.input[self.pos..self.pos + len];
+        self.line += skipped.matches('\n').count();
+        self.pos += len;
+    }
+
+    // Returns everything up to `terminator` and moves past the terminator
+    fn take_until(&mut self, terminator: &str, what: &str) -> Result<&str, XmlError> {
+        match self.rest().find(terminator) {
+            Some(end) => {
+                let start = self.pos;
+                self.advance(end + terminator.len());
+                Ok(&self.input[start..start + end])
+            }
+            None => Err(self.error(&format!("Unterminated {what}"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let len = self.rest().len() - self.rest().trim_start().len();
+        self.advance(len);
+    }
+
+    // Whitespace, comments, processing instructions and DOCTYPE outside of the root element
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+
+            if self.rest().starts_with("<?") {
+                self.take_until("?>", "processing instruction")?;
+            } else if self.rest().starts_with("<!--") {
+                self.take_until("-->", "comment")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.take_until(">", "DOCTYPE declaration")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '=' | '<'))
+            .unwrap_or(self.rest().len());
+
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+
+        let name = String::from(&self.rest()[..len]);
+        self.advance(len);
+        Ok(name)
+    }
+
+    fn element(&mut self) -> Result<Element, XmlError> {
+        if !self.rest().starts_with('<') {
+            return Err(self.error("Expected '<'"));
+        }
+
+        let line = self.line;
+        self.advance(1);
+
+        let name = self.name()?;
+        let mut attributes = Vec::new();
+
+        let empty = loop {
+            self.skip_whitespace();
+
+            if self.rest().starts_with("/>") {
+                self.advance(2);
+                break true;
+            } else if self.rest().starts_with('>') {
+                self.advance(1);
+                break false;
+            } else if self.at_end() {
+                return Err(self.error(&format!("Unterminated start tag <{name}>")));
+            }
+
+            attributes.push(self.attribute()?);
+        };
+
+        // Declarations apply to the element itself and everything inside it
+        let outer_namespaces = self.namespaces.len();
+        for (key, value) in &attributes {
+            let prefix = match key.strip_prefix("xmlns") {
+                Some("") => "",
+                Some(prefixed) => match prefixed.strip_prefix(':') {
+                    Some(prefix) => prefix,
+                    None => continue,
+                },
+                None => continue,
+            };
+            self.namespaces.push((String::from(prefix), value.clone()));
+        }
+
+        let prefix = name.split_once(':').map_or("", |(prefix, _)| prefix);
+        let namespace = self
+            .namespaces
+            .iter()
+            .rev()
+            .find(|(declared, _)| declared == prefix)
+            .map(|(_, uri)| uri.clone())
+            // xmlns="" takes the default namespace away again
+            .filter(|uri| !uri.is_empty());
+
+        let children = if empty {
+            Vec::new()
+        } else {
+            self.content(&name)?
+        };
+        self.namespaces.truncate(outer_namespaces);
+
+        Ok(Element {
+            name,
+            namespace,
+            attributes,
+            children,
+            line,
+        })
+    }
+
+    fn attribute(&mut self) -> Result<(String, String), XmlError> {
+        let key = self.name()?;
+        self.skip_whitespace();
+
+        if !self.rest().starts_with('=') {
+            return Err(self.error(&format!("Expected '=' after attribute {key}")));
+        }
+        self.advance(1);
+        self.skip_whitespace();
+
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error(&format!("Expected a quoted value for attribute {key}"))),
+        };
+        self.advance(1);
+
+        let line = self.line;
+        let raw = self.take_until(&quote.to_string(), "attribute value")?;
+        let value = decode_entities(raw).map_err(|message| XmlError { line, message })?;
+
+        Ok((key, value))
+    }
+
+    // Child nodes up to and including the end tag of `name`
+    fn content(&mut self, name: &str) -> Result<Vec<Node>, XmlError> {
+        let mut children = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            if self.at_end() {
+                return Err(self.error(&format!("Missing end tag </{name}>")));
+            }
+
+            if self.rest().starts_with("</") {
+                self.advance(2);
+                let end_name = self.name()?;
+
+                if end_name != name {
+                    return Err(self.error(&format!(
+                        "Mismatched end tag: expected </{name}>, found </{end_name}>"
+                    )));
+                }
+
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error(&format!("Unterminated end tag </{name}>")));
+                }
+                self.advance(1);
+                break;
+            } else if self.rest().starts_with("<![CDATA[") {
+                self.advance("<![CDATA[".len());
+                text.push_str(self.take_until("]]>", "CDATA section")?);
+            } else if self.rest().starts_with("<!--") {
+                self.take_until("-->", "comment")?;
+            } else if self.rest().starts_with("<?") {
+                self.take_until("?>", "processing instruction")?;
+            } else if self.rest().starts_with('<') {
+                flush_text(&mut text, &mut children);
+                children.push(Node::Element(self.element()?));
+            } else {
+                let len = self.rest().find('<').unwrap_or(self.rest().len());
+                let line = self.line;
+                let raw = &self.rest()[..len];
+                text.push_str(&decode_entities(raw).map_err(|message| XmlError { line, message })?);
+                self.advance(len);
+            }
+        }
+
+        flush_text(&mut text, &mut children);
+        Ok(children)
+    }
+}
+
+// Adjacent text and CDATA sections end up in one text node
+fn flush_text(text: &mut String, children: &mut Vec<Node>) {
+    if !text.is_empty() {
+        children.push(Node::Text(std::mem::take(text)));
+    }
+}
+
+// Decodes the five XML entities, numeric character references and the HTML entities that are common in feeds
+pub fn decode_entities(raw: &str) -> Result<String, String> {
+    decode(raw, true)
+}
+
+// Like decode_entities, but keeps unknown entities and bare '&' characters as they are
+pub fn decode_entities_lenient(raw: &str) -> String {
+    decode(raw, false).unwrap_or_else(|_| String::from(raw))
+}
+
+fn decode(raw: &str, strict: bool) -> Result<String, String> {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|&end| end <= 12)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None if strict => return Err(invalid_entity(rest)),
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+fn invalid_entity(rest: &str) -> String {
+    match rest.find(';').filter(|&end| end <= 12) {
+        Some(end) => format!("Unknown entity {}", &rest[..=end]),
+        None => String::from("Unescaped '&' in text"),
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match entity {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "hellip" => '…',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "euro" => '€',
+        "laquo" => '«',
+        "raquo" => '»',
+        "eacute" => 'é',
+        "uuml" => 'ü',
+        "ouml" => 'ö',
+        "auml" => 'ä',
+        _ => return None,
+    };
+    Some(c)
+}
+
+// Escapes text for use in element content and double quoted attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
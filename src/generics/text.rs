@@ -0,0 +1,195 @@
+use std::iter;
+use std::ops::Range;
+
+// Small text processing helpers shared by the summarizer and other text features
+
+// Common English words that carry little meaning on their own
+const STOPWORDS: [&str; 64] = [
+    "a", "about", "after", "all", "also", "an", "and", "are", "as", "at", "be", "been", "but",
+    "by", "can", "do", "for", "from", "had", "has", "have", "he", "her", "his", "i", "if", "in",
+    "into", "is", "it", "its", "more", "no", "not", "of", "on", "once", "one", "or", "our", "she",
+    "so", "than", "that", "the", "their", "them", "there", "they", "this", "to", "up", "was", "we",
+    "were", "what", "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+// Abbreviations whose trailing period doesn't end a sentence, compared in lowercase
+const ABBREVIATIONS: [&str; 23] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd",
+    "co", "corp", "fig", "approx", "dept", "jan", "feb", "aug", "sept",
+];
+
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.binary_search(&word).is_ok()
+}
+
+// Lowercased words made of alphanumeric characters, apostrophes inside words are kept ("don't")
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    word_spans(text).map(|span| text[span].to_lowercase())
+}
+
+// Byte ranges of the words that `words` returns, in their original case
+pub fn word_spans(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '\'';
+    let mut chars = text.char_indices().peekable();
+
+    iter::from_fn(move || loop {
+        while chars.next_if(|&(_, c)| !is_word_char(c)).is_some() {}
+
+        let &(start, _) = chars.peek()?;
+        let mut end = start;
+        while let Some((i, c)) = chars.next_if(|&(_, c)| is_word_char(c)) {
+            end = i + c.len_utf8();
+        }
+
+        // Apostrophes are only part of a word in the middle, like in "don't"
+        let run = &text[start..end];
+        let start = start + (run.len() - run.trim_start_matches('\'').len());
+        let end = end - (run.len() - run.trim_end_matches('\'').len());
+
+        if start < end {
+            return Some(start..end);
+        }
+    })
+}
+
+// Words that aren't stopwords
+pub fn content_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    words(text).filter(|word| !is_stopword(word))
+}
+
+// Byte ranges of the sentences in `text`, without surrounding whitespace.
+// A sentence ends at '.', '!' or '?' (including runs like "?!" or "...") followed by whitespace and
+// the end of the text or an uppercase letter, digit or opening quote. Periods after known abbreviations
+// ("Mr.", "e.g.") and single letter initials don't end a sentence, and neither do decimal points ("3.14").
+pub fn sentence_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+
+        // Take the whole run of terminators and closing quotes or brackets
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if matches!(next, '.' | '!' | '?' | '"' | '\'' | ')' | '”' | '’') {
+                end = j + next.len_utf8();
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let after = &text[end..];
+        let next_start = after.trim_start();
+        let followed_by_space = after.len() != next_start.len() || after.is_empty();
+        let starts_sentence = next_start.is_empty()
+            || next_start.starts_with(|c: char| {
+                c.is_uppercase() || c.is_ascii_digit() || matches!(c, '"' | '\'' | '“' | '‘' | '(')
+            });
+
+        if c == '.' && end == i + 1 && is_abbreviation(&text[start..i]) {
+            continue;
+        }
+
+        if followed_by_space && starts_sentence {
+            push_trimmed(text, start..end, &mut spans);
+            start = end;
+        }
+    }
+
+    push_trimmed(text, start..text.len(), &mut spans);
+    spans
+}
+
+pub fn sentences(text: &str) -> Vec<&str> {
+    sentence_spans(text)
+        .into_iter()
+        .map(|span| &text[span])
+        .collect()
+}
+
+// Whether the word right before a period is an abbreviation or an initial like the "J." in "J. Smith"
+fn is_abbreviation(before_period: &str) -> bool {
+    let word = before_period
+        .rsplit(|c: char| c.is_whitespace() || c == '(' || c == '"')
+        .next()
+        .unwrap_or("");
+
+    let is_initial = word.chars().count() == 1 && word.chars().all(char::is_uppercase);
+
+    is_initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+fn push_trimmed(text: &str, span: Range<usize>, spans: &mut Vec<Range<usize>>) {
+    let slice = &text[span.clone()];
+    let trimmed_start = span.start + (slice.len() - slice.trim_start().len());
+    let trimmed_end = span.end - (slice.len() - slice.trim_end().len());
+
+    if trimmed_start < trimmed_end {
+        spans.push(trimmed_start..trimmed_end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopwords_are_sorted_for_binary_search() {
+        assert!(STOPWORDS.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(is_stopword("the"));
+        assert!(!is_stopword("penguins"));
+    }
+
+    #[test]
+    fn words_are_lowercased_without_punctuation() {
+        let words: Vec<String> = words("Don't stop, 'Penguins' -- 2024!").collect();
+
+        assert_eq!(words, vec!["don't", "stop", "penguins", "2024"]);
+        assert_eq!(
+            content_words("The best of the NHL").collect::<Vec<_>>(),
+            vec!["best", "nhl"]
+        );
+    }
+
+    #[test]
+    fn word_spans_point_into_the_source() {
+        let text = "'Tis Mr. O'Neil's café";
+
+        let spans: Vec<&str> = word_spans(text).map(|span| &text[span]).collect();
+
+        assert_eq!(spans, vec!["Tis", "Mr", "O'Neil's", "café"]);
+    }
+
+    #[test]
+    fn sentences_respect_abbreviations_and_numbers() {
+        let text = "Call me Mr. Smith. Pi is 3.14 today! Really?! \"Yes.\" Wait... what? E.g. this. J. R. R. Tolkien wrote it.";
+
+        assert_eq!(
+            sentences(text),
+            vec![
+                "Call me Mr. Smith.",
+                "Pi is 3.14 today!",
+                "Really?!",
+                "\"Yes.\"",
+                "Wait... what?",
+                "E.g. this.",
+                "J. R. R. Tolkien wrote it.",
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_point_into_the_source() {
+        let text = "  First one.  Second one  ";
+
+        let spans = sentence_spans(text);
+
+        assert_eq!(spans, vec![2..12, 14..24]);
+        assert_eq!(&text[spans[1].clone()], "Second one");
+        assert!(sentence_spans("   ").is_empty());
+    }
+}
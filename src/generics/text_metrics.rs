@@ -0,0 +1,202 @@
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Ways to measure the length of a string.
+// `len()` counts UTF-8 bytes, so "Здравствуйте" (12 letters, 24 bytes) looks longer than "Good morning!" (13).
+// What people call a character is a grapheme cluster: "é" written as 'e' plus a combining accent is two chars
+// but one grapheme. Terminals need the display width: East Asian wide characters and most emoji take two
+// columns, combining marks and zero-width characters none.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Bytes,
+    Chars,
+    Graphemes,
+    Width,
+}
+
+impl Metric {
+    pub fn measure(self, text: &str) -> usize {
+        match self {
+            Metric::Bytes => text.len(),
+            Metric::Chars => text.chars().count(),
+            Metric::Graphemes => graphemes(text),
+            Metric::Width => display_width(text),
+        }
+    }
+}
+
+pub fn graphemes(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// Columns the text takes in a terminal with a monospaced font
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+// Like lifetimes::longest_string, but with a choice of metric. Ties go to `y`, as there.
+pub fn longest<'a>(x: &'a str, y: &'a str, metric: Metric) -> &'a str {
+    if metric.measure(x) > metric.measure(y) {
+        x
+    } else {
+        y
+    }
+}
+
+// The first of the longest strings, None if there are no strings
+pub fn longest_by<'a>(
+    strings: impl IntoIterator<Item = &'a str>,
+    metric: Metric,
+) -> Option<&'a str> {
+    strings
+        .into_iter()
+        .map(|text| (metric.measure(text), text))
+        .reduce(|best, next| if next.0 > best.0 { next } else { best })
+        .map(|(_, text)| text)
+}
+
+// The first of the shortest strings, None if there are no strings
+pub fn shortest_by<'a>(
+    strings: impl IntoIterator<Item = &'a str>,
+    metric: Metric,
+) -> Option<&'a str> {
+    strings
+        .into_iter()
+        .map(|text| (metric.measure(text), text))
+        .reduce(|best, next| if next.0 < best.0 { next } else { best })
+        .map(|(_, text)| text)
+}
+
+// Cuts the text to at most `width` columns, ending with `ellipsis` if anything was cut.
+// Only whole grapheme clusters are kept, so accents stay on their letters and wide characters are never split.
+// Text that fits is returned as it is without copying.
+pub fn truncate_to_width<'a>(text: &'a str, width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if display_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+
+    let available = width.saturating_sub(display_width(ellipsis));
+    let mut used = 0;
+    let mut end = 0;
+
+    for (start, grapheme) in text.grapheme_indices(true) {
+        let grapheme_width = display_width(grapheme);
+        if used + grapheme_width > available {
+            break;
+        }
+        used += grapheme_width;
+        end = start + grapheme.len();
+    }
+
+    // An ellipsis wider than the whole width is left out
+    if display_width(ellipsis) > width {
+        return Cow::Borrowed(&text[..end]);
+    }
+    Cow::Owned(format!("{}{ellipsis}", &text[..end]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    // Extra space goes to the right when it can't be split evenly
+    Center,
+}
+
+// Pads the text with spaces to `width` columns, text that is already wider is left alone
+pub fn pad_to_width(text: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(display_width(text));
+
+    let (left, right) = match alignment {
+        Alignment::Left => (0, padding),
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+    };
+
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+// Exactly `width` columns: truncated with "…" when too wide, padded when too narrow, e.g. for table cells.
+// A wide character that doesn't fit leaves a column free, which is padded too.
+pub fn fit_to_width(text: &str, width: usize, alignment: Alignment) -> String {
+    pad_to_width(&truncate_to_width(text, width, "…"), width, alignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUSSIAN: &str = "Здравствуйте";
+    // "é" as 'e' followed by U+0301 COMBINING ACUTE ACCENT
+    const COMBINED: &str = "cafe\u{301}";
+    const JAPANESE: &str = "日本語";
+
+    #[test]
+    fn metrics_disagree_on_non_ascii_text() {
+        assert_eq!(Metric::Bytes.measure(RUSSIAN), 24);
+        assert_eq!(Metric::Chars.measure(RUSSIAN), 12);
+
+        assert_eq!(Metric::Chars.measure(COMBINED), 5);
+        assert_eq!(Metric::Graphemes.measure(COMBINED), 4);
+        assert_eq!(Metric::Width.measure(COMBINED), 4);
+
+        assert_eq!(Metric::Graphemes.measure(JAPANESE), 3);
+        assert_eq!(Metric::Width.measure(JAPANESE), 6);
+        assert_eq!(display_width("a\u{200b}b"), 2);
+    }
+
+    #[test]
+    fn longest_and_shortest_use_the_metric() {
+        let greetings = [RUSSIAN, "Good morning!", JAPANESE];
+
+        assert_eq!(longest(RUSSIAN, "Good morning!", Metric::Bytes), RUSSIAN);
+        assert_eq!(
+            longest(RUSSIAN, "Good morning!", Metric::Chars),
+            "Good morning!"
+        );
+
+        assert_eq!(longest_by(greetings, Metric::Bytes), Some(RUSSIAN));
+        assert_eq!(longest_by(greetings, Metric::Chars), Some("Good morning!"));
+        assert_eq!(shortest_by(greetings, Metric::Chars), Some(JAPANESE));
+        assert_eq!(shortest_by(greetings, Metric::Width), Some(JAPANESE));
+        assert_eq!(longest_by([], Metric::Chars), None);
+        // The first one wins ties
+        assert_eq!(longest_by(["ab", "cd"], Metric::Chars), Some("ab"));
+    }
+
+    #[test]
+    fn truncation_respects_graphemes_and_wide_characters() {
+        assert!(matches!(
+            truncate_to_width("short", 10, "…"),
+            Cow::Borrowed("short")
+        ));
+        assert_eq!(truncate_to_width("Hello, world", 8, "…"), "Hello, …");
+        assert_eq!(truncate_to_width(COMBINED, 4, ""), COMBINED);
+        assert_eq!(truncate_to_width("cafe\u{301}s", 4, ""), COMBINED);
+        // Two columns per character, the third doesn't fit next to the ellipsis
+        assert_eq!(truncate_to_width(JAPANESE, 5, "…"), "日本…");
+        assert_eq!(truncate_to_width(JAPANESE, 4, "…"), "日…");
+        assert_eq!(truncate_to_width("abc", 0, "…"), "");
+    }
+
+    #[test]
+    fn table_cells_have_exact_widths() {
+        assert_eq!(pad_to_width("ab", 5, Alignment::Left), "ab   ");
+        assert_eq!(pad_to_width("ab", 5, Alignment::Right), "   ab");
+        assert_eq!(pad_to_width("ab", 5, Alignment::Center), " ab  ");
+        assert_eq!(pad_to_width("toolong", 3, Alignment::Left), "toolong");
+
+        let rows = [["Name", JAPANESE], [RUSSIAN, COMBINED]];
+        for row in rows {
+            let line: Vec<String> = row
+                .iter()
+                .map(|cell| fit_to_width(cell, 7, Alignment::Left))
+                .collect();
+
+            assert!(line.iter().all(|cell| display_width(cell) == 7), "{line:?}");
+        }
+        assert_eq!(fit_to_width(RUSSIAN, 7, Alignment::Left), "Здравс…");
+    }
+}
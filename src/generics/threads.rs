@@ -0,0 +1,354 @@
+use crate::generics::traits::{Summary, Tweet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::iter;
+
+// A tweet together with all replies to it, replies are ordered by id
+#[derive(Debug)]
+pub struct ThreadNode<'a> {
+    pub tweet: &'a Tweet,
+    pub replies: Vec<ThreadNode<'a>>,
+}
+
+// Conversation trees reconstructed from a flat list of tweets
+#[derive(Debug, Default)]
+pub struct Threads<'a> {
+    pub roots: Vec<ThreadNode<'a>>,
+    // Replies whose parent isn't in the list, they start their own thread
+    pub missing_parents: Vec<u64>,
+    // Tweets chosen as thread roots to break reply cycles like a -> b -> a
+    pub broken_cycles: Vec<u64>,
+    // Ids that appeared more than once, only the first tweet with such an id is used
+    pub duplicate_ids: Vec<u64>,
+}
+
+// Threads come from untrusted input and can be very deep, so nothing here recurses once per reply level.
+// A recursion could run out of stack on a long enough reply chain.
+impl<'a> ThreadNode<'a> {
+    // This node and all replies below it, depth first in reply order, with their level below this node
+    pub fn walk(&self) -> impl Iterator<Item = (usize, &ThreadNode<'a>)> + '_ {
+        let mut stack = vec![(0, self)];
+
+        iter::from_fn(move || {
+            let (level, node) = stack.pop()?;
+            stack.extend(node.replies.iter().rev().map(|reply| (level + 1, reply)));
+            Some((level, node))
+        })
+    }
+
+    // Number of tweets in the thread including this one
+    pub fn tweet_count(&self) -> usize {
+        self.walk().count()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.walk().map(|(level, _)| level + 1).max().unwrap_or(1)
+    }
+}
+
+// Dropping the replies one by one would recurse as well
+impl Drop for ThreadNode<'_> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.replies);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.replies);
+        }
+    }
+}
+
+// A whole thread is summarized as one tweet per line, replies indented by two spaces per level
+impl Summary for ThreadNode<'_> {
+    fn summarize_author(&self) -> String {
+        self.tweet.summarize_author()
+    }
+
+    fn summarize(&self) -> String {
+        self.walk()
+            .map(|(level, node)| format!("{}{}", "  ".repeat(level), node.tweet.summarize()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub fn build_threads(tweets: &[Tweet]) -> Threads<'_> {
+    let mut threads = Threads::default();
+    let mut by_id: HashMap<u64, &Tweet> = HashMap::new();
+
+    for tweet in tweets {
+        match by_id.entry(tweet.id) {
+            Entry::Occupied(_) => threads.duplicate_ids.push(tweet.id),
+            Entry::Vacant(entry) => {
+                entry.insert(tweet);
+            }
+        }
+    }
+
+    // BTreeMap and sorted Vecs keep the output independent of the HashMap iteration order
+    let mut replies: BTreeMap<u64, Vec<&Tweet>> = BTreeMap::new();
+    let mut ids: Vec<u64> = by_id.keys().copied().collect();
+    ids.sort_unstable();
+
+    for id in &ids {
+        let tweet = by_id[id];
+
+        if let Some(parent) = tweet.in_reply_to {
+            replies.entry(parent).or_default().push(tweet);
+        }
+    }
+
+    let mut visited = HashSet::new();
+
+    for id in &ids {
+        let tweet = by_id[id];
+
+        match tweet.in_reply_to {
+            None => {}
+            Some(parent) if !by_id.contains_key(&parent) => threads.missing_parents.push(*id),
+            Some(_) => continue,
+        }
+
+        threads
+            .roots
+            .push(build_node(tweet, &replies, &mut visited));
+    }
+
+    // Whatever wasn't reached from a root hangs off a reply cycle.
+    // Walking up the parents from such a tweet must end in a cycle, the first repeated tweet becomes the root.
+    for id in &ids {
+        if visited.contains(id) {
+            continue;
+        }
+
+        let mut path = HashSet::new();
+        let mut current = *id;
+
+        while path.insert(current) {
+            current = by_id[&current]
+                .in_reply_to
+                .expect("Unvisited tweets always have a parent");
+        }
+
+        threads.broken_cycles.push(current);
+        threads
+            .roots
+            .push(build_node(by_id[&current], &replies, &mut visited));
+    }
+
+    threads
+}
+
+fn build_node<'a>(
+    tweet: &'a Tweet,
+    replies: &BTreeMap<u64, Vec<&'a Tweet>>,
+    visited: &mut HashSet<u64>,
+) -> ThreadNode<'a> {
+    let start = |tweet: &'a Tweet| {
+        let children = replies
+            .get(&tweet.id)
+            .map(|children| children.as_slice())
+            .unwrap_or_default();
+        let node = ThreadNode {
+            tweet,
+            replies: Vec::new(),
+        };
+        (node, children)
+    };
+
+    // The nodes from `tweet` down to the one being built, each with the replies that are still to do
+    visited.insert(tweet.id);
+    let mut path = vec![start(tweet)];
+
+    loop {
+        let (_, children) = path.last_mut().expect("The path ends with the root");
+
+        if let Some((child, rest)) = children.split_first() {
+            *children = rest;
+            // Skipping visited tweets is what stops the walk inside reply cycles
+            if visited.insert(child.id) {
+                path.push(start(child));
+            }
+            continue;
+        }
+
+        let (node, _) = path.pop().expect("The path ends with the root");
+        match path.last_mut() {
+            Some((parent, _)) => parent.replies.push(node),
+            None => return node,
+        }
+    }
+}
+
+// Number of retweets per original tweet id.
+// Retweets of retweets count for the original tweet. Broken data with retweet cycles still terminates,
+// such retweets count for the last tweet reached before the cycle repeats.
+pub fn retweet_counts(tweets: &[Tweet]) -> HashMap<u64, usize> {
+    let retweet_of: HashMap<u64, u64> = tweets
+        .iter()
+        .filter_map(|tweet| Some((tweet.id, tweet.retweet_of?)))
+        .collect();
+
+    let mut counts = HashMap::new();
+
+    for &original in retweet_of.values() {
+        let mut seen = HashSet::from([original]);
+        let mut current = original;
+
+        while let Some(&next) = retweet_of.get(&current) {
+            if !seen.insert(next) {
+                break;
+            }
+            current = next;
+        }
+
+        *counts.entry(current).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tweet(id: u64, in_reply_to: Option<u64>, content: &str) -> Tweet {
+        Tweet {
+            username: format!("user{id}"),
+            content: String::from(content),
+            id,
+            in_reply_to,
+            retweet_of: None,
+        }
+    }
+
+    fn retweet(id: u64, retweet_of: u64) -> Tweet {
+        Tweet {
+            retweet_of: Some(retweet_of),
+            ..tweet(id, None, "RT")
+        }
+    }
+
+    #[test]
+    fn reconstructs_threads_from_a_flat_list() {
+        let tweets = vec![
+            tweet(4, Some(2), "nested"),
+            tweet(1, None, "root"),
+            tweet(3, Some(1), "second reply"),
+            tweet(2, Some(1), "first reply"),
+            tweet(5, None, "other root"),
+        ];
+
+        let threads = build_threads(&tweets);
+
+        assert_eq!(threads.roots.len(), 2);
+        assert_eq!(threads.roots[0].tweet_count(), 4);
+        assert_eq!(threads.roots[0].depth(), 3);
+        assert_eq!(
+            threads.roots[0].summarize(),
+            "user1: root\n  user2: first reply\n    user4: nested\n  user3: second reply"
+        );
+        assert_eq!(threads.roots[1].summarize(), "user5: other root");
+        assert_eq!(threads.roots[0].summarize_author(), "@user1");
+        assert!(threads.missing_parents.is_empty());
+        assert!(threads.broken_cycles.is_empty());
+    }
+
+    #[test]
+    fn replies_to_missing_tweets_start_their_own_thread() {
+        let tweets = vec![tweet(7, Some(99), "orphan"), tweet(8, Some(7), "reply")];
+
+        let threads = build_threads(&tweets);
+
+        assert_eq!(threads.missing_parents, vec![7]);
+        assert_eq!(threads.roots.len(), 1);
+        assert_eq!(
+            threads.roots[0].summarize(),
+            "user7: orphan\n  user8: reply"
+        );
+    }
+
+    #[test]
+    fn reply_cycles_are_broken() {
+        let tweets = vec![
+            tweet(1, Some(3), "a"),
+            tweet(2, Some(1), "b"),
+            tweet(3, Some(2), "c"),
+            tweet(4, Some(4), "self reply"),
+            tweet(5, Some(1), "hanging off the cycle"),
+        ];
+
+        let threads = build_threads(&tweets);
+
+        assert_eq!(threads.broken_cycles, vec![1, 4]);
+        assert_eq!(threads.roots.len(), 2);
+        assert_eq!(threads.roots[0].tweet_count(), 4);
+        assert_eq!(threads.roots[1].tweet_count(), 1);
+        assert_eq!(
+            threads
+                .roots
+                .iter()
+                .map(ThreadNode::tweet_count)
+                .sum::<usize>(),
+            tweets.len()
+        );
+    }
+
+    #[test]
+    fn long_reply_chains_dont_overflow_the_stack() {
+        let length = 200_000;
+        let tweets: Vec<Tweet> = (0..length)
+            .map(|id| tweet(id, id.checked_sub(1), "and another thing"))
+            .collect();
+
+        let threads = build_threads(&tweets);
+
+        assert_eq!(threads.roots.len(), 1);
+        assert_eq!(threads.roots[0].tweet_count(), tweets.len());
+        assert_eq!(threads.roots[0].depth(), tweets.len());
+        assert_eq!(
+            threads.roots[0]
+                .walk()
+                .nth(2)
+                .map(|(level, node)| (level, node.tweet.id)),
+            Some((2, 2))
+        );
+    }
+
+    #[test]
+    fn duplicate_ids_are_reported() {
+        let tweets = vec![tweet(1, None, "first"), tweet(1, None, "again")];
+
+        let threads = build_threads(&tweets);
+
+        assert_eq!(threads.duplicate_ids, vec![1]);
+        assert_eq!(threads.roots.len(), 1);
+        assert_eq!(threads.roots[0].summarize(), "user1: first");
+    }
+
+    #[test]
+    fn counts_retweets_of_the_original() {
+        let tweets = vec![
+            tweet(1, None, "original"),
+            retweet(2, 1),
+            retweet(3, 1),
+            retweet(4, 3),
+            retweet(5, 42),
+        ];
+
+        let counts = retweet_counts(&tweets);
+
+        assert_eq!(counts.get(&1), Some(&3));
+        assert_eq!(counts.get(&42), Some(&1));
+        assert_eq!(counts.get(&3), None);
+        assert!(tweets[1].is_retweet());
+        assert!(!tweets[0].is_reply());
+    }
+
+    #[test]
+    fn retweet_cycles_terminate() {
+        let tweets = vec![retweet(1, 2), retweet(2, 1)];
+
+        let counts = retweet_counts(&tweets);
+
+        assert_eq!(counts.values().sum::<usize>(), 2);
+    }
+}
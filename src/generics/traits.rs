@@ -1,4 +1,6 @@
+use crate::generics::summarizer::Summarizer;
 use std::fmt::{Display, Formatter, Result};
+use summary_derive::Summary;
 
 // Generic type parameter that is trait bound on 2 traits
 pub fn breaking_news<T: Summary + Display>(news: &T) {
@@ -10,8 +12,9 @@ pub fn summarizable_tweet() -> impl Summary {
     Tweet {
         username: String::from("horse_ebooks"),
         content: String::from("of course, as you probably already know, people"),
-        reply: false,
-        retweet: false,
+        id: 1,
+        in_reply_to: None,
+        retweet_of: None,
     }
 }
 
@@ -40,9 +43,23 @@ pub trait Summary {
     // Signature declaration without default implementation
     fn summarize_author(&self) -> String;
 
+    // Body text that the default `summarize` condenses, types without one keep the short teaser
+    fn summarize_content(&self) -> Option<&str> {
+        None
+    }
+
     // Signature with default implementation
     fn summarize(&self) -> String {
-        format!("(Read more from {}...)", self.summarize_author())
+        let summary = self
+            .summarize_content()
+            .map(|content| Summarizer::default().summarize(content))
+            .filter(|summary| !summary.trim().is_empty());
+
+        match summary {
+            Some(summary) => format!("{summary} (Read more from {}...)", self.summarize_author()),
+            // Also for empty content, which has nothing to condense
+            None => format!("(Read more from {}...)", self.summarize_author()),
+        }
     }
 }
 
@@ -63,6 +80,7 @@ where
     }
 }
 
+#[derive(Debug)]
 pub struct NewsArticle {
     pub headline: String,
     pub location: String,
@@ -75,6 +93,10 @@ impl Summary for NewsArticle {
     fn summarize_author(&self) -> String {
         format!("{}", self.author)
     }
+
+    fn summarize_content(&self) -> Option<&str> {
+        Some(&self.content)
+    }
 }
 
 // Implement trait for type
@@ -88,11 +110,25 @@ impl Display for NewsArticle {
     }
 }
 
+#[derive(Debug)]
 pub struct Tweet {
     pub username: String,
     pub content: String,
-    pub reply: bool,
-    pub retweet: bool,
+    pub id: u64,
+    // Id of the tweet this one replies to
+    pub in_reply_to: Option<u64>,
+    // Id of the tweet this one retweets
+    pub retweet_of: Option<u64>,
+}
+
+impl Tweet {
+    pub fn is_reply(&self) -> bool {
+        self.in_reply_to.is_some()
+    }
+
+    pub fn is_retweet(&self) -> bool {
+        self.retweet_of.is_some()
+    }
 }
 
 // Implement trait for type
@@ -101,6 +137,10 @@ impl Summary for Tweet {
         format!("@{}", self.username)
     }
 
+    fn summarize_content(&self) -> Option<&str> {
+        Some(&self.content)
+    }
+
     // Override trait's default methods implementation
     fn summarize(&self) -> String {
         format!("{}: {}", self.username, self.content)
@@ -112,7 +152,21 @@ impl Display for Tweet {
         write!(
             f,
             "({}, {}, {}, {})",
-            self.username, self.content, self.reply, self.retweet
+            self.username,
+            self.content,
+            self.is_reply(),
+            self.is_retweet()
         )
     }
 }
+
+// Derive the trait instead of implementing it by hand, the macro generates the same kind of impl as above
+#[derive(Debug, Summary)]
+#[summary(template = "{title} with {host}")]
+pub struct Podcast {
+    pub title: String,
+    #[summary(author, prefix = "host ")]
+    pub host: String,
+    #[summary(content)]
+    pub show_notes: String,
+}
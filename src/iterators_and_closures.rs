@@ -1,3 +1,19 @@
+pub mod cacher;
+pub mod giveaway;
+pub mod inventory;
+pub mod iter_ext;
+pub mod journal;
+pub mod pipeline;
+pub mod shared;
+
+use crate::iterators_and_closures::cacher::{Cacher, Eviction};
+use crate::iterators_and_closures::giveaway::{
+    GiveawayPolicy, MostStocked, PreferenceWithSubstitution, RoundRobin, User, WeightedRandom,
+};
+use crate::iterators_and_closures::inventory::{Inventory, ShirtColor, Size, Sku};
+use crate::iterators_and_closures::iter_ext::IterExt;
+use crate::iterators_and_closures::pipeline::{Options, Pipeline};
+use crate::iterators_and_closures::shared::{AtomicInventory, SharedInventory};
 use std::thread;
 use std::time::Duration;
 
@@ -7,24 +23,77 @@ use std::time::Duration;
 //     - FnMut applies to closures that don’t move captured values out of their body, but that might mutate the captured values. These closures can be called more than once.
 //     - Fn applies to closures that don’t move captured values out of their body and that don’t mutate captured values, as well as closures that capture nothing from their environment. These closures can be called more than once without mutating their environment, which is important in cases such as calling a closure multiple times concurrently.
 pub fn closures() {
-    let store = Inventory {
-        shirts: vec![ShirtColor::Blue, ShirtColor::Red, ShirtColor::Blue],
-    };
+    let mut store = Inventory::new(1);
+    store
+        .receive(Sku::new(ShirtColor::Blue, Size::M), 2, 0)
+        .unwrap();
+    store
+        .receive(Sku::new(ShirtColor::Red, Size::M), 1, 0)
+        .unwrap();
 
+    // giveaway() passes a closure to or_else, which is only called when the user has no preference
     let user_pref1 = Some(ShirtColor::Red);
     println!(
         "The user with preference {:?} gets {:?}",
         user_pref1,
-        store.giveaway(user_pref1)
+        store.giveaway(user_pref1, Size::M, 1)
     );
 
     let user_pref2 = None;
     println!(
         "The user with preference {:?} gets {:?}",
         user_pref2,
-        store.giveaway(user_pref2)
+        store.giveaway(user_pref2, Size::M, 2)
     );
 
+    // The red shirt is gone
+    if let Err(error) = store.giveaway(user_pref1, Size::M, 3) {
+        println!("The user with preference {user_pref1:?} gets nothing: {error}");
+    }
+    for alert in store.take_alerts() {
+        println!("Low stock: {} has {} left", alert.sku, alert.available);
+    }
+
+    // Policies decide who gets what once colors run out
+    let mut stock = Inventory::new(0);
+    for (color, quantity) in [
+        (ShirtColor::Red, 3),
+        (ShirtColor::Blue, 6),
+        (ShirtColor::Black, 3),
+    ] {
+        stock
+            .receive(Sku::new(color, Size::L), quantity, 0)
+            .unwrap();
+    }
+    let users: Vec<User> = [Some(ShirtColor::Red), None, Some(ShirtColor::Black)]
+        .into_iter()
+        .cycle()
+        .take(12)
+        .map(|preference| User {
+            preference,
+            size: Size::L,
+        })
+        .collect();
+
+    let mut substitution = PreferenceWithSubstitution::new();
+    substitution.substitute(ShirtColor::Red, ShirtColor::Black);
+    let mut policies: Vec<Box<dyn GiveawayPolicy>> = vec![
+        Box::new(MostStocked),
+        Box::new(RoundRobin::default()),
+        Box::new(WeightedRandom::new(42)),
+        Box::new(substitution),
+    ];
+    for policy in &mut policies {
+        let report = giveaway::simulate(&stock, policy.as_mut(), &users);
+        println!(
+            "{}: {:.0}% satisfied, {:.0}% substituted, {} stock-outs",
+            report.policy,
+            report.satisfaction_rate() * 100.0,
+            report.substitution_rate() * 100.0,
+            report.stock_outs.len()
+        );
+    }
+
     // Parameter and return value types are annotated explicitely
     let expensive_closure = |num: u32| -> u32 {
         println!("Calculating slowly...");
@@ -33,6 +102,12 @@ pub fn closures() {
     };
     let r = expensive_closure(3);
 
+    // The closure captures nothing, so it is Copy and can be handed to a Cacher too.
+    // Only the first call with 3 calculates slowly, the second one returns the remembered value.
+    let mut cached = Cacher::new(expensive_closure, Eviction::lru(10));
+    let r = cached.value(3) + cached.value(3);
+    println!("Cache hit rate = {:.0}%", cached.stats().hit_rate() * 100.0);
+
     // Parameter and return value type inference happens at first invocation (types locked after that)
     let example_closure = |x| x;
     let s = example_closure("x");
@@ -62,6 +137,21 @@ pub fn closures() {
         .join()
         .unwrap();
     // println!("After calling closure: {:?}", taking_ownership); // Compiler error: borrow of moved value: `taking_ownership`
+
+    // Every thread gets its own clone of the handle moved in, all clones share the same stock
+    let shared = AtomicInventory::new([(Sku::new(ShirtColor::Green, Size::S), 3)]);
+    let workers: Vec<_> = (0..5)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || shared.giveaway(None, Size::S).is_ok())
+        })
+        .collect();
+    let handed_out = workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap())
+        .filter(|&got_one| got_one)
+        .count();
+    println!("5 threads got {handed_out} of 3 shirts");
 }
 
 // All iterators implement a trait named Iterator that is defined in the standard library
@@ -110,39 +200,29 @@ pub fn iterators() {
     for val in &v3 {
         println!("Got: {val}");
     }
-}
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum ShirtColor {
-    Red,
-    Blue,
-}
-
-struct Inventory {
-    shirts: Vec<ShirtColor>,
-}
 
-impl Inventory {
-    fn giveaway(&self, user_preference: Option<ShirtColor>) -> ShirtColor {
-        //user_preference.unwrap_or_else(|| -> ShirtColor {self.most_stocked()})
-        user_preference.unwrap_or_else(|| self.most_stocked())
+    // IterExt adds more adaptors to every iterator, they are just as lazy
+    let readings = [3, 3, 4, 4, 4, 2, 5, 5];
+    let changes: Vec<_> = readings.iter().dedup_by_key(|&&x| x).collect();
+    let averages: Vec<f64> = readings
+        .iter()
+        .windows(3)
+        .map(|window| window.into_iter().sum::<i32>() as f64 / 3.0)
+        .collect();
+    println!("Changes: {changes:?}, moving averages: {averages:?}");
+    for (value, run) in readings.into_iter().group_by(|&x| x) {
+        println!("{value} repeated {} times", run.len());
     }
 
-    fn most_stocked(&self) -> ShirtColor {
-        let mut num_red = 0;
-        let mut num_blue = 0;
-
-        for color in &self.shirts {
-            match color {
-                ShirtColor::Red => num_red += 1,
-                ShirtColor::Blue => num_blue += 1,
-            }
-        }
-
-        if num_red > num_blue {
-            ShirtColor::Red
-        } else {
-            ShirtColor::Blue
-        }
-    }
+    // The same adaptors as stages on worker threads, for inputs too large to process on one thread
+    let lines = ["hello world", "wonderful world"].map(String::from);
+    let shouted: Vec<String> = Pipeline::from_iter(lines, Options::default())
+        .flat_map("split", 2, |line: String| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .map("shout", 2, |word| word.to_uppercase())
+        .collect();
+    println!("Shouted: {shouted:?}");
 }
@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// Memoization: a Cacher wraps a closure and remembers its results, so calling it again with the same
+// argument returns the remembered value instead of calculating it again.
+//
+// Without limits the cache grows forever. With a capacity the least recently used value is evicted when a
+// new one doesn't fit, with a time to live values are calculated again once they are too old. Time comes
+// from a Clock, so tests can move it forward instead of sleeping.
+//
+// SyncCacher can be shared between threads. When several threads ask for the same missing key at once,
+// only the first calculates the value and the others wait for its result.
+
+pub trait Clock: Send + Sync {
+    // Time passed since some fixed point, only differences matter
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// Only moves when told to. Clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().expect("Clock lock poisoned") += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().expect("Clock lock poisoned")
+    }
+}
+
+// No limits by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Eviction {
+    // Most values kept at once, the least recently used goes first
+    pub capacity: Option<usize>,
+    // How long a value stays valid after it was calculated
+    pub time_to_live: Option<Duration>,
+}
+
+impl Eviction {
+    pub fn lru(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            time_to_live: None,
+        }
+    }
+
+    pub fn ttl(time_to_live: Duration) -> Self {
+        Self {
+            capacity: None,
+            time_to_live: Some(time_to_live),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    // Values removed to make room
+    pub evictions: u64,
+    // Values that were too old when they were asked for
+    pub expirations: u64,
+    // SyncCacher only: calls that waited for another thread calculating the same value, counted as hits too
+    pub deduplicated: u64,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    calculated_at: Duration,
+    // Position in Store::recency
+    used: u64,
+}
+
+// The cached values with their bookkeeping, shared by Cacher and SyncCacher
+struct Store<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    // Keys by when they were last used, oldest first
+    recency: BTreeMap<u64, K>,
+    next_use: u64,
+    eviction: Eviction,
+    stats: Stats,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Store<K, V> {
+    fn new(eviction: Eviction) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_use: 0,
+            eviction,
+            stats: Stats::default(),
+        }
+    }
+
+    fn touch(&mut self, key: &K) -> u64 {
+        let used = self.next_use;
+        self.next_use += 1;
+        self.recency.insert(used, key.clone());
+        used
+    }
+
+    // The cached value, if there is one that hasn't expired. Counts neither hits nor misses.
+    fn get(&mut self, key: &K, now: Duration) -> Option<V> {
+        let entry = self.entries.get(key)?;
+
+        let expired = self
+            .eviction
+            .time_to_live
+            .is_some_and(|ttl| now.saturating_sub(entry.calculated_at) >= ttl);
+        if expired {
+            let entry = self.entries.remove(key)?;
+            self.recency.remove(&entry.used);
+            self.stats.expirations += 1;
+            return None;
+        }
+
+        let old = entry.used;
+        let used = self.touch(key);
+        self.recency.remove(&old);
+
+        let entry = self.entries.get_mut(key)?;
+        entry.used = used;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V, now: Duration) {
+        if let Some(old) = self.entries.remove(&key) {
+            self.recency.remove(&old.used);
+        }
+
+        if let Some(capacity) = self.eviction.capacity {
+            while self.entries.len() >= capacity {
+                let Some((_, oldest)) = self.recency.pop_first() else {
+                    break;
+                };
+                self.entries.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+            if capacity == 0 {
+                return;
+            }
+        }
+
+        let used = self.touch(&key);
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                calculated_at: now,
+                used,
+            },
+        );
+    }
+}
+
+pub struct Cacher<K, V, F>
+where
+    F: Fn(K) -> V,
+{
+    calculation: F,
+    store: Store<K, V>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<K, V, F> Cacher<K, V, F>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(K) -> V,
+{
+    pub fn new(calculation: F, eviction: Eviction) -> Self {
+        Self::with_clock(calculation, eviction, Arc::new(SystemClock::default()))
+    }
+
+    pub fn with_clock(calculation: F, eviction: Eviction, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            calculation,
+            store: Store::new(eviction),
+            clock,
+        }
+    }
+
+    pub fn value(&mut self, arg: K) -> V {
+        let now = self.clock.now();
+
+        if let Some(value) = self.store.get(&arg, now) {
+            self.store.stats.hits += 1;
+            return value;
+        }
+
+        self.store.stats.misses += 1;
+        let value = (self.calculation)(arg.clone());
+        self.store.insert(arg, value.clone(), now);
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.store.stats
+    }
+}
+
+enum Slot<V> {
+    Calculating,
+    Done(V),
+    // The calculating thread panicked
+    Failed,
+}
+
+type InFlight<V> = Arc<(Mutex<Slot<V>>, Condvar)>;
+
+struct SyncState<K, V> {
+    store: Store<K, V>,
+    in_flight: HashMap<K, InFlight<V>>,
+}
+
+pub struct SyncCacher<K, V, F>
+where
+    F: Fn(K) -> V,
+{
+    calculation: F,
+    state: Mutex<SyncState<K, V>>,
+    clock: Arc<dyn Clock>,
+}
+
+// Marks the calculation as failed if the calculating thread unwinds before finishing it,
+// so waiting threads don't wait forever
+struct Finish<'a, K: Hash + Eq, V> {
+    key: Option<K>,
+    slot: InFlight<V>,
+    state: &'a Mutex<SyncState<K, V>>,
+}
+
+impl<K: Hash + Eq, V> Drop for Finish<'_, K, V> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            if let Ok(mut state) = self.state.lock() {
+                state.in_flight.remove(&key);
+            }
+            let (slot, ready) = &*self.slot;
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Slot::Failed;
+            }
+            ready.notify_all();
+        }
+    }
+}
+
+impl<K, V, F> SyncCacher<K, V, F>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(K) -> V,
+{
+    pub fn new(calculation: F, eviction: Eviction) -> Self {
+        Self::with_clock(calculation, eviction, Arc::new(SystemClock::default()))
+    }
+
+    pub fn with_clock(calculation: F, eviction: Eviction, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            calculation,
+            state: Mutex::new(SyncState {
+                store: Store::new(eviction),
+                in_flight: HashMap::new(),
+            }),
+            clock,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SyncState<K, V>> {
+        self.state.lock().expect("Cache lock poisoned")
+    }
+
+    // The calculation runs without holding the cache lock, so values for other keys can be looked up and
+    // calculated meanwhile
+    pub fn value(&self, arg: K) -> V {
+        loop {
+            let mut state = self.lock();
+
+            if let Some(value) = state.store.get(&arg, self.clock.now()) {
+                state.store.stats.hits += 1;
+                return value;
+            }
+
+            if let Some(slot) = state.in_flight.get(&arg).cloned() {
+                state.store.stats.hits += 1;
+                state.store.stats.deduplicated += 1;
+                drop(state);
+
+                let (slot, ready) = &*slot;
+                let mut slot = slot.lock().expect("Cache slot lock poisoned");
+                while let Slot::Calculating = *slot {
+                    slot = ready.wait(slot).expect("Cache slot lock poisoned");
+                }
+                match &*slot {
+                    Slot::Done(value) => return value.clone(),
+                    // Try again, calculating it ourselves if nobody else has started meanwhile
+                    _ => continue,
+                }
+            }
+
+            state.store.stats.misses += 1;
+            let slot: InFlight<V> = Arc::new((Mutex::new(Slot::Calculating), Condvar::new()));
+            state.in_flight.insert(arg.clone(), Arc::clone(&slot));
+            drop(state);
+
+            let mut finish = Finish {
+                key: Some(arg.clone()),
+                slot,
+                state: &self.state,
+            };
+            let value = (self.calculation)(arg.clone());
+            finish.key = None;
+
+            let mut state = self.lock();
+            state.in_flight.remove(&arg);
+            state.store.insert(arg, value.clone(), self.clock.now());
+            drop(state);
+
+            let (slot, ready) = &*finish.slot;
+            *slot.lock().expect("Cache slot lock poisoned") = Slot::Done(value.clone());
+            ready.notify_all();
+            return value;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().store.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.lock().store.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn repeated_arguments_are_calculated_once() {
+        let calls = Cell::new(0);
+        let mut cacher = Cacher::new(
+            |x: u32| {
+                calls.set(calls.get() + 1);
+                x * 2
+            },
+            Eviction::default(),
+        );
+
+        assert_eq!(cacher.value(1), 2);
+        assert_eq!(cacher.value(1), 2);
+        assert_eq!(cacher.value(2), 4);
+
+        assert_eq!(calls.get(), 2);
+        assert_eq!(cacher.stats().hits, 1);
+        assert_eq!(cacher.stats().misses, 2);
+        assert!((cacher.stats().hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn least_recently_used_values_are_evicted() {
+        let mut cacher = Cacher::new(|s: &str| s.len(), Eviction::lru(2));
+
+        cacher.value("a");
+        cacher.value("bb");
+        // "a" is now more recently used than "bb"
+        cacher.value("a");
+        cacher.value("ccc");
+
+        assert_eq!(cacher.len(), 2);
+        assert_eq!(cacher.stats().evictions, 1);
+
+        cacher.value("a");
+        assert_eq!(cacher.stats().hits, 2);
+        cacher.value("bb");
+        assert_eq!(cacher.stats().misses, 4);
+    }
+
+    #[test]
+    fn values_expire_after_their_time_to_live() {
+        let clock = ManualClock::default();
+        let mut cacher = Cacher::with_clock(
+            |x: u32| x + 1,
+            Eviction::ttl(Duration::from_secs(60)),
+            Arc::new(clock.clone()),
+        );
+
+        cacher.value(1);
+        clock.advance(Duration::from_secs(59));
+        cacher.value(1);
+        assert_eq!(cacher.stats().hits, 1);
+
+        clock.advance(Duration::from_secs(1));
+        cacher.value(1);
+        assert_eq!(cacher.stats().expirations, 1);
+        assert_eq!(cacher.stats().misses, 2);
+    }
+
+    #[test]
+    fn concurrent_calls_for_one_key_calculate_once() {
+        let calls = AtomicUsize::new(0);
+        let cacher = SyncCacher::new(
+            |x: u64| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                // Keeps the calculation running while the other threads ask for the same key
+                thread::sleep(Duration::from_millis(50));
+                x * x
+            },
+            Eviction::default(),
+        );
+
+        thread::scope(|scope| {
+            for _ in 0..16 {
+                scope.spawn(|| assert_eq!(cacher.value(12), 144));
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let stats = cacher.stats();
+        assert_eq!((stats.misses, stats.hits), (1, 15));
+        assert!(stats.deduplicated > 0);
+    }
+
+    #[test]
+    fn a_panicking_calculation_lets_waiters_retry() {
+        let calls = AtomicUsize::new(0);
+        let cacher = SyncCacher::new(
+            |x: u64| {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    thread::sleep(Duration::from_millis(50));
+                    panic!("first call fails");
+                }
+                x
+            },
+            Eviction::default(),
+        );
+
+        thread::scope(|scope| {
+            let failing = scope.spawn(|| cacher.value(7));
+            // Once the calculation has started, the second call has to wait for it
+            while calls.load(Ordering::SeqCst) == 0 {
+                thread::yield_now();
+            }
+            let waiting = scope.spawn(|| cacher.value(7));
+
+            assert!(failing.join().is_err());
+            assert_eq!(waiting.join().unwrap(), 7);
+        });
+    }
+}
@@ -0,0 +1,395 @@
+use crate::iterators_and_closures::inventory::{
+    Inventory, InventoryError, ShirtColor, Size, Sku, Timestamp,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+// Ways to decide which shirt a user gets in a giveaway, and a simulation to compare them.
+//
+// Every policy gives users their preferred color while it lasts. They differ in what users without a
+// preference get, and in what happens when the preferred color is gone.
+
+pub trait GiveawayPolicy {
+    fn name(&self) -> &str;
+
+    // The color to give away in `size`, None when the user should leave empty-handed.
+    // Takes &mut self so policies can keep state like a position or a random number generator.
+    fn choose(
+        &mut self,
+        inventory: &Inventory,
+        preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Option<ShirtColor>;
+}
+
+fn in_stock(inventory: &Inventory, color: ShirtColor, size: Size) -> bool {
+    inventory.available(Sku::new(color, size)) > 0
+}
+
+// The preference if it's in stock, otherwise the result of `fallback`
+fn preferred_or_else(
+    inventory: &Inventory,
+    preference: Option<ShirtColor>,
+    size: Size,
+    fallback: impl FnOnce() -> Option<ShirtColor>,
+) -> Option<ShirtColor> {
+    preference
+        .filter(|&color| in_stock(inventory, color, size))
+        .or_else(fallback)
+}
+
+impl Inventory {
+    // Like giveaway, but the policy picks the color
+    pub fn giveaway_with(
+        &mut self,
+        policy: &mut dyn GiveawayPolicy,
+        preference: Option<ShirtColor>,
+        size: Size,
+        now: Timestamp,
+    ) -> Result<Sku, InventoryError> {
+        self.expire_reservations(now);
+
+        let color = policy
+            .choose(self, preference, size)
+            .ok_or(InventoryError::OutOfStock(size))?;
+
+        self.giveaway(Some(color), size, now)
+    }
+}
+
+// What Inventory::giveaway does for users without a preference, and for everybody else when their color
+// is gone. Popular colors run out, the others pile up.
+pub struct MostStocked;
+
+impl GiveawayPolicy for MostStocked {
+    fn name(&self) -> &str {
+        "most stocked"
+    }
+
+    fn choose(
+        &mut self,
+        inventory: &Inventory,
+        preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Option<ShirtColor> {
+        preferred_or_else(inventory, preference, size, || inventory.most_stocked(size))
+    }
+}
+
+// Takes turns through ShirtColor::ALL, skipping colors that are out of stock
+#[derive(Default)]
+pub struct RoundRobin {
+    next: usize,
+}
+
+impl GiveawayPolicy for RoundRobin {
+    fn name(&self) -> &str {
+        "round robin"
+    }
+
+    fn choose(
+        &mut self,
+        inventory: &Inventory,
+        preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Option<ShirtColor> {
+        preferred_or_else(inventory, preference, size, || {
+            let colors = ShirtColor::ALL.len();
+
+            let offset = (0..colors).find(|offset| {
+                in_stock(
+                    inventory,
+                    ShirtColor::ALL[(self.next + offset) % colors],
+                    size,
+                )
+            })?;
+            let index = (self.next + offset) % colors;
+
+            self.next = index + 1;
+            Some(ShirtColor::ALL[index])
+        })
+    }
+}
+
+// Picks a random color, each with a probability proportional to its available stock.
+// The same seed always gives the same choices, so simulations can be repeated.
+pub struct WeightedRandom {
+    rng: StdRng,
+}
+
+impl WeightedRandom {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl GiveawayPolicy for WeightedRandom {
+    fn name(&self) -> &str {
+        "weighted random"
+    }
+
+    fn choose(
+        &mut self,
+        inventory: &Inventory,
+        preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Option<ShirtColor> {
+        preferred_or_else(inventory, preference, size, || {
+            let weights = ShirtColor::ALL.map(|color| inventory.available(Sku::new(color, size)));
+            let total: u32 = weights.iter().sum();
+            if total == 0 {
+                return None;
+            }
+
+            let mut roll = self.rng.gen_range(0..total);
+            for (color, weight) in ShirtColor::ALL.into_iter().zip(weights) {
+                if roll < weight {
+                    return Some(color);
+                }
+                roll -= weight;
+            }
+            None
+        })
+    }
+}
+
+// When the preferred color is gone, the first substitute in stock, e.g. Black for Blue.
+// Users whose color has no substitute in stock get nothing rather than a shirt they don't want.
+// Users without a preference get the most stocked color.
+#[derive(Default)]
+pub struct PreferenceWithSubstitution {
+    substitutes: HashMap<ShirtColor, Vec<ShirtColor>>,
+}
+
+impl PreferenceWithSubstitution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Substitutes are tried in the order they were added
+    pub fn substitute(&mut self, color: ShirtColor, substitute: ShirtColor) {
+        self.substitutes.entry(color).or_default().push(substitute);
+    }
+}
+
+impl GiveawayPolicy for PreferenceWithSubstitution {
+    fn name(&self) -> &str {
+        "preference with substitution"
+    }
+
+    fn choose(
+        &mut self,
+        inventory: &Inventory,
+        preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Option<ShirtColor> {
+        let Some(color) = preference else {
+            return inventory.most_stocked(size);
+        };
+
+        std::iter::once(&color)
+            .chain(self.substitutes.get(&color).into_iter().flatten())
+            .copied()
+            .find(|&candidate| in_stock(inventory, candidate, size))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct User {
+    pub preference: Option<ShirtColor>,
+    pub size: Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // Got the preferred color, or any shirt when they had no preference
+    Satisfied(Sku),
+    // Got a shirt, but not in the preferred color
+    Substituted(Sku),
+    EmptyHanded,
+}
+
+// The moment a SKU ran out: after the giveaway to the user with this position in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockOut {
+    pub user: usize,
+    pub sku: Sku,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub policy: String,
+    pub outcomes: Vec<Outcome>,
+    pub stock_outs: Vec<StockOut>,
+}
+
+impl Report {
+    fn share(&self, matches: impl Fn(&Outcome) -> bool) -> f64 {
+        let count = self
+            .outcomes
+            .iter()
+            .filter(|outcome| matches(outcome))
+            .count();
+        count as f64 / self.outcomes.len().max(1) as f64
+    }
+
+    pub fn satisfaction_rate(&self) -> f64 {
+        self.share(|outcome| matches!(outcome, Outcome::Satisfied(_)))
+    }
+
+    pub fn substitution_rate(&self) -> f64 {
+        self.share(|outcome| matches!(outcome, Outcome::Substituted(_)))
+    }
+
+    pub fn empty_handed_rate(&self) -> f64 {
+        self.share(|outcome| matches!(outcome, Outcome::EmptyHanded))
+    }
+}
+
+// Runs the queue of users through a copy of the inventory, so every policy can start from the same stock.
+// The user's position in the queue, counted from the inventory's last movement, serves as the time of
+// their giveaway.
+pub fn simulate(inventory: &Inventory, policy: &mut dyn GiveawayPolicy, users: &[User]) -> Report {
+    let mut inventory = inventory.clone();
+    let start = inventory.last_movement();
+    let mut outcomes = Vec::new();
+    let mut stock_outs = Vec::new();
+
+    for (position, user) in users.iter().enumerate() {
+        let outcome = match inventory.giveaway_with(
+            policy,
+            user.preference,
+            user.size,
+            start + position as Timestamp,
+        ) {
+            Ok(sku) if user.preference.is_none_or(|color| color == sku.color) => {
+                Outcome::Satisfied(sku)
+            }
+            Ok(sku) => Outcome::Substituted(sku),
+            Err(_) => Outcome::EmptyHanded,
+        };
+
+        if let Outcome::Satisfied(sku) | Outcome::Substituted(sku) = outcome {
+            if inventory.available(sku) == 0 {
+                stock_outs.push(StockOut {
+                    user: position,
+                    sku,
+                });
+            }
+        }
+        outcomes.push(outcome);
+    }
+
+    Report {
+        policy: String::from(policy.name()),
+        outcomes,
+        stock_outs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inventory(stock: &[(ShirtColor, u32)]) -> Inventory {
+        let mut inventory = Inventory::new(0);
+        for &(color, quantity) in stock {
+            inventory
+                .receive(Sku::new(color, Size::M), quantity, 0)
+                .unwrap();
+        }
+        inventory
+    }
+
+    fn users(preferences: &[Option<ShirtColor>]) -> Vec<User> {
+        preferences
+            .iter()
+            .map(|&preference| User {
+                preference,
+                size: Size::M,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_robin_takes_turns_and_skips_empty_colors() {
+        let inventory = inventory(&[(ShirtColor::Red, 1), (ShirtColor::Green, 5)]);
+        let mut policy = RoundRobin::default();
+
+        let report = simulate(&inventory, &mut policy, &users(&[None, None, None]));
+
+        let colors: Vec<Option<ShirtColor>> = report
+            .outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                Outcome::Satisfied(sku) => Some(sku.color),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                Some(ShirtColor::Red),
+                Some(ShirtColor::Green),
+                Some(ShirtColor::Green)
+            ]
+        );
+        assert_eq!(
+            report.stock_outs,
+            vec![StockOut {
+                user: 0,
+                sku: Sku::new(ShirtColor::Red, Size::M)
+            }]
+        );
+    }
+
+    #[test]
+    fn weighted_random_is_repeatable_and_follows_stock() {
+        let inventory = inventory(&[(ShirtColor::Red, 90), (ShirtColor::Blue, 10)]);
+        let queue = users(&[None; 50]);
+
+        let first = simulate(&inventory, &mut WeightedRandom::new(7), &queue);
+        let second = simulate(&inventory, &mut WeightedRandom::new(7), &queue);
+        assert_eq!(first, second);
+
+        let red = first
+            .outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome, Outcome::Satisfied(sku) if sku.color == ShirtColor::Red))
+            .count();
+        assert!(red > 30, "{red}");
+        assert_eq!(first.satisfaction_rate(), 1.0);
+    }
+
+    #[test]
+    fn substitutes_replace_sold_out_colors() {
+        let inventory = inventory(&[
+            (ShirtColor::Blue, 1),
+            (ShirtColor::Black, 1),
+            (ShirtColor::Red, 5),
+        ]);
+        let mut policy = PreferenceWithSubstitution::new();
+        policy.substitute(ShirtColor::Blue, ShirtColor::Black);
+        let queue = users(&[Some(ShirtColor::Blue); 3]);
+
+        let report = simulate(&inventory, &mut policy, &queue);
+
+        assert_eq!(
+            report.outcomes,
+            vec![
+                Outcome::Satisfied(Sku::new(ShirtColor::Blue, Size::M)),
+                Outcome::Substituted(Sku::new(ShirtColor::Black, Size::M)),
+                Outcome::EmptyHanded,
+            ]
+        );
+        assert_eq!(report.stock_outs.len(), 2);
+
+        // Most stocked hands out Red instead of sending the third user away
+        let report = simulate(&inventory, &mut MostStocked, &queue);
+        assert_eq!(report.empty_handed_rate(), 0.0);
+        assert!((report.substitution_rate() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
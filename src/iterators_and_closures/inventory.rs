@@ -0,0 +1,709 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+// Stock of shirts by color and size.
+//
+// Every change of the stock on hand goes through receive, ship or giveaway and is recorded in the audit log.
+// Reservations hold back stock for a while without moving it: reserved shirts can't be shipped or given
+// away, and when a reservation expires the shirts are available again. Operations take the current time,
+// so expiry doesn't depend on the system clock. Time can't go backwards: a movement older than the last
+// one is rejected, so the log is always in time order.
+
+// Seconds since the Unix epoch
+pub type Timestamp = u64;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum ShirtColor {
+    Red,
+    Blue,
+    Green,
+    Black,
+    White,
+}
+
+impl ShirtColor {
+    pub const ALL: [ShirtColor; 5] = [
+        ShirtColor::Red,
+        ShirtColor::Blue,
+        ShirtColor::Green,
+        ShirtColor::Black,
+        ShirtColor::White,
+    ];
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum Size {
+    S,
+    M,
+    L,
+    XL,
+}
+
+impl Size {
+    pub const ALL: [Size; 4] = [Size::S, Size::M, Size::L, Size::XL];
+}
+
+// Stock keeping unit, one kind of shirt
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Sku {
+    pub color: ShirtColor,
+    pub size: Size,
+}
+
+impl Sku {
+    pub fn new(color: ShirtColor, size: Size) -> Self {
+        Self { color, size }
+    }
+}
+
+// e.g. "Red/XL"
+impl Display for Sku {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}/{:?}", self.color, self.size)
+    }
+}
+
+impl FromStr for Sku {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid SKU '{s}'");
+        let (color, size) = s.split_once('/').ok_or_else(invalid)?;
+
+        Ok(Sku {
+            color: ShirtColor::ALL
+                .into_iter()
+                .find(|candidate| format!("{candidate:?}") == color)
+                .ok_or_else(invalid)?,
+            size: Size::ALL
+                .into_iter()
+                .find(|candidate| format!("{candidate:?}") == size)
+                .ok_or_else(invalid)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryError {
+    ZeroQuantity,
+    InsufficientStock {
+        sku: Sku,
+        requested: u32,
+        available: u32,
+    },
+    // A giveaway found no shirt of the size in any acceptable color
+    OutOfStock(Size),
+    // Unknown, already fulfilled, cancelled or expired
+    NoSuchReservation(ReservationId),
+    OutOfOrder {
+        at: Timestamp,
+        last: Timestamp,
+    },
+    // More stock than fits in a u32
+    TooMuchStock(Sku),
+    // A reservation has to expire after it is made
+    AlreadyExpired {
+        expires_at: Timestamp,
+        now: Timestamp,
+    },
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InventoryError::ZeroQuantity => write!(f, "Quantity must be at least 1"),
+            InventoryError::InsufficientStock {
+                sku,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Requested {requested} of {sku}, only {available} available"
+            ),
+            InventoryError::OutOfStock(size) => write!(f, "No shirts of size {size:?} left"),
+            InventoryError::NoSuchReservation(id) => write!(f, "No active reservation {id}"),
+            InventoryError::OutOfOrder { at, last } => {
+                write!(f, "Movement at {at} is older than the last one at {last}")
+            }
+            InventoryError::TooMuchStock(sku) => write!(f, "Too much stock of {sku}"),
+            InventoryError::AlreadyExpired { expires_at, now } => write!(
+                f,
+                "Reservation expiring at {expires_at} would already be expired at {now}"
+            ),
+        }
+    }
+}
+
+impl Error for InventoryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementKind {
+    Received,
+    Shipped,
+    GivenAway,
+    // Stock was counted and set to the counted number, the quantity is how many more were counted
+    AdjustedUp,
+    // Like AdjustedUp, the quantity is how many fewer were counted
+    AdjustedDown,
+}
+
+// One entry of the audit log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movement {
+    pub at: Timestamp,
+    pub sku: Sku,
+    pub kind: MovementKind,
+    pub quantity: u32,
+    // Stock on hand after the movement
+    pub on_hand: u32,
+}
+
+pub type ReservationId = u64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub sku: Sku,
+    pub quantity: u32,
+    pub expires_at: Timestamp,
+}
+
+// Raised when the available stock of a SKU drops to its threshold or below
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowStockAlert {
+    pub at: Timestamp,
+    pub sku: Sku,
+    pub available: u32,
+    pub threshold: u32,
+}
+
+#[derive(Clone)]
+pub struct Inventory {
+    on_hand: BTreeMap<Sku, u32>,
+    reservations: BTreeMap<ReservationId, Reservation>,
+    next_reservation: ReservationId,
+    // Alert when the available stock drops to this, unless a SKU has its own threshold
+    low_stock_threshold: u32,
+    thresholds: BTreeMap<Sku, u32>,
+    alerts: Vec<LowStockAlert>,
+    log: Vec<Movement>,
+    // Time of the last movement, newer ones can't be older
+    last_movement: Timestamp,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Inventory {
+    pub fn new(low_stock_threshold: u32) -> Self {
+        Self {
+            on_hand: BTreeMap::new(),
+            reservations: BTreeMap::new(),
+            next_reservation: 1,
+            low_stock_threshold,
+            thresholds: BTreeMap::new(),
+            alerts: Vec::new(),
+            log: Vec::new(),
+            last_movement: 0,
+        }
+    }
+
+    // Starts with the stock on hand at `at`, without entries in the log, e.g. when loading a snapshot
+    pub fn from_stock(
+        stock: impl IntoIterator<Item = (Sku, u32)>,
+        at: Timestamp,
+        low_stock_threshold: u32,
+    ) -> Self {
+        Self {
+            on_hand: stock.into_iter().collect(),
+            last_movement: at,
+            ..Self::new(low_stock_threshold)
+        }
+    }
+
+    pub fn set_low_stock_threshold(&mut self, sku: Sku, threshold: u32) {
+        self.thresholds.insert(sku, threshold);
+    }
+
+    pub fn on_hand(&self, sku: Sku) -> u32 {
+        self.on_hand.get(&sku).copied().unwrap_or(0)
+    }
+
+    pub fn reserved(&self, sku: Sku) -> u32 {
+        self.reservations
+            .values()
+            .filter(|reservation| reservation.sku == sku)
+            .map(|reservation| reservation.quantity)
+            .sum()
+    }
+
+    // On hand and not reserved. An adjustment can leave less on hand than is reserved.
+    pub fn available(&self, sku: Sku) -> u32 {
+        self.on_hand(sku).saturating_sub(self.reserved(sku))
+    }
+
+    // SKUs that have ever been stocked, with their stock on hand
+    pub fn stock(&self) -> impl Iterator<Item = (Sku, u32)> + '_ {
+        self.on_hand.iter().map(|(&sku, &quantity)| (sku, quantity))
+    }
+
+    pub fn log(&self) -> &[Movement] {
+        &self.log
+    }
+
+    // Time of the last movement, or the time the stock was loaded at
+    pub fn last_movement(&self) -> Timestamp {
+        self.last_movement
+    }
+
+    pub fn alerts(&self) -> &[LowStockAlert] {
+        &self.alerts
+    }
+
+    // Hands out the alerts raised since the last call
+    pub fn take_alerts(&mut self) -> Vec<LowStockAlert> {
+        std::mem::take(&mut self.alerts)
+    }
+
+    pub fn reservations(&self) -> impl Iterator<Item = &Reservation> {
+        self.reservations.values()
+    }
+
+    pub fn receive(
+        &mut self,
+        sku: Sku,
+        quantity: u32,
+        now: Timestamp,
+    ) -> Result<(), InventoryError> {
+        if quantity == 0 {
+            return Err(InventoryError::ZeroQuantity);
+        }
+        self.check_order(now)?;
+        let on_hand = self
+            .on_hand(sku)
+            .checked_add(quantity)
+            .ok_or(InventoryError::TooMuchStock(sku))?;
+        self.expire_reservations(now);
+
+        self.on_hand.insert(sku, on_hand);
+        self.record(now, sku, MovementKind::Received, quantity);
+        Ok(())
+    }
+
+    pub fn ship(&mut self, sku: Sku, quantity: u32, now: Timestamp) -> Result<(), InventoryError> {
+        self.expire_reservations(now);
+        self.take(sku, quantity, MovementKind::Shipped, now)
+    }
+
+    // Sets the stock on hand to the result of a stock count, e.g. after shirts were damaged or found.
+    // A count that matches the stock on hand changes nothing and isn't logged.
+    pub fn adjust(&mut self, sku: Sku, counted: u32, now: Timestamp) -> Result<(), InventoryError> {
+        self.check_order(now)?;
+        self.expire_reservations(now);
+
+        let before = self.on_hand(sku);
+        if counted == before {
+            return Ok(());
+        }
+        self.on_hand.insert(sku, counted);
+
+        if counted > before {
+            self.record(now, sku, MovementKind::AdjustedUp, counted - before);
+        } else {
+            self.record(now, sku, MovementKind::AdjustedDown, before - counted);
+            self.check_low_stock(sku, now);
+        }
+        Ok(())
+    }
+
+    // The user's preferred color, or the most available color when they don't mind.
+    // Fails when that color is gone in the user's size, instead of giving away a shirt that isn't there.
+    pub fn giveaway(
+        &mut self,
+        user_preference: Option<ShirtColor>,
+        size: Size,
+        now: Timestamp,
+    ) -> Result<Sku, InventoryError> {
+        self.expire_reservations(now);
+
+        // The closure only runs when there is no preference
+        let color = user_preference
+            .or_else(|| self.most_stocked(size))
+            .ok_or(InventoryError::OutOfStock(size))?;
+        let sku = Sku::new(color, size);
+
+        self.take(sku, 1, MovementKind::GivenAway, now)?;
+        Ok(sku)
+    }
+
+    // The color with the most available shirts of the size, the first in ShirtColor::ALL on ties.
+    // None when the size is sold out.
+    pub fn most_stocked(&self, size: Size) -> Option<ShirtColor> {
+        let mut best = None;
+
+        for color in ShirtColor::ALL {
+            let available = self.available(Sku::new(color, size));
+            if available > 0 && best.is_none_or(|(_, most)| available > most) {
+                best = Some((color, available));
+            }
+        }
+
+        best.map(|(color, _)| color)
+    }
+
+    // Holds back stock until `expires_at`, which has to be after `now`
+    pub fn reserve(
+        &mut self,
+        sku: Sku,
+        quantity: u32,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> Result<ReservationId, InventoryError> {
+        if expires_at <= now {
+            return Err(InventoryError::AlreadyExpired { expires_at, now });
+        }
+        self.expire_reservations(now);
+        self.check_available(sku, quantity)?;
+
+        let id = self.next_reservation;
+        self.next_reservation += 1;
+        self.reservations.insert(
+            id,
+            Reservation {
+                id,
+                sku,
+                quantity,
+                expires_at,
+            },
+        );
+        self.check_low_stock(sku, now);
+        Ok(id)
+    }
+
+    // Ships the reserved shirts. When a stock count found fewer shirts than were reserved, the reservation
+    // stays, so it can be fulfilled once more shirts come in.
+    pub fn fulfill(&mut self, id: ReservationId, now: Timestamp) -> Result<(), InventoryError> {
+        self.expire_reservations(now);
+        let reservation = self
+            .reservations
+            .remove(&id)
+            .ok_or(InventoryError::NoSuchReservation(id))?;
+
+        let shipped = self.take(
+            reservation.sku,
+            reservation.quantity,
+            MovementKind::Shipped,
+            now,
+        );
+        if shipped.is_err() {
+            self.reservations.insert(id, reservation);
+        }
+        shipped
+    }
+
+    pub fn cancel(
+        &mut self,
+        id: ReservationId,
+        now: Timestamp,
+    ) -> Result<Reservation, InventoryError> {
+        self.expire_reservations(now);
+        self.reservations
+            .remove(&id)
+            .ok_or(InventoryError::NoSuchReservation(id))
+    }
+
+    // Releases the reservations that have expired by `now` and returns them.
+    // Every other operation calls this first.
+    pub fn expire_reservations(&mut self, now: Timestamp) -> Vec<Reservation> {
+        let expired: Vec<ReservationId> = self
+            .reservations
+            .values()
+            .filter(|reservation| reservation.expires_at <= now)
+            .map(|reservation| reservation.id)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|id| self.reservations.remove(&id))
+            .collect()
+    }
+
+    fn check_order(&self, now: Timestamp) -> Result<(), InventoryError> {
+        if now < self.last_movement {
+            return Err(InventoryError::OutOfOrder {
+                at: now,
+                last: self.last_movement,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_available(&self, sku: Sku, quantity: u32) -> Result<(), InventoryError> {
+        if quantity == 0 {
+            return Err(InventoryError::ZeroQuantity);
+        }
+
+        let available = self.available(sku);
+        if quantity > available {
+            return Err(InventoryError::InsufficientStock {
+                sku,
+                requested: quantity,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    // Removes available stock, reservations must have been released before
+    fn take(
+        &mut self,
+        sku: Sku,
+        quantity: u32,
+        kind: MovementKind,
+        now: Timestamp,
+    ) -> Result<(), InventoryError> {
+        self.check_order(now)?;
+        self.check_available(sku, quantity)?;
+
+        *self.on_hand.entry(sku).or_insert(0) -= quantity;
+        self.record(now, sku, kind, quantity);
+        self.check_low_stock(sku, now);
+        Ok(())
+    }
+
+    fn record(&mut self, at: Timestamp, sku: Sku, kind: MovementKind, quantity: u32) {
+        self.last_movement = at;
+        self.log.push(Movement {
+            at,
+            sku,
+            kind,
+            quantity,
+            on_hand: self.on_hand(sku),
+        });
+    }
+
+    // Called after the available stock went down
+    fn check_low_stock(&mut self, sku: Sku, now: Timestamp) {
+        let threshold = self
+            .thresholds
+            .get(&sku)
+            .copied()
+            .unwrap_or(self.low_stock_threshold);
+        let available = self.available(sku);
+
+        if available <= threshold {
+            self.alerts.push(LowStockAlert {
+                at: now,
+                sku,
+                available,
+                threshold,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED_M: Sku = Sku {
+        color: ShirtColor::Red,
+        size: Size::M,
+    };
+    const BLUE_M: Sku = Sku {
+        color: ShirtColor::Blue,
+        size: Size::M,
+    };
+
+    fn stocked() -> Inventory {
+        let mut inventory = Inventory::new(1);
+        inventory.receive(RED_M, 2, 0).unwrap();
+        inventory.receive(BLUE_M, 3, 0).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn giveaways_decrement_stock_and_fail_when_empty() {
+        let mut inventory = stocked();
+
+        assert_eq!(inventory.giveaway(None, Size::M, 1), Ok(BLUE_M));
+        // Red and Blue both have 2 left, Red comes first
+        assert_eq!(inventory.giveaway(None, Size::M, 2), Ok(RED_M));
+        assert_eq!(
+            inventory.giveaway(Some(ShirtColor::Red), Size::M, 3),
+            Ok(RED_M)
+        );
+        assert_eq!(
+            inventory.giveaway(Some(ShirtColor::Red), Size::M, 4),
+            Err(InventoryError::InsufficientStock {
+                sku: RED_M,
+                requested: 1,
+                available: 0
+            })
+        );
+        assert_eq!(
+            inventory.giveaway(None, Size::XL, 5),
+            Err(InventoryError::OutOfStock(Size::XL))
+        );
+
+        assert_eq!(inventory.on_hand(RED_M), 0);
+        assert_eq!(inventory.on_hand(BLUE_M), 2);
+    }
+
+    #[test]
+    fn reservations_hold_stock_until_they_expire() {
+        let mut inventory = stocked();
+
+        let id = inventory.reserve(BLUE_M, 2, 100, 10).unwrap();
+        assert_eq!(inventory.available(BLUE_M), 1);
+        assert!(inventory.ship(BLUE_M, 2, 20).is_err());
+
+        // Expired at 100, so the stock is back
+        inventory.ship(BLUE_M, 2, 100).unwrap();
+        assert_eq!(
+            inventory.fulfill(id, 101),
+            Err(InventoryError::NoSuchReservation(id))
+        );
+
+        let id = inventory.reserve(BLUE_M, 1, 300, 200).unwrap();
+        inventory.fulfill(id, 250).unwrap();
+        assert_eq!(inventory.on_hand(BLUE_M), 0);
+        assert_eq!(inventory.reservations().count(), 0);
+    }
+
+    #[test]
+    fn failed_fulfillment_keeps_the_reservation() {
+        let mut inventory = stocked();
+        let id = inventory.reserve(BLUE_M, 2, 100, 10).unwrap();
+
+        // Only one shirt was found when counting
+        inventory.adjust(BLUE_M, 1, 20).unwrap();
+        assert_eq!(
+            inventory.fulfill(id, 30),
+            Err(InventoryError::InsufficientStock {
+                sku: BLUE_M,
+                requested: 2,
+                available: 1
+            })
+        );
+        assert_eq!(inventory.reservations().count(), 1);
+        assert_eq!(inventory.on_hand(BLUE_M), 1);
+
+        inventory.receive(BLUE_M, 1, 40).unwrap();
+        inventory.fulfill(id, 50).unwrap();
+        assert_eq!(inventory.on_hand(BLUE_M), 0);
+        assert_eq!(inventory.reservations().count(), 0);
+    }
+
+    #[test]
+    fn low_stock_raises_alerts() {
+        let mut inventory = stocked();
+        inventory.set_low_stock_threshold(BLUE_M, 2);
+
+        inventory.ship(BLUE_M, 1, 1).unwrap();
+        inventory.ship(RED_M, 1, 2).unwrap();
+
+        let alerts = inventory.take_alerts();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            (alerts[0].sku, alerts[0].available, alerts[0].threshold),
+            (BLUE_M, 2, 2)
+        );
+        assert_eq!(
+            (alerts[1].sku, alerts[1].available, alerts[1].threshold),
+            (RED_M, 1, 1)
+        );
+        assert!(inventory.alerts().is_empty());
+    }
+
+    #[test]
+    fn every_movement_is_logged() {
+        let mut inventory = stocked();
+        inventory
+            .giveaway(Some(ShirtColor::Blue), Size::M, 5)
+            .unwrap();
+        let id = inventory.reserve(RED_M, 1, 50, 6).unwrap();
+        inventory.fulfill(id, 7).unwrap();
+        inventory.adjust(BLUE_M, 5, 8).unwrap();
+
+        let log: Vec<(Timestamp, Sku, MovementKind, u32, u32)> = inventory
+            .log()
+            .iter()
+            .map(|m| (m.at, m.sku, m.kind, m.quantity, m.on_hand))
+            .collect();
+
+        assert_eq!(
+            log,
+            vec![
+                (0, RED_M, MovementKind::Received, 2, 2),
+                (0, BLUE_M, MovementKind::Received, 3, 3),
+                (5, BLUE_M, MovementKind::GivenAway, 1, 2),
+                (7, RED_M, MovementKind::Shipped, 1, 1),
+                (8, BLUE_M, MovementKind::AdjustedUp, 3, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn movements_cant_go_back_in_time() {
+        let mut inventory = stocked();
+        inventory.ship(RED_M, 1, 100).unwrap();
+
+        let too_old = Err(InventoryError::OutOfOrder { at: 50, last: 100 });
+        assert_eq!(inventory.receive(RED_M, 1, 50), too_old);
+        assert_eq!(inventory.ship(BLUE_M, 1, 50), too_old);
+        assert_eq!(inventory.adjust(BLUE_M, 1, 50), too_old);
+        assert_eq!(inventory.on_hand(RED_M), 1);
+        assert_eq!(inventory.on_hand(BLUE_M), 3);
+        assert_eq!(inventory.log().len(), 3);
+
+        // The same time is fine
+        inventory.receive(RED_M, 1, 100).unwrap();
+    }
+
+    #[test]
+    fn adjustments_log_their_direction() {
+        let mut inventory = stocked();
+        inventory.adjust(RED_M, 5, 1).unwrap();
+        inventory.adjust(RED_M, 1, 2).unwrap();
+        // Counted what was on hand anyway
+        inventory.adjust(RED_M, 1, 3).unwrap();
+
+        let adjustments: Vec<(MovementKind, u32, u32)> = inventory.log()[2..]
+            .iter()
+            .map(|m| (m.kind, m.quantity, m.on_hand))
+            .collect();
+        assert_eq!(
+            adjustments,
+            vec![
+                (MovementKind::AdjustedUp, 3, 5),
+                (MovementKind::AdjustedDown, 4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_stock_and_reservations_are_rejected() {
+        let mut inventory = stocked();
+
+        assert_eq!(
+            inventory.receive(RED_M, u32::MAX, 1),
+            Err(InventoryError::TooMuchStock(RED_M))
+        );
+        assert_eq!(inventory.on_hand(RED_M), 2);
+
+        assert_eq!(
+            inventory.reserve(RED_M, 1, 10, 10),
+            Err(InventoryError::AlreadyExpired {
+                expires_at: 10,
+                now: 10
+            })
+        );
+        assert_eq!(inventory.reservations().count(), 0);
+    }
+}
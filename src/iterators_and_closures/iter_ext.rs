@@ -0,0 +1,610 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::iter::Peekable;
+
+// Iterator adaptors that the standard library doesn't have.
+//
+// Like map and filter they are lazy: they only pull items from the underlying iterator when their own next()
+// is called. Every adaptor gives a size_hint that is correct for the items that are actually left, and exact
+// when the underlying iterator's is and the number of items doesn't depend on their values.
+
+pub trait IterExt: Iterator {
+    // Vecs of exactly `size` items, the items left over at the end are in remainder()
+    fn chunks_exact(self, size: usize) -> ChunksExact<Self>
+    where
+        Self: Sized,
+    {
+        assert!(size > 0, "Chunk size must be at least 1");
+        ChunksExact {
+            iter: self,
+            size,
+            remainder: Vec::new(),
+        }
+    }
+
+    // Overlapping windows of `size` consecutive items, like slice::windows but for any iterator
+    fn windows(self, size: usize) -> Windows<Self>
+    where
+        Self: Sized,
+        Self::Item: Clone,
+    {
+        assert!(size > 0, "Window size must be at least 1");
+        Windows {
+            iter: self,
+            size,
+            window: VecDeque::with_capacity(size),
+        }
+    }
+
+    // Alternates between the two iterators, the rest of the longer one comes at the end
+    fn interleave<J>(self, other: J) -> Interleave<Self, J::IntoIter>
+    where
+        Self: Sized,
+        J: IntoIterator<Item = Self::Item>,
+    {
+        Interleave {
+            a: self,
+            b: other.into_iter(),
+            a_next: true,
+        }
+    }
+
+    // Leaves out items with the same key as the item before them
+    fn dedup_by_key<K, F>(self, key: F) -> DedupByKey<Self, K, F>
+    where
+        Self: Sized,
+        K: PartialEq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        DedupByKey {
+            iter: self,
+            key,
+            last: None,
+        }
+    }
+
+    // Runs of consecutive items with the same key, as (key, items).
+    // Items with the same key that aren't next to each other end up in different groups.
+    fn group_by<K, F>(self, key: F) -> GroupBy<Self, K, F>
+    where
+        Self: Sized,
+        K: PartialEq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        GroupBy {
+            iter: self,
+            key,
+            next: None,
+        }
+    }
+
+    // Calls `f` with the underlying iterator, which takes as many items as it likes and returns one result.
+    // Ends when `f` returns None.
+    fn batching<B, F>(self, f: F) -> Batching<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&mut Self) -> Option<B>,
+    {
+        Batching { iter: self, f }
+    }
+
+    // Like take_while, but the first item that doesn't match stays in the iterator
+    fn peeking_take_while<P>(&mut self, predicate: P) -> PeekingTakeWhile<'_, Self, P>
+    where
+        Self: PeekingNext + Sized,
+        P: FnMut(&Self::Item) -> bool,
+    {
+        PeekingTakeWhile {
+            iter: self,
+            predicate,
+        }
+    }
+
+    // Only the first of equal items
+    fn unique(self) -> Unique<Self>
+    where
+        Self: Sized,
+        Self::Item: Clone + Eq + Hash,
+    {
+        Unique {
+            iter: self,
+            seen: HashSet::new(),
+        }
+    }
+
+    // Every pair of an item of this iterator and an item of `other`, e.g. (1, 'a'), (1, 'b'), (2, 'a'), ...
+    // `other` is cloned for every item of this iterator.
+    fn cartesian_product<J>(self, other: J) -> CartesianProduct<Self, J::IntoIter>
+    where
+        Self: Sized,
+        Self::Item: Clone,
+        J: IntoIterator,
+        J::IntoIter: Clone,
+    {
+        let other = other.into_iter();
+        CartesianProduct {
+            a: self,
+            current: None,
+            b: other.clone(),
+            b_original: other,
+        }
+    }
+}
+
+impl<I: Iterator> IterExt for I {}
+
+// Iterators that can hand out their next item only if it is accepted
+pub trait PeekingNext: Iterator {
+    fn peeking_next(&mut self, accept: impl FnOnce(&Self::Item) -> bool) -> Option<Self::Item>;
+}
+
+impl<I: Iterator> PeekingNext for Peekable<I> {
+    fn peeking_next(&mut self, accept: impl FnOnce(&Self::Item) -> bool) -> Option<Self::Item> {
+        self.next_if(accept)
+    }
+}
+
+fn add_hints(a: (usize, Option<usize>), b: (usize, Option<usize>)) -> (usize, Option<usize>) {
+    let upper = match (a.1, b.1) {
+        (Some(a), Some(b)) => a.checked_add(b),
+        _ => None,
+    };
+    (a.0.saturating_add(b.0), upper)
+}
+
+#[derive(Clone)]
+pub struct ChunksExact<I: Iterator> {
+    iter: I,
+    size: usize,
+    remainder: Vec<I::Item>,
+}
+
+impl<I: Iterator> ChunksExact<I> {
+    // The items that didn't fill a whole chunk, only known once the iterator is used up
+    pub fn remainder(&self) -> &[I::Item] {
+        &self.remainder
+    }
+}
+
+impl<I: Iterator> Iterator for ChunksExact<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<I::Item> = self.iter.by_ref().take(self.size).collect();
+
+        if chunk.len() == self.size {
+            Some(chunk)
+        } else {
+            // Calls after the end mustn't overwrite the remainder with nothing
+            if !chunk.is_empty() {
+                self.remainder = chunk;
+            }
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (lower / self.size, upper.map(|upper| upper / self.size))
+    }
+}
+
+#[derive(Clone)]
+pub struct Windows<I: Iterator> {
+    iter: I,
+    size: usize,
+    // Between calls to next() at most size - 1 items, the start of the next window
+    window: VecDeque<I::Item>,
+}
+
+impl<I> Iterator for Windows<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+
+        let window = self.window.iter().cloned().collect();
+        self.window.pop_front();
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let buffered = self.window.len();
+        // The first window needs `size` items, every item after that adds one
+        let windows = |items: usize| items.saturating_sub(self.size - 1);
+
+        (
+            windows(lower.saturating_add(buffered)),
+            upper
+                .and_then(|upper| upper.checked_add(buffered))
+                .map(windows),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct Interleave<I, J> {
+    a: I,
+    b: J,
+    a_next: bool,
+}
+
+impl<I, J> Iterator for Interleave<I, J>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let from_a = self.a_next;
+        self.a_next = !self.a_next;
+
+        if from_a {
+            self.a.next().or_else(|| self.b.next())
+        } else {
+            self.b.next().or_else(|| self.a.next())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        add_hints(self.a.size_hint(), self.b.size_hint())
+    }
+}
+
+#[derive(Clone)]
+pub struct DedupByKey<I, K, F> {
+    iter: I,
+    key: F,
+    // Key of the last item handed out
+    last: Option<K>,
+}
+
+impl<I, K, F> Iterator for DedupByKey<I, K, F>
+where
+    I: Iterator,
+    K: PartialEq,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in self.iter.by_ref() {
+            let key = (self.key)(&item);
+
+            if self.last.as_ref() != Some(&key) {
+                self.last = Some(key);
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    // All remaining items could be duplicates of the last one handed out, unless nothing was handed out yet
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let lower = if self.last.is_none() { lower.min(1) } else { 0 };
+        (lower, upper)
+    }
+}
+
+#[derive(Clone)]
+pub struct GroupBy<I: Iterator, K, F> {
+    iter: I,
+    key: F,
+    // First item of the next group, taken from `iter` while finishing the group before
+    next: Option<(K, I::Item)>,
+}
+
+impl<I, K, F> Iterator for GroupBy<I, K, F>
+where
+    I: Iterator,
+    K: PartialEq,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = (K, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.next.take() {
+            Some(next) => next,
+            None => {
+                let item = self.iter.next()?;
+                ((self.key)(&item), item)
+            }
+        };
+
+        let mut group = vec![first];
+        for item in self.iter.by_ref() {
+            let item_key = (self.key)(&item);
+
+            if item_key != key {
+                self.next = Some((item_key, item));
+                break;
+            }
+            group.push(item);
+        }
+
+        Some((key, group))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = usize::from(self.next.is_some());
+        let (lower, upper) = add_hints(self.iter.size_hint(), (buffered, Some(buffered)));
+        (lower.min(1), upper)
+    }
+}
+
+#[derive(Clone)]
+pub struct Batching<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, B, F> Iterator for Batching<I, F>
+where
+    I: Iterator,
+    F: FnMut(&mut I) -> Option<B>,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.f)(&mut self.iter)
+    }
+
+    // `f` may return results without taking any items, so there's no upper bound
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+pub struct PeekingTakeWhile<'a, I, P> {
+    iter: &'a mut I,
+    predicate: P,
+}
+
+impl<I, P> Iterator for PeekingTakeWhile<'_, I, P>
+where
+    I: PeekingNext,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.peeking_next(&mut self.predicate)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+#[derive(Clone)]
+pub struct Unique<I: Iterator> {
+    iter: I,
+    seen: HashSet<I::Item>,
+}
+
+impl<I> Iterator for Unique<I>
+where
+    I: Iterator,
+    I::Item: Clone + Eq + Hash,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .by_ref()
+            .find(|item| self.seen.insert(item.clone()))
+    }
+
+    // The remaining items could all have been seen already, unless nothing was seen yet
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let lower = if self.seen.is_empty() {
+            lower.min(1)
+        } else {
+            0
+        };
+        (lower, upper)
+    }
+}
+
+#[derive(Clone)]
+pub struct CartesianProduct<I: Iterator, J> {
+    a: I,
+    // The item of `a` paired with the rest of `b`
+    current: Option<I::Item>,
+    b: J,
+    b_original: J,
+}
+
+impl<I, J> Iterator for CartesianProduct<I, J>
+where
+    I: Iterator,
+    I::Item: Clone,
+    J: Iterator + Clone,
+{
+    type Item = (I::Item, J::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(a) = &self.current {
+                if let Some(b) = self.b.next() {
+                    return Some((a.clone(), b));
+                }
+            }
+
+            self.current = Some(self.a.next()?);
+            self.b = self.b_original.clone();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_lower, a_upper) = self.a.size_hint();
+        let (b_lower, b_upper) = self.b_original.size_hint();
+        // What's left of `b` for the current item of `a`
+        let rest = if self.current.is_some() {
+            self.b.size_hint()
+        } else {
+            (0, Some(0))
+        };
+
+        let lower = a_lower.saturating_mul(b_lower).saturating_add(rest.0);
+        let upper = match (a_upper, b_upper, rest.1) {
+            (Some(a), Some(b), Some(rest)) => {
+                a.checked_mul(b).and_then(|all| all.checked_add(rest))
+            }
+            _ => None,
+        };
+        (lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks before every item that the size_hint contains the number of items that are really left,
+    // and that it is exact when `exact` is set
+    fn check_size_hints<I: Iterator + Clone>(mut iter: I, exact: bool) {
+        loop {
+            let left = iter.clone().count();
+            let (lower, upper) = iter.size_hint();
+
+            assert!(lower <= left, "lower bound {lower} > {left} items left");
+            assert!(
+                upper.is_none_or(|upper| left <= upper),
+                "upper bound {upper:?} < {left} items left"
+            );
+            if exact {
+                assert_eq!((lower, upper), (left, Some(left)));
+            }
+
+            if iter.next().is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_and_windows() {
+        let mut chunks = (1..=7).chunks_exact(3);
+        assert_eq!(chunks.next(), Some(vec![1, 2, 3]));
+        assert_eq!(chunks.next(), Some(vec![4, 5, 6]));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(chunks.remainder(), &[7]);
+
+        let windows: Vec<Vec<char>> = "abcd".chars().windows(2).collect();
+        assert_eq!(
+            windows,
+            vec![vec!['a', 'b'], vec!['b', 'c'], vec!['c', 'd']]
+        );
+        assert_eq!((1..3).windows(3).next(), None);
+
+        for length in 0..8 {
+            check_size_hints((0..length).chunks_exact(3), true);
+            check_size_hints((0..length).windows(3), true);
+            // filter doesn't know how many items it lets through
+            check_size_hints((0..length).filter(|x| x % 2 == 0).windows(2), false);
+        }
+    }
+
+    #[test]
+    fn interleave_keeps_the_rest_of_the_longer_iterator() {
+        let mixed: Vec<i32> = [1, 3, 5, 7, 9].into_iter().interleave([2, 4]).collect();
+        assert_eq!(mixed, vec![1, 2, 3, 4, 5, 7, 9]);
+
+        check_size_hints((0..3).interleave(10..17), true);
+        check_size_hints((0..0).interleave(10..12), true);
+    }
+
+    #[test]
+    fn consecutive_keys() {
+        let words = ["apple", "avocado", "banana", "blueberry", "apricot"];
+
+        let deduped: Vec<&str> = words
+            .into_iter()
+            .dedup_by_key(|word| word.chars().next())
+            .collect();
+        assert_eq!(deduped, vec!["apple", "banana", "apricot"]);
+
+        let groups: Vec<(char, Vec<&str>)> = words
+            .into_iter()
+            .group_by(|word| word.chars().next().unwrap())
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ('a', vec!["apple", "avocado"]),
+                ('b', vec!["banana", "blueberry"]),
+                ('a', vec!["apricot"]),
+            ]
+        );
+
+        check_size_hints(words.into_iter().dedup_by_key(|word| word.len()), false);
+        check_size_hints([1, 1, 1].into_iter().dedup_by_key(|&x| x), false);
+        check_size_hints(
+            words.into_iter().group_by(|word| word.chars().next()),
+            false,
+        );
+        check_size_hints([0; 0].into_iter().group_by(|&x: &i32| x), true);
+    }
+
+    #[test]
+    fn batching_and_peeking_take_while() {
+        // Pairs of numbers, a lone number at the end is dropped
+        let pairs: Vec<(i32, i32)> = (1..=5)
+            .batching(|iter| Some((iter.next()?, iter.next()?)))
+            .collect();
+        assert_eq!(pairs, vec![(1, 2), (3, 4)]);
+
+        let mut chars = "123abc".chars().peekable();
+        let digits: String = chars.peeking_take_while(|c| c.is_ascii_digit()).collect();
+        let rest: String = chars.collect();
+        assert_eq!((digits.as_str(), rest.as_str()), ("123", "abc"));
+
+        let mut numbers = (1..10).peekable();
+        let small = numbers.peeking_take_while(|&x| x < 4);
+        assert_eq!(small.size_hint(), (0, Some(9)));
+        assert_eq!(small.count(), 3);
+        assert_eq!(numbers.next(), Some(4));
+    }
+
+    #[test]
+    fn unique_and_cartesian_product() {
+        let unique: Vec<i32> = [3, 1, 3, 2, 1].into_iter().unique().collect();
+        assert_eq!(unique, vec![3, 1, 2]);
+        check_size_hints([3, 1, 3, 2, 1].into_iter().unique(), false);
+
+        let pairs: Vec<(i32, char)> = (1..=2).cartesian_product(['a', 'b']).collect();
+        assert_eq!(pairs, vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+        assert_eq!((1..=2).cartesian_product(0..0).next(), None);
+
+        check_size_hints((0..3).cartesian_product(0..4), true);
+        check_size_hints((0..0).cartesian_product(0..4), true);
+        check_size_hints(
+            (0..5).cartesian_product((0..4).filter(|x| x % 2 == 0)),
+            false,
+        );
+    }
+
+    #[test]
+    fn adaptors_are_lazy() {
+        let mut pulled = 0;
+        let mut chunks = (0..100)
+            .inspect(|_| pulled += 1)
+            .chunks_exact(4)
+            .cartesian_product(0..2);
+
+        chunks.next();
+        drop(chunks);
+        assert_eq!(pulled, 4);
+    }
+}
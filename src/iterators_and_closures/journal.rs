@@ -0,0 +1,595 @@
+use crate::iterators_and_closures::inventory::{Inventory, Movement, MovementKind, Sku, Timestamp};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Keeps an Inventory on disk as the list of its stock movements (event sourcing).
+//
+// The journal directory holds two UTF-8 text files. events.log has one event per line:
+//
+//     inventory-events 1
+//     <timestamp> <received|shipped|given-away|adjusted-up|adjusted-down> <sku> <quantity> <on hand after>
+//
+// and snapshot the stock on hand after the first <count> events:
+//
+//     inventory-snapshot 1
+//     events <count> <timestamp of the last one>
+//     stock <sku> <on hand>
+//
+// The events are the inventory's audit log, appended as it grows. The current stock is rebuilt by replaying
+// them, and every event's stock on hand is checked along the way. A snapshot stores the stock after the
+// first <count> events, so loading only replays the events after it. Replaying the events up to a
+// timestamp gives the stock at that time: event timestamps never decrease, so those events come first.
+// Reservations and alerts aren't persisted, they start out empty after loading.
+
+const EVENTS_HEADER: &str = "inventory-events 1";
+const SNAPSHOT_HEADER: &str = "inventory-snapshot 1";
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    // Unreadable, or the events don't add up to the recorded stock
+    Malformed {
+        file: &'static str,
+        line: usize,
+        message: String,
+    },
+    // Events have to be saved in the order they happened
+    OutOfOrder {
+        at: Timestamp,
+        last: Timestamp,
+    },
+    // The inventory given to save() logged fewer movements than were saved from the one open() returned
+    WrongInventory {
+        logged: usize,
+        saved: usize,
+    },
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Io(error) => write!(f, "Problem accessing the journal: {error}"),
+            JournalError::Malformed {
+                file,
+                line,
+                message,
+            } => write!(f, "Malformed {file} at line {line}: {message}"),
+            JournalError::OutOfOrder { at, last } => {
+                write!(
+                    f,
+                    "Event at {at} is older than the last saved event at {last}"
+                )
+            }
+            JournalError::WrongInventory { logged, saved } => write!(
+                f,
+                "The inventory logged {logged} movements, but {saved} were already saved from it"
+            ),
+        }
+    }
+}
+
+impl Error for JournalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JournalError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for JournalError {
+    fn from(error: io::Error) -> Self {
+        JournalError::Io(error)
+    }
+}
+
+fn malformed(file: &'static str, line: usize, message: &str) -> JournalError {
+    JournalError::Malformed {
+        file,
+        line,
+        message: String::from(message),
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(
+    text: Option<&str>,
+    file: &'static str,
+    line: usize,
+    what: &str,
+) -> Result<T, JournalError> {
+    text.and_then(|text| text.parse().ok())
+        .ok_or_else(|| malformed(file, line, &format!("Expected {what}")))
+}
+
+fn kind_name(kind: MovementKind) -> &'static str {
+    match kind {
+        MovementKind::Received => "received",
+        MovementKind::Shipped => "shipped",
+        MovementKind::GivenAway => "given-away",
+        MovementKind::AdjustedUp => "adjusted-up",
+        MovementKind::AdjustedDown => "adjusted-down",
+    }
+}
+
+fn parse_kind(name: &str) -> Option<MovementKind> {
+    [
+        MovementKind::Received,
+        MovementKind::Shipped,
+        MovementKind::GivenAway,
+        MovementKind::AdjustedUp,
+        MovementKind::AdjustedDown,
+    ]
+    .into_iter()
+    .find(|&kind| kind_name(kind) == name)
+}
+
+pub fn write_event(mut writer: impl Write, event: &Movement) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{} {} {} {} {}",
+        event.at,
+        kind_name(event.kind),
+        event.sku,
+        event.quantity,
+        event.on_hand
+    )
+}
+
+// All events, with the line each one is on
+pub fn read_events(reader: impl Read) -> Result<Vec<(usize, Movement)>, JournalError> {
+    const FILE: &str = "events.log";
+    let mut lines = BufReader::new(reader).lines().zip(1..);
+
+    let header = match lines.next() {
+        Some((line, _)) => line?,
+        None => String::new(),
+    };
+    if header != EVENTS_HEADER {
+        return Err(malformed(
+            FILE,
+            1,
+            "Not an event log or an unsupported version",
+        ));
+    }
+
+    let mut events = Vec::new();
+    for (line, line_number) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+
+        let at = parse_field(fields.next(), FILE, line_number, "a timestamp")?;
+        let kind = fields
+            .next()
+            .and_then(parse_kind)
+            .ok_or_else(|| malformed(FILE, line_number, "Expected a kind of movement"))?;
+        let sku = parse_field(fields.next(), FILE, line_number, "a SKU like Red/M")?;
+        let quantity = parse_field(fields.next(), FILE, line_number, "a quantity")?;
+        let on_hand = parse_field(fields.next(), FILE, line_number, "the stock on hand")?;
+
+        if fields.next().is_some() {
+            return Err(malformed(FILE, line_number, "Unexpected field"));
+        }
+        events.push((
+            line_number,
+            Movement {
+                at,
+                sku,
+                kind,
+                quantity,
+                on_hand,
+            },
+        ));
+    }
+
+    Ok(events)
+}
+
+// Repeats a movement on the inventory, which logs it again.
+// Fails when the movement isn't possible or leaves a different stock than recorded.
+pub fn apply(inventory: &mut Inventory, event: &Movement) -> Result<(), String> {
+    let result = match event.kind {
+        MovementKind::Received => inventory.receive(event.sku, event.quantity, event.at),
+        MovementKind::Shipped => inventory.ship(event.sku, event.quantity, event.at),
+        MovementKind::GivenAway if event.quantity != 1 => {
+            return Err(String::from("Shirts are given away one at a time"));
+        }
+        MovementKind::GivenAway => inventory
+            .giveaway(Some(event.sku.color), event.sku.size, event.at)
+            .map(|_| ()),
+        MovementKind::AdjustedUp | MovementKind::AdjustedDown => {
+            inventory.adjust(event.sku, event.on_hand, event.at)
+        }
+    };
+    result.map_err(|error| error.to_string())?;
+
+    let on_hand = inventory.on_hand(event.sku);
+    if on_hand != event.on_hand {
+        return Err(format!(
+            "Stock of {} is {on_hand} after replaying, the event says {}",
+            event.sku, event.on_hand
+        ));
+    }
+    Ok(())
+}
+
+// Applies the events with timestamps up to `until` to the inventory, all of them for None
+fn replay(
+    inventory: &mut Inventory,
+    events: &[(usize, Movement)],
+    until: Option<Timestamp>,
+) -> Result<(), JournalError> {
+    let events = events
+        .iter()
+        .take_while(|(_, event)| until.is_none_or(|until| event.at <= until));
+
+    for (line, event) in events {
+        apply(inventory, event).map_err(|message| malformed("events.log", *line, &message))?;
+    }
+    // Alerts are for live changes, not for history
+    inventory.take_alerts();
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    // Number of events the snapshot includes
+    pub events: usize,
+    // Timestamp of the last of those events
+    pub at: Timestamp,
+    pub stock: Vec<(Sku, u32)>,
+}
+
+impl Snapshot {
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{SNAPSHOT_HEADER}")?;
+        writeln!(writer, "events {} {}", self.events, self.at)?;
+
+        for (sku, on_hand) in &self.stock {
+            writeln!(writer, "stock {sku} {on_hand}")?;
+        }
+        writer.flush()
+    }
+
+    pub fn read_from(reader: impl Read) -> Result<Snapshot, JournalError> {
+        const FILE: &str = "snapshot";
+        let mut lines = BufReader::new(reader).lines().zip(1..);
+
+        let mut line = |expected: &str| -> Result<String, JournalError> {
+            match lines.next() {
+                Some((line, _)) => Ok(line?),
+                None => Err(malformed(FILE, 1, &format!("Expected {expected}"))),
+            }
+        };
+
+        if line("a header")? != SNAPSHOT_HEADER {
+            return Err(malformed(
+                FILE,
+                1,
+                "Not a snapshot or an unsupported version",
+            ));
+        }
+
+        let counts = line("the number of events")?;
+        let mut fields = counts.split_whitespace();
+        if fields.next() != Some("events") {
+            return Err(malformed(FILE, 2, "Expected the number of events"));
+        }
+        let events = parse_field(fields.next(), FILE, 2, "the number of events")?;
+        let at = parse_field(fields.next(), FILE, 2, "a timestamp")?;
+
+        let mut stock = Vec::new();
+        for (line, line_number) in lines {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+
+            if fields.next() != Some("stock") {
+                return Err(malformed(
+                    FILE,
+                    line_number,
+                    "Expected stock <sku> <on hand>",
+                ));
+            }
+            let sku = parse_field(fields.next(), FILE, line_number, "a SKU like Red/M")?;
+            let on_hand = parse_field(fields.next(), FILE, line_number, "the stock on hand")?;
+            stock.push((sku, on_hand));
+        }
+
+        Ok(Snapshot { events, at, stock })
+    }
+}
+
+pub struct Journal {
+    events_path: PathBuf,
+    snapshot_path: PathBuf,
+    // Events saved in events.log
+    saved: usize,
+    last_at: Timestamp,
+    // Events in events.log that aren't in the log of the inventory returned by open().
+    // That log starts after the snapshot it was loaded from.
+    before_log: usize,
+}
+
+impl Journal {
+    // Opens the journal in `dir`, creating it if needed, and returns it with the current inventory
+    pub fn open(dir: impl AsRef<Path>) -> Result<(Journal, Inventory), JournalError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let mut journal = Journal {
+            events_path: dir.join("events.log"),
+            snapshot_path: dir.join("snapshot"),
+            saved: 0,
+            last_at: 0,
+            before_log: 0,
+        };
+
+        if !journal.events_path.exists() {
+            let mut file = File::create(&journal.events_path)?;
+            writeln!(file, "{EVENTS_HEADER}")?;
+        }
+
+        let events = journal.events()?;
+        let snapshot = journal.snapshot()?;
+        let (mut inventory, skip) = match snapshot {
+            Some(snapshot) if snapshot.events <= events.len() => (
+                Inventory::from_stock(snapshot.stock, snapshot.at, 0),
+                snapshot.events,
+            ),
+            Some(snapshot) => {
+                return Err(malformed(
+                    "snapshot",
+                    2,
+                    &format!(
+                        "Includes {} events, but there are only {}",
+                        snapshot.events,
+                        events.len()
+                    ),
+                ))
+            }
+            None => (Inventory::default(), 0),
+        };
+        replay(&mut inventory, &events[skip..], None)?;
+
+        journal.saved = events.len();
+        journal.last_at = events.last().map_or(0, |(_, event)| event.at);
+        journal.before_log = skip;
+        Ok((journal, inventory))
+    }
+
+    fn events(&self) -> Result<Vec<(usize, Movement)>, JournalError> {
+        read_events(File::open(&self.events_path)?)
+    }
+
+    fn snapshot(&self) -> Result<Option<Snapshot>, JournalError> {
+        match File::open(&self.snapshot_path) {
+            Ok(file) => Ok(Some(Snapshot::read_from(file)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    // Appends the movements of the inventory that haven't been saved yet and returns how many there were.
+    // The inventory has to be the one returned by open(). Either all of the movements are saved or,
+    // when saving fails, none of them: the file is cut back to where it was.
+    pub fn save(&mut self, inventory: &Inventory) -> Result<usize, JournalError> {
+        let saved = self.saved - self.before_log;
+        let new = inventory
+            .log()
+            .get(saved..)
+            .ok_or(JournalError::WrongInventory {
+                logged: inventory.log().len(),
+                saved,
+            })?;
+
+        let mut last_at = self.last_at;
+        for event in new {
+            if event.at < last_at {
+                return Err(JournalError::OutOfOrder {
+                    at: event.at,
+                    last: last_at,
+                });
+            }
+            last_at = event.at;
+        }
+
+        let mut lines = Vec::new();
+        for event in new {
+            write_event(&mut lines, event)?;
+        }
+
+        let mut file = OpenOptions::new().append(true).open(&self.events_path)?;
+        let length = file.metadata()?.len();
+        if let Err(error) = file.write_all(&lines).and_then(|()| file.flush()) {
+            // Otherwise the next save would write these events a second time
+            let _ = file.set_len(length);
+            return Err(error.into());
+        }
+
+        self.saved += new.len();
+        self.last_at = last_at;
+        Ok(new.len())
+    }
+
+    // Saves the inventory and a snapshot of its stock, so the next open() starts from here.
+    // Written to a temporary file first, so a crash never leaves a half written snapshot behind.
+    pub fn save_snapshot(&mut self, inventory: &Inventory) -> Result<(), JournalError> {
+        self.save(inventory)?;
+
+        let snapshot = Snapshot {
+            events: self.saved,
+            at: self.last_at,
+            stock: inventory.stock().collect(),
+        };
+        let temporary = self.snapshot_path.with_extension("tmp");
+        snapshot.write_to(File::create(&temporary)?)?;
+        fs::rename(&temporary, &self.snapshot_path)?;
+        Ok(())
+    }
+
+    // The inventory as it was at `at`, rebuilt from the saved events.
+    // Starts from the snapshot when it isn't newer than `at`.
+    pub fn inventory_at(&self, at: Timestamp) -> Result<Inventory, JournalError> {
+        let events = self.events()?;
+
+        let (mut inventory, skip) = match self.snapshot()? {
+            Some(snapshot) if snapshot.at <= at && snapshot.events <= events.len() => (
+                Inventory::from_stock(snapshot.stock, snapshot.at, 0),
+                snapshot.events,
+            ),
+            _ => (Inventory::default(), 0),
+        };
+
+        replay(&mut inventory, &events[skip..], Some(at))?;
+        Ok(inventory)
+    }
+
+    // e.g. the stock of Red/M at timestamp T
+    pub fn stock_at(&self, sku: Sku, at: Timestamp) -> Result<u32, JournalError> {
+        Ok(self.inventory_at(at)?.on_hand(sku))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iterators_and_closures::inventory::{ShirtColor, Size};
+
+    const RED_M: Sku = Sku {
+        color: ShirtColor::Red,
+        size: Size::M,
+    };
+    const BLUE_L: Sku = Sku {
+        color: ShirtColor::Blue,
+        size: Size::L,
+    };
+
+    fn temporary_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("journal-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn recorded_log_replays_to_the_expected_stock() {
+        let events = read_events(include_str!("journal/recorded.log").as_bytes()).unwrap();
+        let mut inventory = Inventory::default();
+
+        replay(&mut inventory, &events, None).unwrap();
+
+        assert_eq!(
+            inventory.stock().collect::<Vec<_>>(),
+            vec![
+                (RED_M, 7),
+                (Sku::new(ShirtColor::Red, Size::XL), 0),
+                (BLUE_L, 9),
+            ]
+        );
+        assert_eq!(inventory.log().len(), events.len());
+
+        let mut inventory = Inventory::default();
+        replay(&mut inventory, &events, Some(1_700_000_300)).unwrap();
+        assert_eq!(inventory.on_hand(RED_M), 9);
+    }
+
+    #[test]
+    fn replay_checks_the_recorded_stock() {
+        let events = read_events(
+            "inventory-events 1\n\
+             10 received Red/M 5 5\n\
+             20 shipped Red/M 2 4\n"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        match replay(&mut Inventory::default(), &events, None) {
+            Err(JournalError::Malformed { line, message, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(
+                    message,
+                    "Stock of Red/M is 3 after replaying, the event says 4"
+                );
+            }
+            other => panic!("{other:?}"),
+        }
+
+        let overselling =
+            read_events("inventory-events 1\n10 shipped Red/M 1 0\n".as_bytes()).unwrap();
+        assert!(replay(&mut Inventory::default(), &overselling, None).is_err());
+
+        assert!(matches!(
+            read_events("inventory-events 1\n10 stolen Red/M 1 0\n".as_bytes()),
+            Err(JournalError::Malformed { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn journals_survive_reopening_and_answer_point_in_time_queries() {
+        let dir = temporary_dir("reopen");
+
+        let (mut journal, mut inventory) = Journal::open(&dir).unwrap();
+        inventory.receive(RED_M, 10, 100).unwrap();
+        inventory
+            .giveaway(Some(ShirtColor::Red), Size::M, 200)
+            .unwrap();
+        assert_eq!(journal.save(&inventory).unwrap(), 2);
+        journal.save_snapshot(&inventory).unwrap();
+
+        inventory.receive(BLUE_L, 4, 300).unwrap();
+        inventory.adjust(RED_M, 5, 400).unwrap();
+        journal.save(&inventory).unwrap();
+
+        // Starts from the snapshot and replays the two newer events
+        let (mut journal, mut reopened) = Journal::open(&dir).unwrap();
+        assert_eq!(
+            reopened.stock().collect::<Vec<_>>(),
+            inventory.stock().collect::<Vec<_>>()
+        );
+        assert_eq!(reopened.log().len(), 2);
+
+        reopened.ship(BLUE_L, 1, 500).unwrap();
+        assert_eq!(journal.save(&reopened).unwrap(), 1);
+
+        assert_eq!(journal.stock_at(RED_M, 50).unwrap(), 0);
+        assert_eq!(journal.stock_at(RED_M, 250).unwrap(), 9);
+        assert_eq!(journal.stock_at(RED_M, 400).unwrap(), 5);
+        assert_eq!(journal.stock_at(BLUE_L, 450).unwrap(), 4);
+        assert_eq!(journal.stock_at(BLUE_L, 500).unwrap(), 3);
+
+        // The inventory doesn't take the old movement, so the journal never sees it
+        assert!(reopened.receive(BLUE_L, 1, 10).is_err());
+        assert_eq!(journal.save(&reopened).unwrap(), 0);
+
+        // Not the inventory open() returned, its movements are older than the saved ones
+        let mut other = Inventory::default();
+        for at in 1..=4 {
+            other.receive(BLUE_L, 1, at).unwrap();
+        }
+        assert!(matches!(
+            journal.save(&other),
+            Err(JournalError::OutOfOrder { at: 4, last: 500 })
+        ));
+
+        // Rejecting them didn't write anything, the right inventory still saves
+        reopened.receive(BLUE_L, 1, 600).unwrap();
+        assert_eq!(journal.save(&reopened).unwrap(), 1);
+        assert_eq!(journal.stock_at(BLUE_L, 600).unwrap(), 4);
+
+        // Not the inventory open() returned, it doesn't have the movements that were saved
+        assert!(matches!(
+            journal.save(&Inventory::default()),
+            Err(JournalError::WrongInventory {
+                logged: 0,
+                saved: 4
+            })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,594 @@
+use std::collections::BTreeMap;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Iterator-like processing where every stage runs on its own worker threads.
+//
+// Stages are connected by bounded channels: when a stage is slower than the one before it, the channel
+// fills up and the stage before it blocks until there is room again (back-pressure), so a huge input is
+// never read into memory at once. Every item is numbered by the source. With ordered output each stage
+// puts the results of its workers back into that order, which costs a buffer for results that finished
+// early. That buffer is bounded too: workers wait before starting on an item that is more than `capacity`
+// items ahead of the one the stage waits for. Without ordering items come out as soon as they are done.
+//
+// Stages stop when a CancelToken is cancelled, and dropping the output makes every stage stop too, since
+// their sends fail once nobody is receiving anymore.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    // Items each channel holds before the stage in front of it has to wait
+    pub capacity: usize,
+    pub ordered: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            ordered: true,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+struct StageMetrics {
+    name: String,
+    items_in: AtomicU64,
+    items_out: AtomicU64,
+    // Summed over all workers of the stage
+    busy_nanos: AtomicU64,
+}
+
+impl StageMetrics {
+    fn new(name: &str) -> Arc<Self> {
+        Arc::new(Self {
+            name: String::from(name),
+            items_in: AtomicU64::new(0),
+            items_out: AtomicU64::new(0),
+            busy_nanos: AtomicU64::new(0),
+        })
+    }
+
+    fn add_busy(&self, since: Instant) {
+        let nanos = since.elapsed().as_nanos().min(u128::from(u64::MAX)) as u64;
+        self.busy_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStats {
+    pub name: String,
+    pub items_in: u64,
+    pub items_out: u64,
+    // Time the workers spent in the stage's closure, added up
+    pub busy: Duration,
+    // Since the pipeline started
+    pub elapsed: Duration,
+}
+
+impl StageStats {
+    // Items handed to the next stage per second since the pipeline started
+    pub fn throughput(&self) -> f64 {
+        self.items_out as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+// The sequence numbers the workers of an ordered stage may work on, from the next one the merger passes
+// on to `size` items after it
+struct Window {
+    // None once the merger stopped
+    next: Mutex<Option<u64>>,
+    moved: Condvar,
+    size: u64,
+}
+
+impl Window {
+    fn new(size: usize) -> Arc<Self> {
+        Arc::new(Self {
+            next: Mutex::new(Some(0)),
+            moved: Condvar::new(),
+            size: size.max(1) as u64,
+        })
+    }
+
+    // Waits until `seq` is inside the window.
+    // False when the merger stopped or the pipeline was cancelled, the worker should stop then.
+    fn wait_for(&self, seq: u64, cancel: &CancelToken) -> bool {
+        let mut next = self.next.lock().expect("Pipeline window lock poisoned");
+        loop {
+            match *next {
+                None => return false,
+                Some(first) if seq < first + self.size => return true,
+                Some(_) if cancel.is_cancelled() => return false,
+                // Cancelling doesn't notify, so look again every now and then
+                Some(_) => {
+                    next = self
+                        .moved
+                        .wait_timeout(next, Duration::from_millis(10))
+                        .expect("Pipeline window lock poisoned")
+                        .0;
+                }
+            }
+        }
+    }
+
+    fn move_to(&self, first: u64) {
+        let mut next = self.next.lock().expect("Pipeline window lock poisoned");
+        // Once stopped the window stays closed
+        if let Some(next) = next.as_mut() {
+            *next = first;
+        }
+        self.moved.notify_all();
+    }
+
+    fn stop(&self) {
+        *self.next.lock().expect("Pipeline window lock poisoned") = None;
+        self.moved.notify_all();
+    }
+}
+
+// Stops the pipeline when a worker panics. The merger would never get the item the worker was working on,
+// so the other workers of an ordered stage would wait for the window forever.
+// The panic itself is passed on by `finish`.
+struct StopOnPanic {
+    cancel: CancelToken,
+    window: Option<Arc<Window>>,
+}
+
+impl Drop for StopOnPanic {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.cancel.cancel();
+            if let Some(window) = &self.window {
+                window.stop();
+            }
+        }
+    }
+}
+
+pub struct Pipeline<T> {
+    output: Receiver<(u64, T)>,
+    options: Options,
+    cancel: CancelToken,
+    started: Instant,
+    metrics: Vec<Arc<StageMetrics>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    // Starts a thread that feeds the items into the pipeline
+    pub fn from_iter<I>(items: I, options: Options) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+    {
+        let (sender, output) = mpsc::sync_channel(options.capacity);
+        let cancel = CancelToken::default();
+        let metrics = StageMetrics::new("source");
+
+        let items = items.into_iter();
+        let thread = {
+            let cancel = cancel.clone();
+            let metrics = Arc::clone(&metrics);
+
+            thread::spawn(move || {
+                for (seq, item) in (0..).zip(items) {
+                    if cancel.is_cancelled() || sender.send((seq, item)).is_err() {
+                        break;
+                    }
+                    metrics.items_out.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        };
+
+        Pipeline {
+            output,
+            options,
+            cancel,
+            started: Instant::now(),
+            metrics: vec![metrics],
+            threads: vec![thread],
+        }
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    pub fn map<U, F>(self, name: &str, workers: usize, f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        self.stage(name, workers, move |item| vec![f(item)])
+    }
+
+    pub fn filter<P>(self, name: &str, workers: usize, predicate: P) -> Pipeline<T>
+    where
+        P: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.stage(name, workers, move |item| {
+            if predicate(&item) {
+                vec![item]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    pub fn flat_map<U, I, F>(self, name: &str, workers: usize, f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        I: IntoIterator<Item = U>,
+        F: Fn(T) -> I + Send + Sync + 'static,
+    {
+        self.stage(name, workers, move |item| f(item).into_iter().collect())
+    }
+
+    // Every item gives any number of results. `workers` threads take turns taking items from the stage
+    // before, a merger thread passes their results on, in the original order if the output is ordered.
+    fn stage<U, F>(mut self, name: &str, workers: usize, f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Vec<U> + Send + Sync + 'static,
+    {
+        let metrics = StageMetrics::new(name);
+        let input = Arc::new(Mutex::new(self.output));
+        let f = Arc::new(f);
+        let (results, results_output) = mpsc::sync_channel::<(u64, Vec<U>)>(self.options.capacity);
+        let ordered = self.options.ordered;
+        let window = ordered.then(|| Window::new(self.options.capacity));
+
+        for _ in 0..workers.max(1) {
+            let input = Arc::clone(&input);
+            let results = results.clone();
+            let f = Arc::clone(&f);
+            let metrics = Arc::clone(&metrics);
+            let cancel = self.cancel.clone();
+            let window = window.clone();
+
+            self.threads.push(thread::spawn(move || {
+                let _stop = StopOnPanic {
+                    cancel: cancel.clone(),
+                    window: window.clone(),
+                };
+
+                loop {
+                    // The lock is only held while waiting for the next item, not while working on it
+                    let next = input.lock().expect("Pipeline input lock poisoned").recv();
+                    let Ok((seq, item)) = next else {
+                        break;
+                    };
+                    if cancel.is_cancelled() {
+                        break;
+                    }
+                    if window.as_ref().is_some_and(|w| !w.wait_for(seq, &cancel)) {
+                        break;
+                    }
+
+                    let start = Instant::now();
+                    let output = f(item);
+                    metrics.add_busy(start);
+                    metrics.items_in.fetch_add(1, Ordering::Relaxed);
+
+                    if results.send((seq, output)).is_err() {
+                        break;
+                    }
+                }
+            }));
+        }
+        // The merger stops once all workers have dropped their senders
+        drop(results);
+
+        let (sender, output) = mpsc::sync_channel(self.options.capacity);
+        let merger_metrics = Arc::clone(&metrics);
+
+        self.threads.push(thread::spawn(move || {
+            let mut merger = Merger {
+                sender,
+                next_out: 0,
+                metrics: merger_metrics,
+            };
+            let mut pending = BTreeMap::new();
+            let mut next_in = 0;
+
+            for (seq, output) in results_output {
+                let Some(window) = &window else {
+                    if merger.send_all(output).is_err() {
+                        return;
+                    }
+                    continue;
+                };
+
+                pending.insert(seq, output);
+                while let Some(output) = pending.remove(&next_in) {
+                    next_in += 1;
+                    if merger.send_all(output).is_err() {
+                        // Workers waiting for the window would wait forever
+                        window.stop();
+                        return;
+                    }
+                }
+                window.move_to(next_in);
+            }
+        }));
+
+        self.metrics.push(metrics);
+        Pipeline {
+            output,
+            options: self.options,
+            cancel: self.cancel,
+            started: self.started,
+            metrics: self.metrics,
+            threads: self.threads,
+        }
+    }
+
+    // Vecs of `size` items, the last one can be shorter
+    pub fn batch(mut self, name: &str, size: usize) -> Pipeline<Vec<T>> {
+        let size = size.max(1);
+        let metrics = StageMetrics::new(name);
+        let (sender, output) = mpsc::sync_channel(self.options.capacity);
+
+        let input = self.output;
+        let cancel = self.cancel.clone();
+        let thread_metrics = Arc::clone(&metrics);
+
+        self.threads.push(thread::spawn(move || {
+            let mut merger = Merger {
+                sender,
+                next_out: 0,
+                metrics: thread_metrics,
+            };
+            let mut batch = Vec::with_capacity(size);
+
+            for (_, item) in input {
+                if cancel.is_cancelled() {
+                    return;
+                }
+                merger.metrics.items_in.fetch_add(1, Ordering::Relaxed);
+                batch.push(item);
+
+                if batch.len() == size {
+                    let full = std::mem::replace(&mut batch, Vec::with_capacity(size));
+                    if merger.send_all(vec![full]).is_err() {
+                        return;
+                    }
+                }
+            }
+            if !batch.is_empty() {
+                let _ = merger.send_all(vec![batch]);
+            }
+        }));
+
+        self.metrics.push(metrics);
+        Pipeline {
+            output,
+            options: self.options,
+            cancel: self.cancel,
+            started: self.started,
+            metrics: self.metrics,
+            threads: self.threads,
+        }
+    }
+}
+
+impl<T> Pipeline<T> {
+    pub fn stats(&self) -> Vec<StageStats> {
+        let elapsed = self.started.elapsed();
+
+        self.metrics
+            .iter()
+            .map(|metrics| StageStats {
+                name: metrics.name.clone(),
+                items_in: metrics.items_in.load(Ordering::Relaxed),
+                items_out: metrics.items_out.load(Ordering::Relaxed),
+                busy: Duration::from_nanos(metrics.busy_nanos.load(Ordering::Relaxed)),
+                elapsed,
+            })
+            .collect()
+    }
+
+    // Stops receiving, waits for all threads and returns the final stats.
+    // A panic in a stage is passed on here.
+    pub fn finish(self) -> Vec<StageStats> {
+        let stats = self.stats();
+        drop(self.output);
+
+        for thread in self.threads {
+            if let Err(panic) = thread.join() {
+                panic::resume_unwind(panic);
+            }
+        }
+        stats
+    }
+}
+
+// Items come out in the order of the pipeline, or as they are done if it's unordered
+impl<T> Iterator for Pipeline<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.output.recv().ok().map(|(_, item)| item)
+    }
+}
+
+// Numbers the results of a stage and sends them on
+struct Merger<U> {
+    sender: SyncSender<(u64, U)>,
+    next_out: u64,
+    metrics: Arc<StageMetrics>,
+}
+
+impl<U> Merger<U> {
+    fn send_all(&mut self, items: Vec<U>) -> Result<(), ()> {
+        for item in items {
+            self.sender.send((self.next_out, item)).map_err(|_| ())?;
+            self.next_out += 1;
+            self.metrics.items_out.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common_collections;
+    use std::collections::HashMap;
+
+    #[test]
+    fn word_count_matches_hash_maps() {
+        let lines: Vec<String> = (0..2000)
+            .map(|i| format!("hello world line {} wonderful world {}", i % 7, i % 3))
+            .collect();
+        let text = lines.join("\n");
+        let expected: HashMap<String, i32> = common_collections::word_count(&text)
+            .into_iter()
+            .map(|(word, count)| (String::from(word), count))
+            .collect();
+
+        for ordered in [true, false] {
+            let options = Options {
+                capacity: 16,
+                ordered,
+            };
+            let mut pipeline = Pipeline::from_iter(lines.clone(), options)
+                .flat_map("split", 4, |line: String| {
+                    line.split_whitespace()
+                        .map(String::from)
+                        .collect::<Vec<_>>()
+                })
+                .batch("batch", 100)
+                .map("count", 4, |words: Vec<String>| {
+                    let mut counts = HashMap::new();
+                    for word in words {
+                        *counts.entry(word).or_insert(0) += 1;
+                    }
+                    counts
+                });
+
+            let mut counts: HashMap<String, i32> = HashMap::new();
+            for partial in pipeline.by_ref() {
+                for (word, count) in partial {
+                    *counts.entry(word).or_insert(0) += count;
+                }
+            }
+            assert_eq!(counts, expected);
+
+            let stats = pipeline.finish();
+            let names: Vec<&str> = stats.iter().map(|stage| stage.name.as_str()).collect();
+            assert_eq!(names, vec!["source", "split", "batch", "count"]);
+            assert_eq!(stats[0].items_out, 2000);
+            assert_eq!((stats[1].items_in, stats[1].items_out), (2000, 14000));
+            assert_eq!(stats[2].items_out, 140);
+            assert!(stats[1].throughput() > 0.0);
+        }
+    }
+
+    #[test]
+    fn ordered_output_keeps_the_input_order() {
+        let pipeline = Pipeline::from_iter(0..200u64, Options::default())
+            .map("jitter", 8, |x| {
+                // Later items often finish first
+                thread::sleep(Duration::from_micros((200 - x) % 7 * 100));
+                x * 2
+            })
+            .filter("multiples of 3", 3, |x| x % 3 == 0);
+
+        let output: Vec<u64> = pipeline.collect();
+        let expected: Vec<u64> = (0..200).map(|x| x * 2).filter(|x| x % 3 == 0).collect();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn slow_consumers_hold_back_the_source() {
+        let options = Options {
+            capacity: 2,
+            ordered: true,
+        };
+        let pipeline = Pipeline::from_iter(0..1_000_000, options).map("identity", 1, |x| x);
+
+        thread::sleep(Duration::from_millis(50));
+
+        // Two channels, a worker and the merger can hold a few items each, but nowhere near all of them
+        let produced = pipeline.stats()[0].items_out;
+        assert!(produced < 20, "{produced}");
+        pipeline.finish();
+    }
+
+    #[test]
+    fn a_slow_item_holds_back_the_source() {
+        let options = Options {
+            capacity: 4,
+            ordered: true,
+        };
+        let pipeline = Pipeline::from_iter(0..1_000_000, options).map("slow first", 4, |x| {
+            if x == 0 {
+                thread::sleep(Duration::from_millis(200));
+            }
+            x
+        });
+
+        thread::sleep(Duration::from_millis(100));
+
+        // The other workers can get `capacity` items ahead of the slow one, then they wait
+        let produced = pipeline.stats()[0].items_out;
+        assert!(produced < 30, "{produced}");
+        pipeline.finish();
+    }
+
+    #[test]
+    fn a_panic_in_an_ordered_stage_reaches_finish() {
+        let options = Options {
+            capacity: 4,
+            ordered: true,
+        };
+        let mut pipeline = Pipeline::from_iter(0..100, options).map("boom", 4, |x| {
+            if x == 10 {
+                panic!("boom");
+            }
+            x
+        });
+
+        // Only the items before the one that panicked can come out in order
+        let passed = pipeline.by_ref().count();
+        assert!(passed <= 10, "{passed}");
+
+        let finished = panic::catch_unwind(panic::AssertUnwindSafe(|| pipeline.finish()));
+        assert!(finished.is_err());
+    }
+
+    #[test]
+    fn cancelling_stops_all_stages() {
+        let mut pipeline =
+            Pipeline::from_iter(0.., Options::default()).map("square", 2, |x: u64| x * x);
+        let cancel = pipeline.cancel_token();
+
+        assert_eq!(pipeline.next(), Some(0));
+        cancel.cancel();
+
+        // What was already in the channels still comes out, then the pipeline ends
+        let rest = pipeline.by_ref().count();
+        assert!(rest < 1000, "{rest}");
+        pipeline.finish();
+    }
+}
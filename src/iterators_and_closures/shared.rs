@@ -0,0 +1,260 @@
+use crate::iterators_and_closures::inventory::{
+    Inventory, InventoryError, ShirtColor, Size, Sku, Timestamp,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Inventory handles that many threads can give shirts away from at the same time without overselling.
+//
+// Handles are cheap to clone and all clones share the same stock, so every worker thread can get its own
+// clone moved into its closure. LockedInventory puts the whole Inventory behind a Mutex and keeps all of its
+// features. AtomicInventory only keeps a counter per SKU and never blocks: a shirt is taken by decrementing
+// the counter with compare-and-swap, which fails instead of going below zero when another thread got there
+// first.
+
+pub trait SharedInventory: Send + Sync {
+    fn giveaway(
+        &self,
+        user_preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Result<Sku, InventoryError>;
+
+    fn available(&self, sku: Sku) -> u32;
+}
+
+fn now() -> Timestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[derive(Clone)]
+pub struct LockedInventory {
+    inventory: Arc<Mutex<Inventory>>,
+}
+
+impl LockedInventory {
+    pub fn new(inventory: Inventory) -> Self {
+        Self {
+            inventory: Arc::new(Mutex::new(inventory)),
+        }
+    }
+
+    // Runs `f` while holding the lock, for everything the handle doesn't offer itself.
+    // Other threads wait until `f` returns, so keep it short.
+    pub fn with<R>(&self, f: impl FnOnce(&mut Inventory) -> R) -> R {
+        let mut inventory = self.inventory.lock().expect("Inventory lock poisoned");
+        f(&mut inventory)
+    }
+}
+
+impl SharedInventory for LockedInventory {
+    fn giveaway(
+        &self,
+        user_preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Result<Sku, InventoryError> {
+        self.with(|inventory| inventory.giveaway(user_preference, size, now()))
+    }
+
+    fn available(&self, sku: Sku) -> u32 {
+        self.with(|inventory| inventory.available(sku))
+    }
+}
+
+#[derive(Clone)]
+pub struct AtomicInventory {
+    // One counter per SKU, see index()
+    counts: Arc<Vec<AtomicU32>>,
+}
+
+fn index(sku: Sku) -> usize {
+    let color = ShirtColor::ALL
+        .iter()
+        .position(|&c| c == sku.color)
+        .unwrap();
+    let size = Size::ALL.iter().position(|&s| s == sku.size).unwrap();
+    color * Size::ALL.len() + size
+}
+
+impl AtomicInventory {
+    pub fn new(stock: impl IntoIterator<Item = (Sku, u32)>) -> Self {
+        let counts: Vec<AtomicU32> = (0..ShirtColor::ALL.len() * Size::ALL.len())
+            .map(|_| AtomicU32::new(0))
+            .collect();
+
+        for (sku, quantity) in stock {
+            counts[index(sku)].fetch_add(quantity, Ordering::Relaxed);
+        }
+        Self {
+            counts: Arc::new(counts),
+        }
+    }
+
+    // Starts with the available stock of `inventory`
+    pub fn from_inventory(inventory: &Inventory) -> Self {
+        Self::new(
+            inventory
+                .stock()
+                .map(|(sku, _)| (sku, inventory.available(sku))),
+        )
+    }
+
+    pub fn receive(&self, sku: Sku, quantity: u32) {
+        self.counts[index(sku)].fetch_add(quantity, Ordering::Relaxed);
+    }
+
+    // Every counter stands on its own and nothing else is published through them, so Relaxed is enough:
+    // read-modify-write operations on one counter always see its latest value.
+    fn take(&self, sku: Sku) -> bool {
+        self.counts[index(sku)]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
+
+impl SharedInventory for AtomicInventory {
+    fn giveaway(
+        &self,
+        user_preference: Option<ShirtColor>,
+        size: Size,
+    ) -> Result<Sku, InventoryError> {
+        if let Some(color) = user_preference {
+            let sku = Sku::new(color, size);
+
+            return if self.take(sku) {
+                Ok(sku)
+            } else {
+                Err(InventoryError::InsufficientStock {
+                    sku,
+                    requested: 1,
+                    available: 0,
+                })
+            };
+        }
+
+        // The most stocked color can be gone by the time we take it, then we look again
+        loop {
+            let sku = ShirtColor::ALL
+                .into_iter()
+                .map(|color| Sku::new(color, size))
+                .filter(|&sku| self.available(sku) > 0)
+                .max_by_key(|&sku| self.available(sku))
+                .ok_or(InventoryError::OutOfStock(size))?;
+
+            if self.take(sku) {
+                return Ok(sku);
+            }
+        }
+    }
+
+    fn available(&self, sku: Sku) -> u32 {
+        self.counts[index(sku)].load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::thread;
+
+    const STOCK: [(ShirtColor, u32); 3] = [
+        (ShirtColor::Red, 300),
+        (ShirtColor::Blue, 200),
+        (ShirtColor::Green, 100),
+    ];
+    const THREADS: usize = 64;
+    const ATTEMPTS: usize = 20;
+
+    fn stock() -> impl Iterator<Item = (Sku, u32)> {
+        STOCK
+            .into_iter()
+            .map(|(color, quantity)| (Sku::new(color, Size::M), quantity))
+    }
+
+    // More requests than shirts, from many threads at once.
+    // Returns how many shirts of every SKU were handed out.
+    fn stress(inventory: impl SharedInventory + Clone + 'static) -> HashMap<Sku, u32> {
+        let preferences = [
+            Some(ShirtColor::Red),
+            Some(ShirtColor::Blue),
+            None,
+            Some(ShirtColor::Green),
+        ];
+
+        let workers: Vec<_> = (0..THREADS)
+            .map(|worker| {
+                let inventory = inventory.clone();
+                let preference = preferences[worker % preferences.len()];
+
+                thread::spawn(move || {
+                    (0..ATTEMPTS)
+                        .filter_map(|_| inventory.giveaway(preference, Size::M).ok())
+                        .collect::<Vec<Sku>>()
+                })
+            })
+            .collect();
+
+        let mut handed_out = HashMap::new();
+        for worker in workers {
+            for sku in worker.join().unwrap() {
+                *handed_out.entry(sku).or_insert(0) += 1;
+            }
+        }
+
+        let total: u32 = handed_out.values().sum();
+        // Every color gets more requests than it has shirts, so all 600 go, but not one more
+        assert_eq!(total, 600);
+        for (sku, quantity) in stock() {
+            let given = handed_out.get(&sku).copied().unwrap_or(0);
+            assert_eq!(given + inventory.available(sku), quantity, "{sku}");
+        }
+
+        handed_out
+    }
+
+    #[test]
+    fn locked_inventory_never_oversells() {
+        let mut inventory = Inventory::new(0);
+        for (sku, quantity) in stock() {
+            inventory.receive(sku, quantity, 0).unwrap();
+        }
+        let shared = LockedInventory::new(inventory);
+
+        let handed_out = stress(shared.clone());
+
+        // Every giveaway made it into the audit log exactly once
+        let logged = shared.with(|inventory| inventory.log().len());
+        assert_eq!(
+            logged,
+            STOCK.len() + handed_out.values().sum::<u32>() as usize
+        );
+    }
+
+    #[test]
+    fn atomic_inventory_never_oversells() {
+        stress(AtomicInventory::new(stock()));
+    }
+
+    #[test]
+    fn atomic_inventory_reports_empty_colors() {
+        let inventory = AtomicInventory::new([(Sku::new(ShirtColor::Red, Size::S), 1)]);
+
+        assert_eq!(
+            inventory.giveaway(None, Size::S),
+            Ok(Sku::new(ShirtColor::Red, Size::S))
+        );
+        assert_eq!(
+            inventory.giveaway(None, Size::S),
+            Err(InventoryError::OutOfStock(Size::S))
+        );
+        assert!(inventory.giveaway(Some(ShirtColor::Red), Size::S).is_err());
+
+        inventory.receive(Sku::new(ShirtColor::Red, Size::S), 2);
+        assert_eq!(inventory.available(Sku::new(ShirtColor::Red, Size::S)), 2);
+    }
+}
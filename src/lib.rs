@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+// Lets code generated by #[derive(Summary)] refer to ::hello_rust inside this crate too
+extern crate self as hello_rust;
+
 mod common_collections;
 mod control_flow;
 mod enumerations;
@@ -18,6 +21,16 @@ use crate::iterators_and_closures as iter_clos;
 use crate::statements_and_expressions as stmt_expr;
 use crate::variables_and_datatypes as var_types;
 
+// Re-exported so benchmarks and other crates can use the parallel reductions
+pub use crate::generics::parallel;
+
+// Re-exported for the `stats` command in main.rs
+pub use crate::common_collections::text_stats;
+
+// The trait and its derive macro live in different namespaces, so both can be called Summary
+pub use crate::generics::traits::Summary;
+pub use summary_derive::Summary;
+
 pub fn try_variables_and_datatypes() {
     var_types::variables();
     var_types::data_types();
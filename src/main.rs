@@ -1,6 +1,28 @@
 use hello_rust::*;
+use std::env;
+use std::io;
+use std::process;
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Without arguments it runs through the chapters, with a command it runs that instead
+    match args.first().map(String::as_str) {
+        None => try_everything(),
+        Some("stats") => {
+            if let Err(error) = text_stats::run(&args[1..], &mut io::stdout().lock()) {
+                eprintln!("{error}");
+                process::exit(1);
+            }
+        }
+        Some(command) => {
+            eprintln!("Unknown command {command}, the only one is: stats");
+            process::exit(2);
+        }
+    }
+}
+
+fn try_everything() {
     try_variables_and_datatypes();
     try_statements_and_expressions();
     try_control_flow();
@@ -1,7 +1,9 @@
+mod matrix;
+mod point;
+pub mod rectangle;
 mod user;
-mod rectangle;
 
-use crate::structs::{rectangle::Rectangle, user::*};
+use crate::structs::{matrix::Matrix4, point::Point, rectangle::Rectangle, user::*};
 
 pub fn structs() {
     // Reminder: assignment means moving values if they don't have the Copy trait
@@ -32,6 +34,10 @@ pub fn structs() {
     let black = Color(0, 0, 0);
     let origin = Point(0, 0, 0);
 
+    // The Point tuple struct is generic and overloads the arithmetic operators
+    let moved = Matrix4::translation(1, 2, 3) * (origin + Point(1, 1, 1) * 2);
+    println!("Moved point = {:?}", moved);
+
     // The dbg! macro takes ownership of an expression and returns ownership of the value
     let scale = 2;
     let rect1 = Rectangle {
@@ -54,4 +60,3 @@ pub fn structs() {
 
 // Tuple structs have the added meaning the struct name provides but don’t have names associated with their fields
 struct Color(i32, i32, i32);
-struct Point(i32, i32, i32);
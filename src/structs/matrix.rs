@@ -0,0 +1,278 @@
+use crate::generics::numeric::{Float, Numeric};
+use crate::structs::point::Point;
+use std::ops::Mul;
+
+// Row-major 3x3 matrix for linear transforms (rotation and scaling) of 3D points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix3<T>(pub [[T; 3]; 3]);
+
+// Row-major 4x4 matrix in homogeneous coordinates, which additionally allows translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4<T>(pub [[T; 4]; 4]);
+
+impl<T: Numeric> Matrix3<T> {
+    pub fn identity() -> Self {
+        Self::scaling(T::ONE, T::ONE, T::ONE)
+    }
+
+    pub fn scaling(x: T, y: T, z: T) -> Self {
+        Matrix3([
+            [x, T::ZERO, T::ZERO],
+            [T::ZERO, y, T::ZERO],
+            [T::ZERO, T::ZERO, z],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let m = &self.0;
+        let mut result = self.0;
+
+        for (row, values) in result.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = m[column][row];
+            }
+        }
+
+        Matrix3(result)
+    }
+
+    pub fn determinant(&self) -> T {
+        let m = &self.0;
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn transform(&self, point: Point<T>) -> Point<T> {
+        let m = &self.0;
+
+        Point(
+            m[0][0] * point.0 + m[0][1] * point.1 + m[0][2] * point.2,
+            m[1][0] * point.0 + m[1][1] * point.1 + m[1][2] * point.2,
+            m[2][0] * point.0 + m[2][1] * point.1 + m[2][2] * point.2,
+        )
+    }
+}
+
+// Rotations are counter-clockwise by `angle` radians when looking down the axis towards the origin
+impl<T: Float> Matrix3<T> {
+    pub fn rotation_x(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        Matrix3([[one, zero, zero], [zero, cos, -sin], [zero, sin, cos]])
+    }
+
+    pub fn rotation_y(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        Matrix3([[cos, zero, sin], [zero, one, zero], [-sin, zero, cos]])
+    }
+
+    pub fn rotation_z(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        Matrix3([[cos, -sin, zero], [sin, cos, zero], [zero, zero, one]])
+    }
+
+    // Rotation around an arbitrary axis (Rodrigues' rotation formula), None if the axis is the zero vector
+    pub fn rotation(axis: Point<T>, angle: T) -> Option<Self> {
+        let Point(x, y, z) = axis.normalize()?;
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let t = T::ONE - cos;
+
+        Some(Matrix3([
+            [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y],
+            [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x],
+            [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos],
+        ]))
+    }
+}
+
+impl<T: Numeric> Mul for Matrix3<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut result = [[T::ZERO; 3]; 3];
+
+        for (row, values) in result.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                for k in 0..3 {
+                    *value = *value + self.0[row][k] * other.0[k][column];
+                }
+            }
+        }
+
+        Matrix3(result)
+    }
+}
+
+impl<T: Numeric> Mul<Point<T>> for Matrix3<T> {
+    type Output = Point<T>;
+
+    fn mul(self, point: Point<T>) -> Point<T> {
+        self.transform(point)
+    }
+}
+
+impl<T: Numeric> Matrix4<T> {
+    pub fn identity() -> Self {
+        Matrix3::identity().into()
+    }
+
+    pub fn translation(x: T, y: T, z: T) -> Self {
+        let mut result = Self::identity();
+        result.0[0][3] = x;
+        result.0[1][3] = y;
+        result.0[2][3] = z;
+        result
+    }
+
+    pub fn scaling(x: T, y: T, z: T) -> Self {
+        Matrix3::scaling(x, y, z).into()
+    }
+
+    // Transforms a position, so the translation part applies (w = 1)
+    // Affine transforms are assumed, the resulting w component is not divided out
+    pub fn transform_point(&self, point: Point<T>) -> Point<T> {
+        let m = &self.0;
+
+        Point(
+            m[0][0] * point.0 + m[0][1] * point.1 + m[0][2] * point.2 + m[0][3],
+            m[1][0] * point.0 + m[1][1] * point.1 + m[1][2] * point.2 + m[1][3],
+            m[2][0] * point.0 + m[2][1] * point.1 + m[2][2] * point.2 + m[2][3],
+        )
+    }
+
+    // Transforms a direction, so the translation part is ignored (w = 0)
+    pub fn transform_vector(&self, vector: Point<T>) -> Point<T> {
+        let m = &self.0;
+
+        Point(
+            m[0][0] * vector.0 + m[0][1] * vector.1 + m[0][2] * vector.2,
+            m[1][0] * vector.0 + m[1][1] * vector.1 + m[1][2] * vector.2,
+            m[2][0] * vector.0 + m[2][1] * vector.1 + m[2][2] * vector.2,
+        )
+    }
+}
+
+impl<T: Float> Matrix4<T> {
+    pub fn rotation_x(angle: T) -> Self {
+        Matrix3::rotation_x(angle).into()
+    }
+
+    pub fn rotation_y(angle: T) -> Self {
+        Matrix3::rotation_y(angle).into()
+    }
+
+    pub fn rotation_z(angle: T) -> Self {
+        Matrix3::rotation_z(angle).into()
+    }
+}
+
+// Embeds a linear transform into homogeneous coordinates
+impl<T: Numeric> From<Matrix3<T>> for Matrix4<T> {
+    fn from(linear: Matrix3<T>) -> Self {
+        let mut result = [[T::ZERO; 4]; 4];
+
+        for (row, values) in linear.0.iter().enumerate() {
+            result[row][..3].copy_from_slice(values);
+        }
+        result[3][3] = T::ONE;
+
+        Matrix4(result)
+    }
+}
+
+// Matrices compose right to left: (a * b) applies b first, then a
+impl<T: Numeric> Mul for Matrix4<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut result = [[T::ZERO; 4]; 4];
+
+        for (row, values) in result.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                for k in 0..4 {
+                    *value = *value + self.0[row][k] * other.0[k][column];
+                }
+            }
+        }
+
+        Matrix4(result)
+    }
+}
+
+impl<T: Numeric> Mul<Point<T>> for Matrix4<T> {
+    type Output = Point<T>;
+
+    fn mul(self, point: Point<T>) -> Point<T> {
+        self.transform_point(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close(actual: Point<f64>, expected: Point<f64>) {
+        assert!(
+            actual.distance(&expected) < 1e-9,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn rotations_by_a_quarter_turn() {
+        assert_close(
+            Matrix3::rotation_z(FRAC_PI_2) * Point(1.0, 0.0, 0.0),
+            Point(0.0, 1.0, 0.0),
+        );
+        assert_close(
+            Matrix3::rotation_x(FRAC_PI_2) * Point(0.0, 1.0, 0.0),
+            Point(0.0, 0.0, 1.0),
+        );
+        assert_close(
+            Matrix3::rotation_y(FRAC_PI_2) * Point(0.0, 0.0, 1.0),
+            Point(1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn axis_rotation_matches_axis_aligned_rotation() {
+        let around_z = Matrix3::rotation(Point(0.0, 0.0, 2.0), 0.3).unwrap();
+        let p = Point(1.0, 2.0, 3.0);
+
+        assert_close(around_z * p, Matrix3::rotation_z(0.3) * p);
+        assert_eq!(Matrix3::rotation(Point(0.0, 0.0, 0.0), 0.3), None);
+    }
+
+    #[test]
+    fn translation_only_moves_points() {
+        let m = Matrix4::translation(1, 2, 3);
+
+        assert_eq!(m * Point(1, 1, 1), Point(2, 3, 4));
+        assert_eq!(m.transform_vector(Point(1, 1, 1)), Point(1, 1, 1));
+    }
+
+    #[test]
+    fn composition_applies_right_to_left() {
+        let scale_then_move = Matrix4::translation(10, 0, 0) * Matrix4::scaling(2, 2, 2);
+
+        assert_eq!(scale_then_move * Point(1, 1, 1), Point(12, 2, 2));
+        assert_eq!(Matrix4::identity() * scale_then_move, scale_then_move);
+    }
+
+    #[test]
+    fn determinant_and_transpose() {
+        let m = Matrix3([[1, 2, 3], [0, 1, 4], [5, 6, 0]]);
+
+        assert_eq!(m.determinant(), 1);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(Matrix3::scaling(2, 3, 4).determinant(), 24);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::generics::numeric::{Float, Numeric};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// A point (or vector) in 3D space, generic over the numeric type of its coordinates
+// The tuple struct fields are (x, y, z)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Point<T>(pub T, pub T, pub T);
+
+impl<T: Numeric> Point<T> {
+    pub fn origin() -> Self {
+        Point(T::ZERO, T::ZERO, T::ZERO)
+    }
+
+    pub fn x(&self) -> T {
+        self.0
+    }
+
+    pub fn y(&self) -> T {
+        self.1
+    }
+
+    pub fn z(&self) -> T {
+        self.2
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+
+    // For unsigned coordinate types the cross product can underflow, prefer signed or float types
+    pub fn cross(&self, other: &Self) -> Self {
+        Point(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    pub fn to_f64(self) -> Point<f64> {
+        Point(self.0.to_f64(), self.1.to_f64(), self.2.to_f64())
+    }
+}
+
+// Length, normalization and distance need a square root, so they are only available for float coordinates
+// Integer points can be converted first with `to_f64`
+impl<T: Float> Point<T> {
+    pub fn length(&self) -> T {
+        self.length_squared().sqrt()
+    }
+
+    // The zero vector has no direction and cannot be normalized
+    pub fn normalize(&self) -> Option<Self> {
+        let length = self.length();
+
+        if length <= T::EPSILON {
+            None
+        } else {
+            Some(Point(self.0 / length, self.1 / length, self.2 / length))
+        }
+    }
+
+    pub fn distance(&self, other: &Self) -> T {
+        (*self - *other).length()
+    }
+}
+
+// Operator overloading by implementing the traits from std::ops
+impl<T: Numeric> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+impl<T: Numeric> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
+impl<T: Numeric> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Numeric> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+// Scaling by a scalar: point * factor
+impl<T: Numeric> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, factor: T) -> Self {
+        Point(self.0 * factor, self.1 * factor, self.2 * factor)
+    }
+}
+
+impl<T: Numeric + Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point(-self.0, -self.1, -self.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators_work_on_integers() {
+        let a = Point(1, 2, 3);
+        let b = Point(4, 5, 6);
+
+        assert_eq!(a + b, Point(5, 7, 9));
+        assert_eq!(b - a, Point(3, 3, 3));
+        assert_eq!(a * 2, Point(2, 4, 6));
+        assert_eq!(-a, Point(-1, -2, -3));
+    }
+
+    #[test]
+    fn dot_and_cross_products() {
+        let x = Point(1, 0, 0);
+        let y = Point(0, 1, 0);
+
+        assert_eq!(x.dot(&y), 0);
+        assert_eq!(x.cross(&y), Point(0, 0, 1));
+        assert_eq!(y.cross(&x), Point(0, 0, -1));
+        assert_eq!(Point(1, 2, 3).dot(&Point(4, 5, 6)), 32);
+    }
+
+    #[test]
+    fn length_normalize_and_distance() {
+        let p = Point(3.0, 4.0, 0.0);
+
+        assert_eq!(p.length(), 5.0);
+        assert_eq!(p.normalize(), Some(Point(0.6, 0.8, 0.0)));
+        assert_eq!(Point::<f64>::origin().normalize(), None);
+        assert_eq!(Point(1.0, 1.0, 1.0).distance(&Point(1.0, 1.0, 3.0)), 2.0);
+        assert_eq!(Point(1, 2, 2).to_f64().length(), 3.0);
+    }
+}
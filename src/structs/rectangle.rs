@@ -1,3 +1,5 @@
+use crate::generics::shapes::Shape;
+
 #[derive(Debug)]
 pub struct Rectangle {
     pub width: u32,
@@ -27,3 +29,14 @@ impl Rectangle {
         }
     }
 }
+
+// Implementing the common `Shape` trait lets rectangles be used together with polygons
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        f64::from(self.width) * f64::from(self.height)
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (f64::from(self.width) + f64::from(self.height))
+    }
+}
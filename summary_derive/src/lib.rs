@@ -0,0 +1,240 @@
+// #[derive(Summary)] for hello_rust's Summary trait.
+//
+// Field attributes:
+//     #[summary(author)]                 the field summarize_author returns, exactly one field needs it
+//     #[summary(author, prefix = "@")]   text put in front of the author, e.g. "@horse_ebooks"
+//     #[summary(content)]                the body text condensed by the default summarize
+// Struct attribute:
+//     #[summary(template = "{username}: {content}")]
+//                                        summarize formats the named fields instead of the default summary
+//
+// The generated code refers to the trait as ::hello_rust::Summary.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+#[proc_macro_derive(Summary, attributes(summary))]
+pub fn derive_summary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    author: bool,
+    content: bool,
+    prefix: Option<LitStr>,
+}
+
+fn field_options(field: &syn::Field) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attribute in field.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("author") {
+                options.author = true;
+            } else if meta.path.is_ident("content") {
+                options.content = true;
+            } else if meta.path.is_ident("prefix") {
+                options.prefix = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unknown summary field attribute, expected `author`, `prefix` or `content`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(prefix) = &options.prefix {
+        if !options.author {
+            return Err(Error::new_spanned(
+                prefix,
+                "`prefix` can only be used together with `author`",
+            ));
+        }
+    }
+
+    Ok(options)
+}
+
+fn template(input: &DeriveInput) -> Result<Option<LitStr>> {
+    let mut template = None;
+
+    for attribute in input.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("template") {
+                template = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown summary attribute, expected `template`"))
+            }
+        })?;
+    }
+
+    Ok(template)
+}
+
+// Names used in the placeholders of a format string like "{username}: {content:.40}"
+fn placeholders(template: &LitStr) -> Result<Vec<String>> {
+    let text = template.value();
+    let error = |message: &str| Error::new_spanned(template, message);
+
+    let mut names = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut inside = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inside.push(c),
+                        None => return Err(error("unclosed `{` in template")),
+                    }
+                }
+
+                let name = inside.split(':').next().unwrap_or("").trim();
+                if syn::parse_str::<Ident>(name).is_err() {
+                    return Err(error(
+                        "template placeholders must name a field, like `{content}`",
+                    ));
+                }
+                if !names.iter().any(|existing| existing == name) {
+                    names.push(String::from(name));
+                }
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '}' => {
+                return Err(error(
+                    "unmatched `}` in template, use `}}` for a literal brace",
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(names)
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "#[derive(Summary)] only supports structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "#[derive(Summary)] only supports structs with named fields",
+            ))
+        }
+    };
+
+    let mut author = None;
+    let mut content = None;
+
+    for field in fields {
+        let options = field_options(field)?;
+        let ident = field.ident.as_ref().expect("Named fields have names");
+
+        if options.author {
+            if author.is_some() {
+                return Err(Error::new_spanned(
+                    ident,
+                    "only one field can be marked #[summary(author)]",
+                ));
+            }
+            let prefix = options
+                .prefix
+                .map(|prefix| prefix.value())
+                .unwrap_or_default();
+            author = Some((ident, prefix));
+        }
+
+        if options.content {
+            if content.is_some() {
+                return Err(Error::new_spanned(
+                    ident,
+                    "only one field can be marked #[summary(content)]",
+                ));
+            }
+            content = Some(ident);
+        }
+    }
+
+    let Some((author, prefix)) = author else {
+        return Err(Error::new_spanned(
+            name,
+            "#[derive(Summary)] needs one field marked #[summary(author)]",
+        ));
+    };
+
+    let summarize_content = content.map(|field| {
+        quote! {
+            fn summarize_content(&self) -> ::std::option::Option<&str> {
+                ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&self.#field))
+            }
+        }
+    });
+
+    let summarize = match template(input)? {
+        Some(template) => {
+            let names = placeholders(&template)?;
+
+            for placeholder in &names {
+                if !fields
+                    .iter()
+                    .any(|field| field.ident.as_ref().is_some_and(|i| i == placeholder))
+                {
+                    return Err(Error::new_spanned(
+                        &template,
+                        format!(
+                            "template refers to `{placeholder}`, which isn't a field of `{name}`"
+                        ),
+                    ));
+                }
+            }
+
+            let idents: Vec<Ident> = names.iter().map(|name| format_ident!("{}", name)).collect();
+
+            Some(quote! {
+                fn summarize(&self) -> ::std::string::String {
+                    ::std::format!(#template, #(#idents = self.#idents),*)
+                }
+            })
+        }
+        None => None,
+    };
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::hello_rust::Summary for #name #type_generics #where_clause {
+            fn summarize_author(&self) -> ::std::string::String {
+                ::std::format!("{}{}", #prefix, self.#author)
+            }
+
+            #summarize_content
+
+            #summarize
+        }
+    })
+}
@@ -0,0 +1,53 @@
+// The derive macro is tested from the outside, the way other crates use it
+use hello_rust::Summary;
+
+#[derive(Summary)]
+struct Post {
+    #[summary(author)]
+    writer: String,
+    #[summary(content)]
+    body: String,
+}
+
+#[derive(Summary)]
+#[summary(template = "{username}: {content} ({likes} likes, {{pinned}})")]
+struct Toot<T: std::fmt::Display> {
+    #[summary(author, prefix = "@")]
+    username: String,
+    content: String,
+    likes: T,
+}
+
+#[test]
+fn derived_author_and_content() {
+    let post = Post {
+        writer: String::from("Iceburgh"),
+        body: String::from("The Penguins won. Fans celebrated."),
+    };
+
+    assert_eq!(post.summarize_author(), "Iceburgh");
+    assert_eq!(
+        post.summarize(),
+        "The Penguins won. Fans celebrated. (Read more from Iceburgh...)"
+    );
+}
+
+#[test]
+fn derived_template_and_prefix() {
+    let toot = Toot {
+        username: String::from("horse_ebooks"),
+        content: String::from("of course"),
+        likes: 3,
+    };
+
+    assert_eq!(toot.summarize_author(), "@horse_ebooks");
+    assert_eq!(toot.summarize(), "horse_ebooks: of course (3 likes, {pinned})");
+}
+
+// Each file in tests/ui must fail to compile with the error in the matching .stderr file.
+// Run with TRYBUILD=overwrite to update the expected errors after changing a message.
+#[test]
+fn misuse_fails_to_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,8 @@
+use hello_rust::Summary;
+
+#[derive(Summary)]
+struct Post {
+    body: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hello_rust::Summary;
+
+#[derive(Summary)]
+#[summary(template = "{}: {content}")]
+struct Post {
+    #[summary(author)]
+    username: String,
+    content: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hello_rust::Summary;
+
+#[derive(Summary)]
+struct Post {
+    #[summary(author)]
+    writer: String,
+    #[summary(prefix = "@")]
+    body: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use hello_rust::Summary;
+
+#[derive(Summary)]
+struct Post(String, String);
+
+fn main() {}
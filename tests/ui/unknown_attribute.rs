@@ -0,0 +1,9 @@
+use hello_rust::Summary;
+
+#[derive(Summary)]
+struct Post {
+    #[summary(auther)]
+    writer: String,
+}
+
+fn main() {}